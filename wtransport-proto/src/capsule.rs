@@ -0,0 +1,376 @@
+use crate::bytes::BufferReader;
+use crate::bytes::BytesReader;
+use crate::bytes::BytesWriter;
+use crate::bytes::EndOfBuffer;
+use crate::error::ErrorCode;
+use crate::frame::FrameKind;
+use crate::varint::VarInt;
+use std::borrow::Cow;
+
+/// Known capsule types.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CapsuleKind {
+    /// `DRAIN_WEBTRANSPORT_SESSION` capsule.
+    DrainWebTransportSession,
+}
+
+impl CapsuleKind {
+    const fn parse(id: VarInt) -> Option<Self> {
+        match id {
+            capsule_type_ids::DRAIN_WEBTRANSPORT_SESSION => {
+                Some(CapsuleKind::DrainWebTransportSession)
+            }
+            _ => None,
+        }
+    }
+
+    const fn id(self) -> VarInt {
+        match self {
+            CapsuleKind::DrainWebTransportSession => capsule_type_ids::DRAIN_WEBTRANSPORT_SESSION,
+        }
+    }
+}
+
+/// A capsule (RFC 9297), exchanged on the data stream of a WebTransport session.
+///
+/// On HTTP3, capsules are carried in the payload of [`FrameKind::Data`](crate::frame::FrameKind::Data)
+/// frames of the session stream.
+pub struct Capsule<'a> {
+    type_id: VarInt,
+    payload: Cow<'a, [u8]>,
+}
+
+impl<'a> Capsule<'a> {
+    /// Creates a new `DRAIN_WEBTRANSPORT_SESSION` capsule.
+    #[inline(always)]
+    pub fn new_drain_webtransport_session() -> Self {
+        Self {
+            type_id: CapsuleKind::DrainWebTransportSession.id(),
+            payload: Cow::Owned(Default::default()),
+        }
+    }
+
+    /// Reads a [`Capsule`] from a [`BytesReader`].
+    ///
+    /// It returns [`None`] if the `bytes_reader` does not contain enough bytes
+    /// to parse an entire capsule.
+    ///
+    /// Unknown capsule types are parsed as well (see [`Self::kind`]), as they must be
+    /// skipped by the receiver.
+    ///
+    /// In case [`None`], `bytes_reader` might be partially read.
+    pub fn read<R>(bytes_reader: &mut R) -> Option<Self>
+    where
+        R: BytesReader<'a>,
+    {
+        let type_id = bytes_reader.get_varint()?;
+        let payload_len = bytes_reader.get_varint()?.into_inner() as usize;
+        let payload = bytes_reader.get_bytes(payload_len)?;
+
+        Some(Self {
+            type_id,
+            payload: Cow::Borrowed(payload),
+        })
+    }
+
+    /// Writes a [`Capsule`] into a [`BytesWriter`].
+    ///
+    /// It returns [`Err`] if the `bytes_writer` does not have enough capacity
+    /// to write the entire capsule.
+    /// See [`Self::write_size`] to retrieve the exact amount of required capacity.
+    ///
+    /// In case [`Err`], `bytes_writer` might be partially written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload size if greater than [`VarInt::MAX`].
+    pub fn write<W>(&self, bytes_writer: &mut W) -> Result<(), EndOfBuffer>
+    where
+        W: BytesWriter,
+    {
+        bytes_writer.put_varint(self.type_id)?;
+        bytes_writer.put_varint(
+            VarInt::try_from(self.payload.len() as u64)
+                .expect("Payload cannot be larger than varint max"),
+        )?;
+        bytes_writer.put_bytes(&self.payload)?;
+
+        Ok(())
+    }
+
+    /// Returns the needed capacity to write this capsule.
+    pub fn write_size(&self) -> usize {
+        self.type_id.size()
+            + VarInt::try_from(self.payload.len() as u64)
+                .expect("Payload cannot be larger than varint max")
+                .size()
+            + self.payload.len()
+    }
+
+    /// Returns the [`CapsuleKind`] of this [`Capsule`], or [`None`] if the type is unknown.
+    #[inline(always)]
+    pub fn kind(&self) -> Option<CapsuleKind> {
+        CapsuleKind::parse(self.type_id)
+    }
+
+    /// Returns the type identifier of this [`Capsule`].
+    #[inline(always)]
+    pub fn type_id(&self) -> VarInt {
+        self.type_id
+    }
+
+    /// Returns the payload of this [`Capsule`].
+    #[inline(always)]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+/// Incremental decoder of the capsules received on the session stream.
+///
+/// Stream data is [pushed](Self::push) as it is received, regardless of frame or capsule
+/// boundaries, then capsules are [decoded](Self::decode). Capsules are carried by the
+/// payload of [`FrameKind::Data`] frames, hence they can span multiple frames, and a frame
+/// can carry multiple capsules. Other frames allowed on the session stream are skipped.
+///
+/// Capsules whose payload exceeds [`MAX_PAYLOAD_SIZE`](Self::MAX_PAYLOAD_SIZE) are skipped
+/// without being buffered: no known capsule is that large.
+#[derive(Debug, Default)]
+pub struct CapsuleDecoder {
+    stream_buffer: Vec<u8>,
+    frame: FrameState,
+    capsule_buffer: Vec<u8>,
+    capsule_skip: u64,
+}
+
+#[derive(Debug, Default)]
+enum FrameState {
+    #[default]
+    Header,
+    Data(u64),
+    Skip(u64),
+}
+
+impl CapsuleDecoder {
+    /// Maximum payload size of a buffered capsule.
+    pub const MAX_PAYLOAD_SIZE: usize = 4096;
+
+    /// Creates an empty decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends data received on the stream.
+    pub fn push(&mut self, data: &[u8]) {
+        self.stream_buffer.extend_from_slice(data);
+    }
+
+    /// Extracts the next complete capsule, if any.
+    ///
+    /// Unknown capsule types are returned as well (see [`Capsule::kind`]), as they must be
+    /// skipped by the receiver.
+    ///
+    /// It returns [`Err`] if the stream carries a frame not allowed on the session stream.
+    pub fn decode(&mut self) -> Result<Option<Capsule<'static>>, ErrorCode> {
+        loop {
+            if let Some(capsule) = self.next_capsule() {
+                return Ok(Some(capsule));
+            }
+
+            match self.frame {
+                FrameState::Header => {
+                    let mut reader = BufferReader::new(&self.stream_buffer);
+
+                    let Some(kind_id) = reader.get_varint() else {
+                        return Ok(None);
+                    };
+
+                    let kind = FrameKind::parse(kind_id);
+                    if matches!(
+                        kind,
+                        Some(FrameKind::Settings | FrameKind::GoAway | FrameKind::WebTransport)
+                    ) {
+                        return Err(ErrorCode::FrameUnexpected);
+                    }
+
+                    let Some(payload_len) = reader.get_varint() else {
+                        return Ok(None);
+                    };
+
+                    let header_len = reader.offset();
+                    self.stream_buffer.drain(..header_len);
+
+                    self.frame = match kind {
+                        Some(FrameKind::Data) => FrameState::Data(payload_len.into_inner()),
+                        _ => FrameState::Skip(payload_len.into_inner()),
+                    };
+                }
+                FrameState::Data(remaining) | FrameState::Skip(remaining) if remaining == 0 => {
+                    self.frame = FrameState::Header;
+                }
+                FrameState::Data(remaining) | FrameState::Skip(remaining) => {
+                    if self.stream_buffer.is_empty() {
+                        return Ok(None);
+                    }
+
+                    let length = remaining.min(self.stream_buffer.len() as u64);
+                    let payload = self.stream_buffer.drain(..length as usize);
+
+                    if let FrameState::Data(_) = self.frame {
+                        let skipped = self.capsule_skip.min(length);
+                        self.capsule_skip -= skipped;
+                        self.capsule_buffer.extend(payload.skip(skipped as usize));
+                    } else {
+                        drop(payload);
+                    }
+
+                    match &mut self.frame {
+                        FrameState::Data(remaining) | FrameState::Skip(remaining) => {
+                            *remaining -= length;
+                        }
+                        FrameState::Header => unreachable!(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_capsule(&mut self) -> Option<Capsule<'static>> {
+        loop {
+            if self.capsule_skip > 0 {
+                return None;
+            }
+
+            let mut reader = BufferReader::new(&self.capsule_buffer);
+            let type_id = reader.get_varint()?;
+            let payload_len = reader.get_varint()?.into_inner();
+            let header_len = reader.offset();
+
+            let available = (self.capsule_buffer.len() - header_len) as u64;
+
+            if payload_len > Self::MAX_PAYLOAD_SIZE as u64 {
+                let skipped = available.min(payload_len);
+                self.capsule_buffer.drain(..header_len + skipped as usize);
+                self.capsule_skip = payload_len - skipped;
+                continue;
+            }
+
+            if available < payload_len {
+                return None;
+            }
+
+            let capsule_len = header_len + payload_len as usize;
+            let payload = self.capsule_buffer[header_len..capsule_len].to_vec();
+            self.capsule_buffer.drain(..capsule_len);
+
+            return Some(Capsule {
+                type_id,
+                payload: Cow::Owned(payload),
+            });
+        }
+    }
+}
+
+mod capsule_type_ids {
+    use crate::varint::VarInt;
+
+    pub const DRAIN_WEBTRANSPORT_SESSION: VarInt = VarInt::from_u32(0x78ae);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain() {
+        let capsule = Capsule::new_drain_webtransport_session();
+
+        let mut buffer = Vec::new();
+        capsule.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), capsule.write_size());
+
+        let mut bytes = buffer.as_slice();
+        let capsule = Capsule::read(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(capsule.kind(), Some(CapsuleKind::DrainWebTransportSession));
+        assert!(capsule.payload().is_empty());
+    }
+
+    #[test]
+    fn unknown() {
+        let mut buffer = Vec::new();
+        buffer.put_varint(VarInt::from_u32(0x42)).unwrap();
+        buffer.put_varint(VarInt::from_u32(3)).unwrap();
+        buffer.put_bytes(b"abc").unwrap();
+
+        let capsule = Capsule::read(&mut buffer.as_slice()).unwrap();
+        assert_eq!(capsule.kind(), None);
+        assert_eq!(capsule.type_id(), VarInt::from_u32(0x42));
+        assert_eq!(capsule.payload(), b"abc");
+
+        assert!(Capsule::read(&mut &buffer[..buffer.len() - 1]).is_none());
+    }
+
+    fn data_frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        frame.put_varint(VarInt::from_u32(0x00)).unwrap();
+        frame
+            .put_varint(VarInt::try_from(payload.len() as u64).unwrap())
+            .unwrap();
+        frame.put_bytes(payload).unwrap();
+        frame
+    }
+
+    #[test]
+    fn decoder() {
+        let mut drain = Vec::new();
+        Capsule::new_drain_webtransport_session()
+            .write(&mut drain)
+            .unwrap();
+
+        let mut large = Vec::new();
+        large.put_varint(VarInt::from_u32(0x42)).unwrap();
+        large
+            .put_varint(VarInt::from_u32(
+                CapsuleDecoder::MAX_PAYLOAD_SIZE as u32 + 1,
+            ))
+            .unwrap();
+        large.resize(large.len() + CapsuleDecoder::MAX_PAYLOAD_SIZE + 1, 0);
+
+        // Large capsule and drain capsule, carried by a DATA frame larger than 4096 bytes.
+        let mut payload = large.clone();
+        payload.extend_from_slice(&drain);
+        let mut stream = data_frame(&payload);
+
+        // Drain capsule split across two DATA frames, with an HEADERS frame in between.
+        stream.extend(data_frame(&drain[..1]));
+        stream.extend([0x01, 0x01, 0xff]);
+        stream.extend(data_frame(&drain[1..]));
+
+        let mut decoder = CapsuleDecoder::new();
+        let mut capsules = Vec::new();
+
+        // Byte by byte: capsules are decoded regardless of how data is received.
+        for byte in stream {
+            decoder.push(&[byte]);
+            while let Some(capsule) = decoder.decode().unwrap() {
+                capsules.push(capsule.kind());
+            }
+        }
+
+        assert_eq!(
+            capsules,
+            [
+                Some(CapsuleKind::DrainWebTransportSession),
+                Some(CapsuleKind::DrainWebTransportSession)
+            ]
+        );
+    }
+
+    #[test]
+    fn decoder_unexpected_frame() {
+        let mut decoder = CapsuleDecoder::new();
+        decoder.push(&[0x04, 0x00]); // SETTINGS
+        assert!(matches!(decoder.decode(), Err(ErrorCode::FrameUnexpected)));
+    }
+}
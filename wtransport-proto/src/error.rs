@@ -4,7 +4,7 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 
 /// HTTP3 protocol errors.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ErrorCode {
     /// H3_DATAGRAM_ERROR.
     Datagram,
@@ -75,6 +75,32 @@ impl ErrorCode {
             ErrorCode::SessionGone => wt_error_codes::WEBTRANSPORT_SESSION_GONE,
         }
     }
+
+    /// Returns the error corresponding to the integer representation (code), if known.
+    pub fn from_code(code: VarInt) -> Option<Self> {
+        let error_code = match code {
+            h3_error_codes::H3_DATAGRAM_ERROR => ErrorCode::Datagram,
+            h3_error_codes::H3_NO_ERROR => ErrorCode::NoError,
+            h3_error_codes::H3_STREAM_CREATION_ERROR => ErrorCode::StreamCreation,
+            h3_error_codes::H3_CLOSED_CRITICAL_STREAM => ErrorCode::ClosedCriticalStream,
+            h3_error_codes::H3_FRAME_UNEXPECTED => ErrorCode::FrameUnexpected,
+            h3_error_codes::H3_FRAME_ERROR => ErrorCode::Frame,
+            h3_error_codes::H3_EXCESSIVE_LOAD => ErrorCode::ExcessiveLoad,
+            h3_error_codes::H3_ID_ERROR => ErrorCode::Id,
+            h3_error_codes::H3_SETTINGS_ERROR => ErrorCode::Settings,
+            h3_error_codes::H3_MISSING_SETTINGS => ErrorCode::MissingSettings,
+            h3_error_codes::H3_REQUEST_REJECTED => ErrorCode::RequestRejected,
+            h3_error_codes::H3_MESSAGE_ERROR => ErrorCode::Message,
+            qpack_error_codes::QPACK_DECOMPRESSION_FAILED => ErrorCode::Decompression,
+            wt_error_codes::WEBTRANSPORT_BUFFERED_STREAM_REJECTED => {
+                ErrorCode::BufferedStreamRejected
+            }
+            wt_error_codes::WEBTRANSPORT_SESSION_GONE => ErrorCode::SessionGone,
+            _ => return None,
+        };
+
+        Some(error_code)
+    }
 }
 
 impl Debug for ErrorCode {
@@ -136,3 +162,35 @@ mod wt_error_codes {
     pub const WEBTRANSPORT_BUFFERED_STREAM_REJECTED: VarInt = VarInt::from_u32(0x3994_bd84);
     pub const WEBTRANSPORT_SESSION_GONE: VarInt = VarInt::from_u32(0x170d_7b68);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_roundtrip() {
+        let error_codes = [
+            ErrorCode::Datagram,
+            ErrorCode::NoError,
+            ErrorCode::StreamCreation,
+            ErrorCode::ClosedCriticalStream,
+            ErrorCode::FrameUnexpected,
+            ErrorCode::Frame,
+            ErrorCode::ExcessiveLoad,
+            ErrorCode::Id,
+            ErrorCode::Settings,
+            ErrorCode::MissingSettings,
+            ErrorCode::RequestRejected,
+            ErrorCode::Message,
+            ErrorCode::Decompression,
+            ErrorCode::BufferedStreamRejected,
+            ErrorCode::SessionGone,
+        ];
+
+        for error_code in error_codes {
+            assert_eq!(ErrorCode::from_code(error_code.to_code()), Some(error_code));
+        }
+
+        assert_eq!(ErrorCode::from_code(VarInt::from_u32(0x42)), None);
+    }
+}
@@ -3,6 +3,7 @@ use crate::bytes::BufferWriter;
 use crate::bytes::BytesReader;
 use crate::bytes::BytesWriter;
 use crate::bytes::EndOfBuffer;
+use crate::error::ErrorCode;
 use crate::ids::InvalidSessionId;
 use crate::ids::SessionId;
 use crate::varint::VarInt;
@@ -77,6 +78,9 @@ pub enum FrameKind {
     /// WebTransport frame type.
     WebTransport,
 
+    /// GOAWAY frame type.
+    GoAway,
+
     /// Exercise frame.
     Exercise(VarInt),
 }
@@ -88,12 +92,13 @@ impl FrameKind {
         id.into_inner() >= 0x21 && ((id.into_inner() - 0x21) % 0x1f == 0)
     }
 
-    const fn parse(id: VarInt) -> Option<Self> {
+    pub(crate) const fn parse(id: VarInt) -> Option<Self> {
         match id {
             frame_kind_ids::DATA => Some(FrameKind::Data),
             frame_kind_ids::HEADERS => Some(FrameKind::Headers),
             frame_kind_ids::SETTINGS => Some(FrameKind::Settings),
             frame_kind_ids::WEBTRANSPORT_STREAM => Some(FrameKind::WebTransport),
+            frame_kind_ids::GOAWAY => Some(FrameKind::GoAway),
             id if FrameKind::is_id_exercise(id) => Some(FrameKind::Exercise(id)),
             _ => None,
         }
@@ -105,6 +110,7 @@ impl FrameKind {
             FrameKind::Headers => frame_kind_ids::HEADERS,
             FrameKind::Settings => frame_kind_ids::SETTINGS,
             FrameKind::WebTransport => frame_kind_ids::WEBTRANSPORT_STREAM,
+            FrameKind::GoAway => frame_kind_ids::GOAWAY,
             FrameKind::Exercise(id) => id,
         }
     }
@@ -120,6 +126,16 @@ pub struct Frame<'a> {
 impl<'a> Frame<'a> {
     const MAX_PARSE_PAYLOAD_ALLOWED: usize = 4096;
 
+    /// Creates a new frame of type [`FrameKind::Data`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `payload` size if greater than [`VarInt::MAX`].
+    #[inline(always)]
+    pub fn new_data(payload: Cow<'a, [u8]>) -> Self {
+        Self::new(FrameKind::Data, payload, None)
+    }
+
     /// Creates a new frame of type [`FrameKind::Headers`].
     ///
     /// # Panics
@@ -150,6 +166,18 @@ impl<'a> Frame<'a> {
         )
     }
 
+    /// Creates a new frame of type [`FrameKind::GoAway`].
+    ///
+    /// `id` is a stream ID (when sent by a server) or a push ID (when sent by a client).
+    pub fn new_goaway(id: VarInt) -> Self {
+        let mut payload = Vec::with_capacity(id.size());
+        payload
+            .put_varint(id)
+            .expect("Vec does not have capacity limit");
+
+        Self::new(FrameKind::GoAway, Cow::Owned(payload), None)
+    }
+
     /// Creates a new frame of type [`FrameKind::Exercise`].
     ///
     /// # Panics
@@ -385,6 +413,24 @@ impl<'a> Frame<'a> {
         })
     }
 
+    /// Returns the identifier carried by a [`FrameKind::GoAway`] frame.
+    ///
+    /// It returns [`ErrorCode::Frame`] if the payload is malformed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not type [`FrameKind::GoAway`].
+    pub fn goaway_id(&self) -> Result<VarInt, ErrorCode> {
+        assert!(matches!(self.kind, FrameKind::GoAway));
+
+        let mut payload = &*self.payload;
+
+        match payload.get_varint() {
+            Some(id) if payload.is_empty() => Ok(id),
+            _ => Err(ErrorCode::Frame),
+        }
+    }
+
     /// # Panics
     ///
     /// Panics if the `payload` size if greater than [`VarInt::MAX`].
@@ -451,6 +497,7 @@ mod frame_kind_ids {
     pub const DATA: VarInt = VarInt::from_u32(0x00);
     pub const HEADERS: VarInt = VarInt::from_u32(0x01);
     pub const SETTINGS: VarInt = VarInt::from_u32(0x04);
+    pub const GOAWAY: VarInt = VarInt::from_u32(0x07);
     pub const WEBTRANSPORT_STREAM: VarInt = VarInt::from_u32(0x41);
 }
 
@@ -552,6 +599,19 @@ mod tests {
         assert!(matches!(frame.kind(), FrameKind::WebTransport));
     }
 
+    #[test]
+    fn goaway() {
+        let frame = Frame::new_goaway(VarInt::from_u32(4));
+        assert!(matches!(frame.kind(), FrameKind::GoAway));
+
+        let frame = utils::assert_serde(frame);
+        assert!(matches!(frame.kind(), FrameKind::GoAway));
+        assert_eq!(frame.goaway_id().unwrap(), VarInt::from_u32(4));
+
+        let frame = Frame::new(FrameKind::GoAway, Cow::Owned(vec![0, 0]), None);
+        assert!(matches!(frame.goaway_id(), Err(ErrorCode::Frame)));
+    }
+
     #[test]
     fn read_eof() {
         let buffer = Frame::serialize_any(FrameKind::Data.id(), b"This is a test payload");
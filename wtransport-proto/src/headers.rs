@@ -50,6 +50,17 @@ impl Headers {
     {
         self.0.insert(key.to_string(), value.to_string());
     }
+
+    /// Returns the size of the field section, as defined by RFC 9114 (Section 4.2.2).
+    ///
+    /// That is, the sum of the uncompressed length of each name and value, plus 32 octets
+    /// of overhead for each field.
+    pub fn field_section_size(&self) -> u64 {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.len() + value.len() + 32) as u64)
+            .sum()
+    }
 }
 
 impl<K, V> FromIterator<(K, V)> for Headers
@@ -112,6 +123,19 @@ mod tests {
         assert_eq!(headers.get("key3"), Some("value3"));
     }
 
+    #[test]
+    fn field_section_size() {
+        let headers = [("key1", "value1"), ("key2", "value2")]
+            .into_iter()
+            .collect::<Headers>();
+
+        assert_eq!(headers.field_section_size(), 2 * (4 + 6 + 32));
+        assert_eq!(
+            Headers::from_iter::<[(&str, &str); 0]>([]).field_section_size(),
+            0
+        );
+    }
+
     #[test]
     fn idempotence() {
         let headers = [("key1", "value1"), ("key2", "value2")]
@@ -265,6 +265,9 @@ impl StatusCode {
     /// HTTP 404 Not Found status code.
     pub const NOT_FOUND: Self = Self(404);
 
+    /// HTTP 431 Request Header Fields Too Large status code.
+    pub const REQUEST_HEADER_FIELDS_TOO_LARGE: Self = Self(431);
+
     /// Tries to construct from `u32`.
     #[inline(always)]
     pub fn try_from_u32(value: u32) -> Result<Self, InvalidStatusCode> {
@@ -164,6 +164,33 @@ impl SessionRequest {
             .expect("Session request must contain ':authority' field")
     }
 
+    /// Sets the `:authority` field of the request, overriding the one parsed from the URL.
+    ///
+    /// No validation is performed on `authority`.
+    pub fn set_authority<V>(&mut self, authority: V)
+    where
+        V: ToString,
+    {
+        self.0.insert(":authority", authority);
+    }
+
+    /// Returns the `:scheme` field of the request.
+    pub fn scheme(&self) -> &str {
+        self.0
+            .get(":scheme")
+            .expect("Session request must contain ':scheme' field")
+    }
+
+    /// Sets the `:scheme` field of the request, overriding the default (`https`).
+    ///
+    /// No validation is performed on `scheme`.
+    pub fn set_scheme<V>(&mut self, scheme: V)
+    where
+        V: ToString,
+    {
+        self.0.insert(":scheme", scheme);
+    }
+
     /// Returns the `:path` field of the request.
     pub fn path(&self) -> &str {
         self.0
@@ -302,6 +329,11 @@ impl SessionResponse {
         Self::with_status_code(StatusCode::NOT_FOUND)
     }
 
+    /// Constructs with [`StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE`].
+    pub fn request_header_fields_too_large() -> Self {
+        Self::with_status_code(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE)
+    }
+
     /// Returns the status code.
     pub fn code(&self) -> StatusCode {
         self.0
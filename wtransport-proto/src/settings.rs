@@ -202,6 +202,12 @@ impl SettingsBuilder {
         self
     }
 
+    /// Sets the maximum size of a field section (headers) the endpoint is willing to accept.
+    pub fn max_field_section_size(mut self, value: VarInt) -> Self {
+        self.0 .0.insert(SettingId::MaxFieldSectionSize, value);
+        self
+    }
+
     /// Enables `CONNECT` method.
     pub fn enable_connect_protocol(mut self) -> Self {
         self.0
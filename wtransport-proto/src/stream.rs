@@ -217,6 +217,7 @@ pub mod biremote {
                 FrameKind::Data => Ok(frame),
                 FrameKind::Headers => Ok(frame),
                 FrameKind::Settings => Err(ErrorCode::FrameUnexpected),
+                FrameKind::GoAway => Err(ErrorCode::FrameUnexpected),
                 FrameKind::WebTransport => {
                     if !first_frame_done {
                         Ok(frame)
@@ -474,6 +475,7 @@ pub mod bilocal {
                 FrameKind::Data => Ok(frame),
                 FrameKind::Headers => Ok(frame),
                 FrameKind::Settings => Err(ErrorCode::FrameUnexpected),
+                FrameKind::GoAway => Err(ErrorCode::FrameUnexpected),
                 FrameKind::WebTransport => Err(ErrorCode::FrameUnexpected),
                 FrameKind::Exercise(_) => Ok(frame),
             }
@@ -721,6 +723,7 @@ pub mod uniremote {
                 FrameKind::Data => Err(ErrorCode::FrameUnexpected),
                 FrameKind::Headers => Err(ErrorCode::FrameUnexpected),
                 FrameKind::Settings => Ok(frame),
+                FrameKind::GoAway => Ok(frame),
                 FrameKind::WebTransport => Err(ErrorCode::FrameUnexpected),
                 FrameKind::Exercise(_) => Ok(frame),
             }
@@ -1043,6 +1046,7 @@ pub mod session {
                 FrameKind::Data => Ok(frame),
                 FrameKind::Headers => Ok(frame),
                 FrameKind::Settings => Err(ErrorCode::FrameUnexpected),
+                FrameKind::GoAway => Err(ErrorCode::FrameUnexpected),
                 FrameKind::WebTransport => Err(ErrorCode::FrameUnexpected),
                 FrameKind::Exercise(_) => Ok(frame),
             }
@@ -0,0 +1,477 @@
+use crate::RecvStream;
+use crate::SendStream;
+use flate2::Compress;
+use flate2::Decompress;
+use flate2::FlushCompress;
+use flate2::FlushDecompress;
+use flate2::Status;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use wtransport_proto::ids::StreamId;
+
+/// Maximum size (in bytes) of data compressed by a single write of a [`CompressedSendStream`].
+const WRITE_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Size (in bytes) of the buffer of compressed data read by a [`CompressedRecvStream`].
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The codec applied to the data of a stream.
+///
+/// The codec is announced by the sender in a single header byte, the first byte of the stream,
+/// so that the receiver does not need to know it in advance.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamCompression {
+    /// No compression: data is sent as is, after the header byte.
+    None,
+
+    /// Raw *DEFLATE* (RFC 1951) compression.
+    Deflate,
+}
+
+impl StreamCompression {
+    fn to_header(self) -> u8 {
+        match self {
+            StreamCompression::None => 0x00,
+            StreamCompression::Deflate => 0x01,
+        }
+    }
+
+    fn from_header(header: u8) -> Option<Self> {
+        match header {
+            0x00 => Some(StreamCompression::None),
+            0x01 => Some(StreamCompression::Deflate),
+            _ => None,
+        }
+    }
+}
+
+/// A [`SendStream`] compressing the data written to it.
+///
+/// The stream starts with a header byte announcing the [`StreamCompression`] codec; the peer
+/// must read it with a [`CompressedRecvStream`].
+///
+/// This is an application-level convention, unrelated to QUIC and WebTransport (neither of
+/// them compresses stream data): both peers must agree on using these adapters for a stream.
+///
+/// Compressed data is buffered: flushing forces the data written so far to be sent, at the
+/// cost of a slightly worse compression ratio. Shutting down terminates the compressed data
+/// and finishes the stream; dropping the stream before may lose buffered data.
+///
+/// # Example
+///
+/// ```no_run
+/// # use wtransport::Connection;
+/// # use anyhow::Result;
+/// use tokio::io::AsyncWriteExt;
+/// use wtransport::compression::CompressedSendStream;
+/// use wtransport::compression::StreamCompression;
+///
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let stream = connection.open_uni().await?.await?;
+/// let mut stream = CompressedSendStream::new(stream, StreamCompression::Deflate);
+///
+/// stream.write_all(b"hello hello hello").await?;
+/// stream.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CompressedSendStream {
+    stream: SendStream,
+    compress: Option<Compress>,
+    output: Vec<u8>,
+    position: usize,
+    flushed: bool,
+    finished: bool,
+}
+
+impl CompressedSendStream {
+    /// Wraps `stream`, compressing its data with `compression`.
+    pub fn new(stream: SendStream, compression: StreamCompression) -> Self {
+        let compress = match compression {
+            StreamCompression::None => None,
+            StreamCompression::Deflate => {
+                Some(Compress::new(flate2::Compression::default(), false))
+            }
+        };
+
+        Self {
+            stream,
+            compress,
+            output: vec![compression.to_header()],
+            position: 0,
+            flushed: true,
+            finished: false,
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &SendStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// Writing to it directly corrupts the compressed data.
+    pub fn get_mut(&mut self) -> &mut SendStream {
+        &mut self.stream
+    }
+
+    /// Returns the [`StreamId`] associated.
+    #[inline(always)]
+    pub fn id(&self) -> StreamId {
+        self.stream.id()
+    }
+
+    /// Writes the pending output to the underlying stream.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while self.position < self.output.len() {
+            let written = std::task::ready!(
+                Pin::new(&mut self.stream).poll_write(cx, &self.output[self.position..])
+            )?;
+
+            if written == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+
+            self.position += written;
+        }
+
+        self.output.clear();
+        self.position = 0;
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Compresses `input` into the pending output.
+    ///
+    /// Unless `flush` is [`FlushCompress::None`], all the compressed data is output.
+    fn compress(&mut self, mut input: &[u8], flush: FlushCompress) -> std::io::Result<()> {
+        let compress = self.compress.as_mut().expect("compressed stream");
+
+        loop {
+            self.output.reserve(input.len() + 1024);
+
+            let total_in = compress.total_in();
+            let status = compress
+                .compress_vec(input, &mut self.output, flush)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+            input = &input[(compress.total_in() - total_in) as usize..];
+
+            if status == Status::StreamEnd
+                || (input.is_empty() && self.output.len() < self.output.capacity())
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl AsyncWrite for CompressedSendStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        std::task::ready!(self.poll_drain(cx))?;
+
+        if self.compress.is_none() {
+            return Pin::new(&mut self.stream).poll_write(cx, buf);
+        }
+
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let buf = &buf[..buf.len().min(WRITE_CHUNK_SIZE)];
+        self.compress(buf, FlushCompress::None)?;
+        self.flushed = false;
+
+        // Data is accepted: a pending output is written by the next write or flush.
+        if let Poll::Ready(Err(error)) = self.poll_drain(cx) {
+            return Poll::Ready(Err(error));
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if !self.flushed {
+            std::task::ready!(self.poll_drain(cx))?;
+            self.compress(&[], FlushCompress::Sync)?;
+            self.flushed = true;
+        }
+
+        std::task::ready!(self.poll_drain(cx))?;
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if !self.finished {
+            if self.compress.is_some() {
+                std::task::ready!(self.poll_drain(cx))?;
+                self.compress(&[], FlushCompress::Finish)?;
+            }
+
+            self.finished = true;
+        }
+
+        std::task::ready!(self.poll_drain(cx))?;
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// A [`RecvStream`] decompressing the data read from it.
+///
+/// The codec is read from the header byte written by a [`CompressedSendStream`].
+/// A stream finished before its header byte yields no data.
+///
+/// # Example
+///
+/// ```no_run
+/// # use wtransport::Connection;
+/// # use anyhow::Result;
+/// use tokio::io::AsyncReadExt;
+/// use wtransport::compression::CompressedRecvStream;
+///
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let stream = connection.accept_uni().await?;
+/// let mut stream = CompressedRecvStream::new(stream);
+///
+/// let mut data = Vec::new();
+/// stream.read_to_end(&mut data).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CompressedRecvStream {
+    stream: RecvStream,
+    state: RecvState,
+}
+
+#[derive(Debug)]
+enum RecvState {
+    Header,
+    Plain,
+    Deflate(Box<Inflater>),
+    Empty,
+}
+
+#[derive(Debug)]
+struct Inflater {
+    decompress: Decompress,
+    input: Box<[u8]>,
+    position: usize,
+    filled: usize,
+    eof: bool,
+    end: bool,
+}
+
+impl Inflater {
+    fn new() -> Self {
+        Self {
+            decompress: Decompress::new(false),
+            input: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+            position: 0,
+            filled: 0,
+            eof: false,
+            end: false,
+        }
+    }
+
+    fn poll_read(
+        &mut self,
+        stream: &mut RecvStream,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        loop {
+            if self.end || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            if self.position < self.filled {
+                let total_in = self.decompress.total_in();
+                let total_out = self.decompress.total_out();
+
+                let status = self
+                    .decompress
+                    .decompress(
+                        &self.input[self.position..self.filled],
+                        buf.initialize_unfilled(),
+                        FlushDecompress::None,
+                    )
+                    .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+                let read = (self.decompress.total_in() - total_in) as usize;
+                let produced = (self.decompress.total_out() - total_out) as usize;
+
+                self.position += read;
+                buf.advance(produced);
+                self.end = status == Status::StreamEnd;
+
+                if produced > 0 || self.end {
+                    return Poll::Ready(Ok(()));
+                }
+
+                if read > 0 {
+                    continue;
+                }
+            }
+
+            if self.eof {
+                return Poll::Ready(Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "compressed stream truncated",
+                )));
+            }
+
+            self.input.copy_within(self.position..self.filled, 0);
+            self.filled -= self.position;
+            self.position = 0;
+
+            let mut input_buf = ReadBuf::new(&mut self.input[self.filled..]);
+            std::task::ready!(Pin::new(&mut *stream).poll_read(cx, &mut input_buf))?;
+
+            let read = input_buf.filled().len();
+            self.filled += read;
+            self.eof = read == 0;
+        }
+    }
+}
+
+impl CompressedRecvStream {
+    /// Wraps `stream`, decompressing its data.
+    pub fn new(stream: RecvStream) -> Self {
+        Self {
+            stream,
+            state: RecvState::Header,
+        }
+    }
+
+    /// Returns the codec announced by the peer, once its header byte has been read.
+    pub fn compression(&self) -> Option<StreamCompression> {
+        match self.state {
+            RecvState::Header | RecvState::Empty => None,
+            RecvState::Plain => Some(StreamCompression::None),
+            RecvState::Deflate { .. } => Some(StreamCompression::Deflate),
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &RecvStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// Reading from it directly corrupts the compressed data.
+    pub fn get_mut(&mut self) -> &mut RecvStream {
+        &mut self.stream
+    }
+
+    /// Returns the [`StreamId`] associated.
+    #[inline(always)]
+    pub fn id(&self) -> StreamId {
+        self.stream.id()
+    }
+}
+
+impl AsyncRead for CompressedRecvStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+
+        if let RecvState::Header = this.state {
+            let mut header = [0; 1];
+            let mut header_buf = ReadBuf::new(&mut header);
+            std::task::ready!(Pin::new(&mut this.stream).poll_read(cx, &mut header_buf))?;
+
+            this.state = if header_buf.filled().is_empty() {
+                RecvState::Empty
+            } else {
+                match StreamCompression::from_header(header[0]) {
+                    Some(StreamCompression::None) => RecvState::Plain,
+                    Some(StreamCompression::Deflate) => {
+                        RecvState::Deflate(Box::new(Inflater::new()))
+                    }
+                    None => {
+                        return Poll::Ready(Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "unknown stream compression",
+                        )))
+                    }
+                }
+            };
+        }
+
+        match &mut this.state {
+            RecvState::Header => unreachable!("header is read"),
+            RecvState::Plain => Pin::new(&mut this.stream).poll_read(cx, buf),
+            RecvState::Deflate(inflater) => inflater.poll_read(&mut this.stream, cx, buf),
+            RecvState::Empty => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "self-signed"))]
+mod tests {
+    use super::*;
+    use crate::test_util::connected_pair;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn roundtrip() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let data = b"wtransport compression ".repeat(4096);
+
+        for compression in [StreamCompression::None, StreamCompression::Deflate] {
+            let bytes_sent = client.bytes_sent();
+            let stream = client.open_uni().await.unwrap().await.unwrap();
+            let mut send_stream = CompressedSendStream::new(stream, compression);
+
+            let (first, second) = data.split_at(1000);
+            send_stream.write_all(first).await.unwrap();
+            send_stream.flush().await.unwrap();
+            send_stream.write_all(second).await.unwrap();
+            send_stream.shutdown().await.unwrap();
+
+            let mut recv_stream = CompressedRecvStream::new(server.accept_uni().await.unwrap());
+
+            let mut received = Vec::new();
+            recv_stream.read_to_end(&mut received).await.unwrap();
+
+            assert_eq!(received, data);
+            assert_eq!(recv_stream.compression(), Some(compression));
+
+            let sent = (client.bytes_sent() - bytes_sent) as usize;
+            match compression {
+                StreamCompression::None => assert_eq!(sent, data.len() + 1),
+                StreamCompression::Deflate => assert!(sent < data.len() / 10),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn truncated() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut stream = client.open_uni().await.unwrap().await.unwrap();
+        stream.write_all(&[0x01, 0xca, 0xcd]).await.unwrap();
+        stream.finish().await.unwrap();
+
+        let mut recv_stream = CompressedRecvStream::new(server.accept_uni().await.unwrap());
+
+        let mut received = Vec::new();
+        assert!(recv_stream.read_to_end(&mut received).await.is_err());
+    }
+}
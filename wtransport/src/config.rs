@@ -32,6 +32,7 @@
 //!     .build();
 //! ```
 
+use crate::driver::utils::varint_w2q;
 use crate::Certificate;
 use quinn::ClientConfig as QuicClientConfig;
 use quinn::ServerConfig as QuicServerConfig;
@@ -40,20 +41,40 @@ use rustls::ClientConfig as TlsClientConfig;
 use rustls::RootCertStore;
 use rustls::ServerConfig as TlsServerConfig;
 use std::fmt::Debug;
-use std::fmt::Display;
 use std::future::Future;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::net::SocketAddrV6;
+use std::net::ToSocketAddrs;
+use std::ops::RangeInclusive;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::warn;
+use wtransport_proto::varint::VarInt;
 use wtransport_proto::WEBTRANSPORT_ALPN;
 
+/// Default value of [`ServerConfigBuilder::datagram_receive_buffer_size`] and
+/// [`ClientConfigBuilder::datagram_receive_buffer_size`] (the QUIC stack default).
+pub(crate) const DEFAULT_DATAGRAM_RECEIVE_BUFFER_SIZE: usize = 1_250_000;
+
+/// Unidirectional streams opened by each endpoint for HTTP/3 itself: the control stream,
+/// the QPACK encoder stream and the QPACK decoder stream.
+///
+/// They are granted in addition to the WebTransport streams of the peer.
+pub(crate) const H3_INTERNAL_UNI_STREAMS: u64 = 3;
+
+/// Bidirectional streams opened by the client for HTTP/3 itself: the session stream,
+/// carrying the session request (CONNECT).
+///
+/// It is granted by the server in addition to the WebTransport streams of the client.
+pub(crate) const H3_INTERNAL_BIDI_STREAMS: u64 = 1;
+
 /// Configuration for IP address socket bind.
 #[derive(Debug, Copy, Clone)]
 pub enum IpBindConfig {
@@ -114,9 +135,98 @@ pub enum Ipv6DualStackConfig {
     Allow,
 }
 
+/// Address family preference, applied to the addresses of a resolved domain.
+///
+/// See [`ClientConfigBuilder::address_family_preference`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum AddressFamily {
+    /// Only use IPv4 addresses.
+    Ipv4Only,
+
+    /// Only use IPv6 addresses.
+    Ipv6Only,
+
+    /// Use an IPv4 address if any, otherwise an IPv6 address.
+    PreferV4,
+
+    /// Use an IPv6 address if any, otherwise an IPv4 address.
+    PreferV6,
+
+    /// Use the first address, in the order returned by the resolver.
+    #[default]
+    System,
+}
+
+impl AddressFamily {
+    /// Selects the address to connect to among the resolved `addresses`.
+    ///
+    /// If `ipv4_socket` is `true`, IPv6 addresses cannot be used at all: no address
+    /// is selected for [`AddressFamily::Ipv6Only`].
+    pub(crate) fn select(self, addresses: &[SocketAddr], ipv4_socket: bool) -> Option<SocketAddr> {
+        let first_v4 = || addresses.iter().find(|address| address.is_ipv4());
+        let first_v6 = || addresses.iter().find(|address| address.is_ipv6());
+
+        let address = match self {
+            AddressFamily::Ipv6Only if ipv4_socket => None,
+            _ if ipv4_socket => first_v4(),
+            AddressFamily::Ipv4Only => first_v4(),
+            AddressFamily::Ipv6Only => first_v6(),
+            AddressFamily::PreferV4 => first_v4().or_else(first_v6),
+            AddressFamily::PreferV6 => first_v6().or_else(first_v4),
+            AddressFamily::System => addresses.first(),
+        };
+
+        address.copied()
+    }
+}
+
 /// Invalid idle timeout.
+#[derive(thiserror::Error, Debug)]
+#[error("idle timeout value configuration is invalid")]
 pub struct InvalidIdleTimeout;
 
+/// Invalid maximum UDP payload size.
+#[derive(thiserror::Error, Debug)]
+#[error("max UDP payload size value configuration is out of range")]
+pub struct InvalidMaxUdpPayloadSize;
+
+/// Invalid reordering threshold.
+#[derive(thiserror::Error, Debug)]
+#[error("reordering threshold value configuration is out of range")]
+pub struct InvalidReorderingThreshold;
+
+/// Invalid crypto buffer size.
+#[derive(thiserror::Error, Debug)]
+#[error("crypto buffer size value configuration is out of range")]
+pub struct InvalidCryptoBufferSize;
+
+/// Error loading native root certificates.
+#[derive(thiserror::Error, Debug)]
+pub enum NativeCertsError {
+    /// The system certificate store cannot be read.
+    #[error("cannot load native certificates")]
+    Load(#[source] std::io::Error),
+
+    /// No valid root certificate has been found.
+    #[error("no native root certificate found")]
+    Empty,
+}
+
+/// Error loading root certificates from PEM.
+#[derive(thiserror::Error, Debug)]
+pub enum PemRootCertsError {
+    /// The PEM content is malformed.
+    #[error("invalid PEM")]
+    Pem(#[source] std::io::Error),
+
+    /// No valid certificate has been found.
+    #[error("no valid certificate found in PEM")]
+    Empty,
+}
+
+/// Policy approving client migrations (see [`ServerConfigBuilder::migration_policy`]).
+pub(crate) type MigrationPolicy = Arc<dyn Fn(SocketAddr, SocketAddr) -> bool + Send + Sync>;
+
 /// Server configuration.
 ///
 /// You can create an instance of `ServerConfig` using its builder pattern by calling
@@ -137,6 +247,8 @@ pub struct InvalidIdleTimeout;
 ///
 ///   - [`with_bind_default`](ServerConfigBuilder::with_bind_default): the simplest
 ///     configuration where only the port will be specified.
+///   - [`with_bind_default_v4`](ServerConfigBuilder::with_bind_default_v4): as the previous
+///     one, but binding IPv4 only.
 ///   - [`with_bind_config`](ServerConfigBuilder::with_bind_config): configures
 ///     to bind an address determined by a configuration preset.
 ///   - [`with_bind_address`](ServerConfigBuilder::with_bind_address): configures
@@ -187,6 +299,60 @@ pub struct InvalidIdleTimeout;
 /// - [`max_idle_timeout`](ServerConfigBuilder::max_idle_timeout)
 /// - [`keep_alive_interval`](ServerConfigBuilder::keep_alive_interval)
 /// - [`allow_migration`](ServerConfigBuilder::allow_migration)
+/// - [`migration_policy`](ServerConfigBuilder::migration_policy)
+/// - [`migration_rate_limit`](ServerConfigBuilder::migration_rate_limit)
+/// - [`handshake_timeout`](ServerConfigBuilder::handshake_timeout)
+/// - [`session_setup_timeout`](ServerConfigBuilder::session_setup_timeout)
+/// - [`max_field_section_size`](ServerConfigBuilder::max_field_section_size)
+/// - [`max_request_headers`](ServerConfigBuilder::max_request_headers)
+/// - [`max_request_header_bytes`](ServerConfigBuilder::max_request_header_bytes)
+/// - [`allowed_origins`](ServerConfigBuilder::allowed_origins)
+/// - [`max_concurrent_connections`](ServerConfigBuilder::max_concurrent_connections)
+/// - [`max_connection_lifetime`](ServerConfigBuilder::max_connection_lifetime)
+/// - [`max_connection_receive_memory`](ServerConfigBuilder::max_connection_receive_memory)
+/// - [`datagram_receive_buffer_size`](ServerConfigBuilder::datagram_receive_buffer_size)
+/// - [`max_udp_payload_size`](ServerConfigBuilder::max_udp_payload_size)
+/// - [`initial_rtt`](ServerConfigBuilder::initial_rtt)
+/// - [`packet_reordering_threshold`](ServerConfigBuilder::packet_reordering_threshold)
+/// - [`time_reordering_threshold`](ServerConfigBuilder::time_reordering_threshold)
+/// - [`crypto_buffer_size`](ServerConfigBuilder::crypto_buffer_size)
+/// - [`initial_max_bidi_streams`](ServerConfigBuilder::initial_max_bidi_streams)
+/// - [`initial_max_uni_streams`](ServerConfigBuilder::initial_max_uni_streams)
+/// - [`enable_gso`](ServerConfigBuilder::enable_gso)
+/// - (Linux only) `bind_device`
+///
+/// #### Timeouts:
+///
+/// A connection is subject to different timeouts, depending on its phase:
+///
+/// 1. *Handshake*: from the first packet received from the client until the QUIC/TLS
+///    handshake completes. This phase is bounded by
+///    [`handshake_timeout`](ServerConfigBuilder::handshake_timeout), regardless of the
+///    activity of the client. A short value reaps half-open connections quickly.
+/// 2. *Established*: once the handshake completes. The connection is closed after
+///    [`max_idle_timeout`](ServerConfigBuilder::max_idle_timeout) of inactivity (the minimum
+///    of the local and peer values), and
+///    [`keep_alive_interval`](ServerConfigBuilder::keep_alive_interval) can prevent it.
+///
+/// The idle timeout also applies during the handshake, so the handshake timeout is only
+/// effective if it is shorter than the idle timeout.
+///
+/// ```
+/// # use anyhow::Result;
+/// use wtransport::ServerConfig;
+/// use wtransport::Certificate;
+/// use std::time::Duration;
+///
+/// # async fn run() -> Result<()> {
+/// let server_config = ServerConfig::builder()
+///     .with_bind_default(443)
+///     .with_certificate(Certificate::load("cert.pem", "key.pem").await?)
+///     .handshake_timeout(Some(Duration::from_secs(2)))
+///     .max_idle_timeout(Some(Duration::from_secs(300)))?
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
 ///
 /// #### Examples:
 /// ```
@@ -207,6 +373,19 @@ pub struct ServerConfig {
     pub(crate) bind_address: SocketAddr,
     pub(crate) dual_stack_config: Ipv6DualStackConfig,
     pub(crate) quic_config: QuicServerConfig,
+    pub(crate) handshake_timeout: Option<Duration>,
+    pub(crate) session_setup_timeout: Option<Duration>,
+    pub(crate) max_field_section_size: Option<VarInt>,
+    pub(crate) max_request_headers: Option<usize>,
+    pub(crate) max_request_header_bytes: Option<usize>,
+    pub(crate) allowed_origins: Option<Arc<[String]>>,
+    pub(crate) max_connection_lifetime: Option<Duration>,
+    pub(crate) receive_window: VarInt,
+    pub(crate) datagram_receive_buffer_size: usize,
+    pub(crate) endpoint_config: quinn::EndpointConfig,
+    pub(crate) bind_device: Option<Vec<u8>>,
+    pub(crate) migration_policy: Option<MigrationPolicy>,
+    pub(crate) migration_rate_limit: Option<(u32, Duration)>,
 }
 
 impl ServerConfig {
@@ -257,6 +436,9 @@ impl ServerConfigBuilder<states::WantsBindAddress> {
     /// `listening_port` is the port where the server will accept incoming connections.
     ///
     /// This is equivalent to: [`Self::with_bind_config`] with [`IpBindConfig::InAddrAnyDual`].
+    ///
+    /// **Note**: binding fails on hosts where IPv6 is disabled. In that case, use
+    /// [`Self::with_bind_default_v4`].
     pub fn with_bind_default(
         self,
         listening_port: u16,
@@ -264,6 +446,18 @@ impl ServerConfigBuilder<states::WantsBindAddress> {
         self.with_bind_config(IpBindConfig::InAddrAnyDual, listening_port)
     }
 
+    /// Configures for accepting incoming connections binding ANY IPv4 address (no IPv6).
+    ///
+    /// `listening_port` is the port where the server will accept incoming connections.
+    ///
+    /// This is equivalent to: [`Self::with_bind_config`] with [`IpBindConfig::InAddrAnyV4`].
+    pub fn with_bind_default_v4(
+        self,
+        listening_port: u16,
+    ) -> ServerConfigBuilder<states::WantsCertificate> {
+        self.with_bind_config(IpBindConfig::InAddrAnyV4, listening_port)
+    }
+
     /// Sets the binding (local) socket address with a specific [`IpBindConfig`].
     ///
     /// `listening_port` is the port where the server will accept incoming connections.
@@ -345,6 +539,9 @@ impl ServerConfigBuilder<states::WantsCertificate> {
     /// Generally, it is recommended to use the [`with_certificate`](Self::with_certificate) method
     /// to configure TLS with safe defaults and a [`Certificate`].
     ///
+    /// **Note**: 0-RTT is not supported, hence `max_early_data_size` is reset to `0`: clients
+    /// always complete a full handshake, and no replayable early data reaches the application.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -377,6 +574,20 @@ impl ServerConfigBuilder<states::WantsCertificate> {
             tls_config,
             transport_config,
             migration: true,
+            migration_policy: None,
+            migration_rate_limit: None,
+            handshake_timeout: None,
+            session_setup_timeout: None,
+            max_field_section_size: None,
+            max_request_headers: None,
+            max_request_header_bytes: None,
+            allowed_origins: None,
+            max_concurrent_connections: None,
+            max_connection_lifetime: None,
+            receive_window: VarInt::MAX,
+            datagram_receive_buffer_size: DEFAULT_DATAGRAM_RECEIVE_BUFFER_SIZE,
+            endpoint_config: quinn::EndpointConfig::default(),
+            bind_device: None,
         })
     }
 
@@ -404,15 +615,35 @@ impl ServerConfigBuilder<states::WantsCertificate> {
 impl ServerConfigBuilder<states::WantsTransportConfigServer> {
     /// Completes configuration process.
     #[must_use]
-    pub fn build(self) -> ServerConfig {
+    pub fn build(mut self) -> ServerConfig {
+        // Streams opened in 0-RTT would be accepted as regular streams (see `with_custom_tls`)
+        self.0.tls_config.max_early_data_size = 0;
+
         let mut quic_config = QuicServerConfig::with_crypto(Arc::new(self.0.tls_config));
         quic_config.transport_config(Arc::new(self.0.transport_config));
         quic_config.migration(self.0.migration);
 
+        if let Some(max) = self.0.max_concurrent_connections {
+            quic_config.concurrent_connections(max);
+        }
+
         ServerConfig {
             bind_address: self.0.bind_address,
             dual_stack_config: self.0.dual_stack_config,
             quic_config,
+            handshake_timeout: self.0.handshake_timeout,
+            session_setup_timeout: self.0.session_setup_timeout,
+            max_field_section_size: self.0.max_field_section_size,
+            max_request_headers: self.0.max_request_headers,
+            max_request_header_bytes: self.0.max_request_header_bytes,
+            allowed_origins: self.0.allowed_origins,
+            max_connection_lifetime: self.0.max_connection_lifetime,
+            receive_window: self.0.receive_window,
+            datagram_receive_buffer_size: self.0.datagram_receive_buffer_size,
+            endpoint_config: self.0.endpoint_config,
+            bind_device: self.0.bind_device,
+            migration_policy: self.0.migration_policy,
+            migration_rate_limit: self.0.migration_rate_limit,
         }
     }
 
@@ -449,6 +680,91 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
         self
     }
 
+    /// Whether to use UDP generic segmentation offload (GSO) when sending.
+    ///
+    /// With GSO, a batch of packets is passed to the kernel (or to the network interface) in a
+    /// single call, which greatly reduces the CPU cost of bulk transfers. It is only supported
+    /// on Linux: on other platforms, this option has no effect.
+    ///
+    /// Enabled by default. If the network interface driver does not support it, the first
+    /// packets sent fail, and GSO is automatically disabled for the socket (this can cause
+    /// spurious packet loss at startup). Disable it where offloads are known to be unavailable
+    /// or harmful (e.g., with some packet inspection tools).
+    ///
+    /// **Note**: generic receive offload (GRO) is always enabled opportunistically on Linux,
+    /// and it is transparently unused where unsupported.
+    pub fn enable_gso(mut self, enabled: bool) -> Self {
+        self.0.transport_config.enable_segmentation_offload(enabled);
+        self
+    }
+
+    /// Maximum reordering, in packets, tolerated before a packet is declared lost.
+    ///
+    /// A packet is considered lost once a packet sent this many packets later has been
+    /// acknowledged. On paths reordering packets (e.g., some wireless or satellite links),
+    /// a higher threshold avoids spurious retransmissions and congestion window reductions,
+    /// at the cost of slower recovery of actually lost packets. Unacknowledged packets are
+    /// tracked longer, which slightly increases memory usage.
+    ///
+    /// The value must be at least `3` (RFC 9002), which is the default.
+    ///
+    /// **Note**: the number of ACK ranges tracked by the receiver is not configurable.
+    pub fn packet_reordering_threshold(
+        mut self,
+        packets: u32,
+    ) -> Result<Self, InvalidReorderingThreshold> {
+        if packets < 3 {
+            return Err(InvalidReorderingThreshold);
+        }
+
+        self.0.transport_config.packet_threshold(packets);
+
+        Ok(self)
+    }
+
+    /// Maximum reordering, in time, tolerated before a packet is declared lost, as a factor
+    /// of the round-trip time.
+    ///
+    /// A packet is considered lost once this fraction of the RTT has elapsed since a later
+    /// packet was acknowledged. As for
+    /// [`packet_reordering_threshold`](Self::packet_reordering_threshold), a higher value
+    /// tolerates more reordering, but delays loss recovery.
+    ///
+    /// The value must be finite and at least `1.0`. Defaults to `1.125` (RFC 9002).
+    pub fn time_reordering_threshold(
+        mut self,
+        factor: f32,
+    ) -> Result<Self, InvalidReorderingThreshold> {
+        if !factor.is_finite() || factor < 1.0 {
+            return Err(InvalidReorderingThreshold);
+        }
+
+        self.0.transport_config.time_threshold(factor);
+
+        Ok(self)
+    }
+
+    /// Maximum amount of out-of-order cryptographic handshake data buffered, in bytes.
+    ///
+    /// Handshake messages (e.g., large certificate chains) received out of order beyond this
+    /// amount cause the connection to be closed with a protocol error.
+    ///
+    /// The value must be at least `1200` bytes (the minimum QUIC packet size). Defaults to
+    /// `16 KiB`.
+    ///
+    /// **Note**: once established, connections update their traffic keys automatically,
+    /// before reaching the confidentiality limit of the negotiated cipher
+    /// (2<sup>23</sup> packets for AES-GCM, RFC 9001). This threshold is not configurable.
+    pub fn crypto_buffer_size(mut self, bytes: usize) -> Result<Self, InvalidCryptoBufferSize> {
+        if bytes < 1200 {
+            return Err(InvalidCryptoBufferSize);
+        }
+
+        self.0.transport_config.crypto_buffer_size(bytes);
+
+        Ok(self)
+    }
+
     /// Whether to allow clients to migrate to new addresses.
     ///
     /// Improves behavior for clients that move between different internet connections or suffer NAT
@@ -457,6 +773,352 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
         self.0.migration = value;
         self
     }
+
+    /// Sets a policy approving or denying client migrations.
+    ///
+    /// When a client address changes, `policy` is invoked with the previous and the new
+    /// address (normalized, see [`normalize_addr`](crate::endpoint::normalize_addr)). If it
+    /// returns `false`, the connection is closed with [`MIGRATION_DENIED_ERROR_CODE`]. This
+    /// allows, for instance, denying migrations to untrusted address ranges, while keeping
+    /// legitimate NAT rebinding working.
+    ///
+    /// The address change is detected as soon as the QUIC stack sends the first packet on
+    /// the new path (i.e., the path validation challenge): the denied path does not receive
+    /// further application data.
+    ///
+    /// **Note**: the policy must be set in the configuration the endpoint is created with
+    /// (see [`Endpoint::reload_config`](crate::Endpoint::reload_config)). It has no effect if
+    /// migration is disabled (see [`allow_migration`](Self::allow_migration)).
+    ///
+    /// [`MIGRATION_DENIED_ERROR_CODE`]: crate::connection::MIGRATION_DENIED_ERROR_CODE
+    pub fn migration_policy<F>(mut self, policy: F) -> Self
+    where
+        F: Fn(SocketAddr, SocketAddr) -> bool + Send + Sync + 'static,
+    {
+        self.0.migration_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Limits the rate of migrations of each connection to `max` within `period`.
+    ///
+    /// Every migration makes the QUIC stack validate the new path: a client (or an attacker
+    /// rewriting its packets) migrating too often is closed with
+    /// [`MIGRATION_DENIED_ERROR_CODE`], as if its migration was denied. Only migrations
+    /// approved by the [`migration_policy`](Self::migration_policy), if any, are counted.
+    ///
+    /// **Note**: as the policy, the limit must be set in the configuration the endpoint is
+    /// created with, and it has no effect if migration is disabled.
+    ///
+    /// [`MIGRATION_DENIED_ERROR_CODE`]: crate::connection::MIGRATION_DENIED_ERROR_CODE
+    pub fn migration_rate_limit(mut self, max: u32, period: Duration) -> Self {
+        self.0.migration_rate_limit = Some((max, period));
+        self
+    }
+
+    /// Maximum duration allowed for the QUIC/TLS handshake of an incoming connection.
+    ///
+    /// If the handshake does not complete within this time, the connection is dropped and
+    /// the [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::HandshakeTimedOut`](crate::error::ConnectionError::HandshakeTimedOut).
+    ///
+    /// This is independent of [`max_idle_timeout`](Self::max_idle_timeout), and it allows
+    /// half-open connections to be reaped quickly, while keeping a longer idle timeout for
+    /// established sessions.
+    ///
+    /// `None` to disable, which is the default.
+    pub fn handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.0.handshake_timeout = timeout;
+        self
+    }
+
+    /// Maximum duration allowed for establishing the WebTransport session, once the QUIC
+    /// handshake is complete.
+    ///
+    /// This bounds the HTTP/3 settings exchange and the reception of the session request
+    /// (`CONNECT`): a client completing the QUIC handshake but never sending its request is
+    /// dropped, and the [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::SessionSetupTimedOut`](crate::error::ConnectionError::SessionSetupTimedOut).
+    /// The time taken by the application to [accept](crate::endpoint::SessionRequest::accept)
+    /// the request is not counted.
+    ///
+    /// This is independent of both the [`handshake_timeout`](Self::handshake_timeout) and
+    /// the [`max_idle_timeout`](Self::max_idle_timeout).
+    ///
+    /// `None` to disable, which is the default.
+    pub fn session_setup_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.0.session_setup_timeout = timeout;
+        self
+    }
+
+    /// Maximum size (in bytes) of the HTTP/3 header field section the server accepts.
+    ///
+    /// The limit is advertised to clients (`SETTINGS_MAX_FIELD_SECTION_SIZE`). Session requests
+    /// exceeding it are rejected with a `431` status code, and the
+    /// [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::HeadersTooLarge`](crate::error::ConnectionError::HeadersTooLarge).
+    ///
+    /// The size of a field section is computed as defined by RFC 9114: the sum of the length
+    /// of each name and value, plus 32 bytes for each field.
+    ///
+    /// By default, no limit is applied.
+    pub fn max_field_section_size(mut self, size: u64) -> Self {
+        self.0.max_field_section_size = Some(VarInt::try_from_u64(size).unwrap_or(VarInt::MAX));
+        self
+    }
+
+    /// Maximum number of header fields (pseudo-headers included) of a session request.
+    ///
+    /// Session requests exceeding it are rejected with a `431` status code, and the
+    /// [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::HeadersTooLarge`](crate::error::ConnectionError::HeadersTooLarge).
+    /// Repeated fields with the same name are counted once.
+    ///
+    /// Unlike [`max_field_section_size`](Self::max_field_section_size), the limit is not
+    /// advertised to clients: it is only enforced once the request has been received.
+    ///
+    /// By default, no limit is applied.
+    pub fn max_request_headers(mut self, max: usize) -> Self {
+        self.0.max_request_headers = Some(max);
+        self
+    }
+
+    /// Maximum total size (in bytes) of the header fields of a session request, computed as
+    /// the sum of the length of each name and value.
+    ///
+    /// Session requests exceeding it are rejected with a `431` status code, and the
+    /// [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::HeadersTooLarge`](crate::error::ConnectionError::HeadersTooLarge).
+    ///
+    /// Unlike [`max_field_section_size`](Self::max_field_section_size), the limit is not
+    /// advertised to clients and carries no per-field overhead.
+    ///
+    /// By default, no limit is applied.
+    pub fn max_request_header_bytes(mut self, max: usize) -> Self {
+        self.0.max_request_header_bytes = Some(max);
+        self
+    }
+
+    /// Restricts the origins (`origin` header of the session request) allowed to establish
+    /// a session, e.g., `["https://example.com"]`.
+    ///
+    /// Browsers attach the origin of the page to every WebTransport session request, and any
+    /// web page can attempt to connect to any server. Checking the origin prevents cross-site
+    /// abuse: a malicious page cannot use the browser of a visitor (and its network position)
+    /// to open sessions with the server.
+    ///
+    /// Session requests whose origin is not in the list, or without origin, are rejected with
+    /// a `403` status code before being returned by the endpoint: the
+    /// [`IncomingSession`](crate::endpoint::IncomingSession) resolves with
+    /// [`ConnectionError::OriginNotAllowed`](crate::error::ConnectionError::OriginNotAllowed).
+    /// Scheme and host are compared case-insensitively.
+    ///
+    /// The special entry `"*"` allows every request (including those without origin), which
+    /// is meant for development only.
+    ///
+    /// **Note**: non-browser clients can send any origin, so this does not replace client
+    /// authentication.
+    ///
+    /// By default, all origins are allowed.
+    pub fn allowed_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.0.allowed_origins = Some(origins.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Maximum number of connections the server handles at the same time, whether their
+    /// handshake is in progress or they are established.
+    ///
+    /// The limit is enforced by the QUIC stack on the first packet of a connection: beyond
+    /// it, new connections are refused (with a `CONNECTION_REFUSED` error) before any TLS
+    /// processing, so they cost no handshake CPU. Thus, it bounds the handshake work under a
+    /// flood of initial packets, together with the established sessions.
+    ///
+    /// If address validation (*retry*) is enabled in the QUIC configuration, clients which
+    /// do not complete it never take a slot: spoofed source addresses cannot exhaust the
+    /// limit.
+    ///
+    /// By default, the limit of the QUIC stack applies (`100000`).
+    pub fn max_concurrent_connections(mut self, max: u32) -> Self {
+        self.0.max_concurrent_connections = Some(max);
+        self
+    }
+
+    /// Maximum lifetime of a session, counted from its establishment (see
+    /// [`Connection::established_at`](crate::Connection::established_at)).
+    ///
+    /// Once exceeded, the connection is closed by the server with
+    /// [`LIFETIME_EXCEEDED_ERROR_CODE`](crate::connection::LIFETIME_EXCEEDED_ERROR_CODE),
+    /// whatever its activity. Clients can then reconnect, e.g., in order to re-authenticate
+    /// periodically.
+    ///
+    /// Unlike the [idle timeout](Self::max_idle_timeout), this bounds the lifetime of active
+    /// connections too. Streams still open are aborted: the application can use
+    /// [`Connection::uptime`](crate::Connection::uptime) to wind down beforehand.
+    ///
+    /// By default, no limit is applied.
+    pub fn max_connection_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.0.max_connection_lifetime = lifetime;
+        self
+    }
+
+    /// Maximum amount of received data (in bytes) buffered across all the streams of a
+    /// connection.
+    ///
+    /// This is the connection-level QUIC flow-control window: once the peer has sent this
+    /// many bytes the application has not read yet, it must wait for the application to
+    /// consume some data. No data is dropped; backpressure applies to all streams at once.
+    ///
+    /// The limit applies together with the per-stream receive window (fixed by the QUIC
+    /// stack): a single stream cannot buffer more than the smaller of the two. Thus, it
+    /// bounds the memory used by many streams, each within its own window. It also
+    /// accounts for the internal HTTP/3 streams, so it should not be set too small.
+    ///
+    /// The window can be changed later with
+    /// [`Connection::set_receive_window`](crate::Connection::set_receive_window).
+    /// A finite limit is required for
+    /// [`Connection::pause_incoming`](crate::Connection::pause_incoming) to apply backpressure.
+    ///
+    /// By default, no limit is applied (only per-stream windows).
+    pub fn max_connection_receive_memory(mut self, max: usize) -> Self {
+        let receive_window = VarInt::try_from_u64(max as u64).unwrap_or(VarInt::MAX);
+
+        self.0.receive_window = receive_window;
+        self.0
+            .transport_config
+            .receive_window(varint_w2q(receive_window));
+        self
+    }
+
+    /// Maximum amount of incoming datagrams data (in bytes) buffered, waiting to be read by
+    /// the application.
+    ///
+    /// When the application does not read datagrams fast enough and the buffer is full, the
+    /// oldest buffered datagrams are discarded to make room for new ones. Discarded datagrams
+    /// are counted by [`Connection::datagrams_dropped_rx`](crate::Connection::datagrams_dropped_rx),
+    /// and a warning is logged the first time it happens on a connection.
+    ///
+    /// A good size is the expected incoming datagram rate (in bytes per second) multiplied by
+    /// the longest pause of the application between two reads. The value also bounds the size
+    /// of datagrams the peer is allowed to send, so it should not be smaller than the largest
+    /// expected datagram: the advertised `max_datagram_frame_size` transport parameter is
+    /// the minimum of this value and `65535`. The peer's limit is reflected by
+    /// [`Connection::max_datagram_size`](crate::Connection::max_datagram_size).
+    ///
+    /// Default: 1.25 MB.
+    pub fn datagram_receive_buffer_size(mut self, size: usize) -> Self {
+        self.0.datagram_receive_buffer_size = size;
+        self.0
+            .transport_config
+            .datagram_receive_buffer_size(Some(size));
+        self
+    }
+
+    /// Maximum UDP payload size (in bytes) accepted from the peer.
+    ///
+    /// The value is advertised to the peer (`max_udp_payload_size` transport parameter), and
+    /// it bounds the size of incoming packets, hence the size of the receive buffer allocated
+    /// by the endpoint. It does not affect the size of outgoing packets, which is
+    /// determined by path MTU discovery.
+    ///
+    /// Valid range is from `1200` (the minimum QUIC packet size) to `65527` (the largest UDP
+    /// payload over IPv6). Values outside this range result in an error.
+    ///
+    /// Defaults to `1472`, which is the largest UDP payload that fits in the typical 1500 bytes
+    /// Ethernet MTU. Links with larger MTUs (e.g., loopback) can raise it.
+    ///
+    /// **Note**: other QUIC transport parameters, such as `active_connection_id_limit`, are
+    /// fixed by the QUIC stack and they cannot be configured.
+    pub fn max_udp_payload_size(mut self, size: u16) -> Result<Self, InvalidMaxUdpPayloadSize> {
+        self.0
+            .endpoint_config
+            .max_udp_payload_size(size)
+            .map_err(|_| InvalidMaxUdpPayloadSize)?;
+
+        Ok(self)
+    }
+
+    /// Initial estimate of the round-trip time (RTT), used before any RTT sample is available.
+    ///
+    /// The initial RTT drives the loss-detection timers of the early connection
+    /// (i.e., the handshake): a value much lower than the actual RTT causes spurious
+    /// retransmissions (probe timeouts), while a value much higher delays the recovery
+    /// of actually lost packets. On high-latency links (e.g., satellite, with ~600ms RTT),
+    /// setting a realistic value (e.g., 500ms) avoids spurious retransmissions.
+    ///
+    /// Once RTT samples are available, the estimate converges to the measured value.
+    ///
+    /// Defaults to 333ms, as recommended by RFC 9002.
+    ///
+    /// **Note**: the anti-amplification limit (a server sends at most three times the
+    /// amount of data received from an unvalidated client address) is mandated by QUIC,
+    /// and it cannot be configured.
+    pub fn initial_rtt(mut self, rtt: Duration) -> Self {
+        self.0.transport_config.initial_rtt(rtt);
+        self
+    }
+
+    /// Number of bidirectional streams a client may open right after connecting.
+    ///
+    /// This is advertised in the QUIC handshake, so the client does not have to wait for
+    /// stream credit: for instance, an RPC workload opening several streams just after
+    /// connecting saves a round-trip.
+    ///
+    /// In QUIC, this initial credit is also the concurrency limit: as streams are closed,
+    /// new credit is granted so that at most `max` streams are open at the same time.
+    /// Higher values allow more parallelism, at the cost of server memory for the state and
+    /// buffers of the streams (see also
+    /// [`max_connection_receive_memory`](Self::max_connection_receive_memory)).
+    ///
+    /// Only WebTransport streams are accounted: the stream carrying the session request is
+    /// granted in addition.
+    ///
+    /// By default, 100 streams are granted (internal HTTP/3 streams included).
+    pub fn initial_max_bidi_streams(mut self, max: u32) -> Self {
+        self.0.transport_config.max_concurrent_bidi_streams(
+            quinn::VarInt::from_u64(u64::from(max) + H3_INTERNAL_BIDI_STREAMS)
+                .expect("Value fits varint"),
+        );
+        self
+    }
+
+    /// Number of unidirectional streams a client may open right after connecting.
+    ///
+    /// As for [`initial_max_bidi_streams`](Self::initial_max_bidi_streams), this is also the
+    /// concurrency limit of unidirectional streams.
+    ///
+    /// Only WebTransport streams are accounted: the HTTP/3 control and QPACK streams are
+    /// granted in addition.
+    ///
+    /// By default, 100 streams are granted (internal HTTP/3 streams included).
+    pub fn initial_max_uni_streams(mut self, max: u32) -> Self {
+        self.0.transport_config.max_concurrent_uni_streams(
+            quinn::VarInt::from_u64(u64::from(max) + H3_INTERNAL_UNI_STREAMS)
+                .expect("Value fits varint"),
+        );
+        self
+    }
+
+    /// Binds the socket to a specific network interface (e.g., `"eth0"`, or a VRF device),
+    /// by means of the `SO_BINDTODEVICE` socket option.
+    ///
+    /// Only packets received from the given interface are processed, and packets are sent
+    /// out through it regardless of the routing table. This is commonly used for VRF-based
+    /// network segmentation.
+    ///
+    /// The option is applied on endpoint creation: failures (e.g., unknown interface, or
+    /// missing privileges) are reported as an I/O error at that point.
+    ///
+    /// **Note**: this is Linux-specific. Setting this option usually requires the
+    /// `CAP_NET_RAW` capability.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    pub fn bind_device(mut self, interface: &str) -> Self {
+        self.0.bind_device = Some(interface.as_bytes().to_vec());
+        self
+    }
 }
 
 /// Client configuration.
@@ -479,10 +1141,14 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
 ///
 ///   - [`with_bind_default`](ClientConfigBuilder::with_bind_default): configures to use
 ///     the default bind address. This is generally the *default* choice for a client.
+///   - [`with_bind_default_v4`](ClientConfigBuilder::with_bind_default_v4): as the previous
+///     one, but binding IPv4 only.
 ///   - [`with_bind_config`](ClientConfigBuilder::with_bind_config): configures
 ///     to bind an address determined by a configuration preset.
 ///   - [`with_bind_address`](ClientConfigBuilder::with_bind_address): configures
 ///     to bind a custom specified socket address.
+///   - [`with_bind_port_range`](ClientConfigBuilder::with_bind_port_range): configures
+///     to bind the first available port within a range.
 ///
 /// Only one of these options can be selected during the client configuration process.
 ///
@@ -502,6 +1168,14 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
 /// - [`with_native_certs`](ClientConfigBuilder::with_native_certs): configures to use
 ///   root certificates found in the platform's native certificate store. This is the *default*
 ///   configuration as it uses root store installed on the current machine.
+///   [`try_with_native_certs`](ClientConfigBuilder::try_with_native_certs) does the same,
+///   but fails if no native certificate can be loaded.
+/// - [`with_root_certificates`](ClientConfigBuilder::with_root_certificates): configures to
+///   trust only the supplied root certificates.
+///   [`with_root_certificates_pem`](ClientConfigBuilder::with_root_certificates_pem) loads them
+///   from a PEM bundle.
+/// - [`with_pinned_certificate`](ClientConfigBuilder::with_pinned_certificate): configures
+///   to trust exactly one server certificate.
 /// - [`with_custom_tls`](ClientConfigBuilder::with_custom_tls): sets the TLS client
 ///   configuration manually.
 /// - (**unsafe**) [`with_no_cert_validation`](ClientConfigBuilder::with_no_cert_validation):
@@ -530,6 +1204,21 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
 /// - [`max_idle_timeout`](ClientConfigBuilder::max_idle_timeout)
 /// - [`keep_alive_interval`](ClientConfigBuilder::keep_alive_interval)
 /// - [`dns_resolver`](ClientConfigBuilder::dns_resolver)
+/// - [`dns_resolve_timeout`](ClientConfigBuilder::dns_resolve_timeout)
+/// - [`address_family_preference`](ClientConfigBuilder::address_family_preference)
+/// - [`handshake_timeout`](ClientConfigBuilder::handshake_timeout)
+/// - [`session_setup_timeout`](ClientConfigBuilder::session_setup_timeout)
+/// - [`max_field_section_size`](ClientConfigBuilder::max_field_section_size)
+/// - [`strict_hostname_verification`](ClientConfigBuilder::strict_hostname_verification)
+/// - [`max_connection_receive_memory`](ClientConfigBuilder::max_connection_receive_memory)
+/// - [`datagram_receive_buffer_size`](ClientConfigBuilder::datagram_receive_buffer_size)
+/// - [`max_udp_payload_size`](ClientConfigBuilder::max_udp_payload_size)
+/// - [`initial_rtt`](ClientConfigBuilder::initial_rtt)
+/// - [`packet_reordering_threshold`](ClientConfigBuilder::packet_reordering_threshold)
+/// - [`time_reordering_threshold`](ClientConfigBuilder::time_reordering_threshold)
+/// - [`crypto_buffer_size`](ClientConfigBuilder::crypto_buffer_size)
+/// - [`enable_gso`](ClientConfigBuilder::enable_gso)
+/// - (Linux only) `bind_device`
 ///
 /// #### Examples:
 /// ```
@@ -546,9 +1235,22 @@ impl ServerConfigBuilder<states::WantsTransportConfigServer> {
 /// ```
 pub struct ClientConfig {
     pub(crate) bind_address: SocketAddr,
+    pub(crate) bind_port_range: Option<RangeInclusive<u16>>,
     pub(crate) dual_stack_config: Ipv6DualStackConfig,
     pub(crate) quic_config: QuicClientConfig,
     pub(crate) dns_resolver: Box<dyn DnsResolver + Send + Sync + Unpin>,
+    pub(crate) dns_resolve_timeout: Option<Duration>,
+    pub(crate) address_family: AddressFamily,
+    pub(crate) handshake_timeout: Option<Duration>,
+    pub(crate) session_setup_timeout: Option<Duration>,
+    pub(crate) max_field_section_size: Option<VarInt>,
+    pub(crate) strict_hostname_verification: bool,
+    #[cfg(feature = "dangerous-configuration")]
+    pub(crate) rejected_names: RejectedNames,
+    pub(crate) receive_window: VarInt,
+    pub(crate) datagram_receive_buffer_size: usize,
+    pub(crate) endpoint_config: quinn::EndpointConfig,
+    pub(crate) bind_device: Option<Vec<u8>>,
 }
 
 impl ClientConfig {
@@ -603,10 +1305,22 @@ impl ClientConfigBuilder<states::WantsBindAddress> {
     /// Bind port will be randomly picked.
     ///
     /// This is equivalent to: [`Self::with_bind_config`] with [`IpBindConfig::InAddrAnyDual`].
+    ///
+    /// **Note**: binding fails on hosts where IPv6 is disabled. In that case, use
+    /// [`Self::with_bind_default_v4`].
     pub fn with_bind_default(self) -> ClientConfigBuilder<states::WantsRootStore> {
         self.with_bind_config(IpBindConfig::InAddrAnyDual)
     }
 
+    /// Configures for connecting binding ANY IPv4 address (no IPv6).
+    ///
+    /// Bind port will be randomly picked.
+    ///
+    /// This is equivalent to: [`Self::with_bind_config`] with [`IpBindConfig::InAddrAnyV4`].
+    pub fn with_bind_default_v4(self) -> ClientConfigBuilder<states::WantsRootStore> {
+        self.with_bind_config(IpBindConfig::InAddrAnyV4)
+    }
+
     /// Sets the binding (local) socket address with a specific [`IpBindConfig`].
     ///
     /// Bind port will be randomly picked.
@@ -625,6 +1339,33 @@ impl ClientConfigBuilder<states::WantsBindAddress> {
         }
     }
 
+    /// Sets the binding (local) socket address with a specific [`IpBindConfig`], and a port
+    /// within `ports`.
+    ///
+    /// This complies with firewalls allowing outbound UDP traffic from given source ports
+    /// only. Ports are tried in order when the endpoint is created (see
+    /// [`Endpoint::client`](crate::Endpoint::client)), until one can be bound. If none is
+    /// available (or the range is empty), endpoint creation fails with an error of kind
+    /// [`AddrInUse`](std::io::ErrorKind::AddrInUse).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wtransport::config::IpBindConfig;
+    /// use wtransport::ClientConfig;
+    ///
+    /// ClientConfig::builder().with_bind_port_range(IpBindConfig::InAddrAnyV4, 50000..=50100);
+    /// ```
+    pub fn with_bind_port_range(
+        self,
+        ip_bind_config: IpBindConfig,
+        ports: RangeInclusive<u16>,
+    ) -> ClientConfigBuilder<states::WantsRootStore> {
+        let mut builder = self.with_bind_config(ip_bind_config);
+        builder.0.bind_port_range = Some(ports);
+        builder
+    }
+
     /// Sets the binding (local) socket address for the endpoint.
     pub fn with_bind_address(
         self,
@@ -633,6 +1374,7 @@ impl ClientConfigBuilder<states::WantsBindAddress> {
         ClientConfigBuilder(states::WantsRootStore {
             bind_address: address,
             dual_stack_config: Ipv6DualStackConfig::OsDefault,
+            bind_port_range: None,
         })
     }
 
@@ -647,6 +1389,7 @@ impl ClientConfigBuilder<states::WantsBindAddress> {
         ClientConfigBuilder(states::WantsRootStore {
             bind_address: address.into(),
             dual_stack_config,
+            bind_port_range: None,
         })
     }
 }
@@ -658,8 +1401,148 @@ impl ClientConfigBuilder<states::WantsRootStore> {
     /// ensuring that your client can trust certificates signed by well-known authorities.
     ///
     /// It configures safe default TLS configuration.
+    ///
+    /// **Note**: if native certificates cannot be loaded, or none is found, a warning is
+    /// logged and the root store is left empty: every server certificate will fail validation.
+    /// Use [`try_with_native_certs`](Self::try_with_native_certs) to handle this as an error.
     pub fn with_native_certs(self) -> ClientConfigBuilder<states::WantsTransportConfigClient> {
-        self.with_custom_tls(Self::build_tls_config(Self::native_cert_store()))
+        let root_store = Self::native_cert_store().unwrap_or_else(|error| {
+            warn!("Native root certificates not loaded: {error}");
+            RootCertStore::empty()
+        });
+
+        self.with_root_certificates(root_store)
+    }
+
+    /// Like [`with_native_certs`](Self::with_native_certs), but it fails if no native root
+    /// certificate can be loaded.
+    ///
+    /// This turns a misconfigured system certificate store into an error at configuration
+    /// time, rather than handshake failures later on.
+    pub fn try_with_native_certs(
+        self,
+    ) -> Result<ClientConfigBuilder<states::WantsTransportConfigClient>, NativeCertsError> {
+        Ok(self.with_root_certificates(Self::native_cert_store()?))
+    }
+
+    /// Configures the client to trust exactly the root certificates in `root_store` for
+    /// server validation (e.g., an internal CA bundle).
+    ///
+    /// Native root certificates are not loaded. As [`with_native_certs`](Self::with_native_certs),
+    /// it configures safe default TLS configuration.
+    pub fn with_root_certificates(
+        self,
+        root_store: RootCertStore,
+    ) -> ClientConfigBuilder<states::WantsTransportConfigClient> {
+        #[cfg(feature = "dangerous-configuration")]
+        {
+            let mut builder = self.with_custom_tls(Self::build_tls_config(root_store.clone()));
+            builder.0.root_store = Some(root_store);
+            builder
+        }
+
+        #[cfg(not(feature = "dangerous-configuration"))]
+        self.with_custom_tls(Self::build_tls_config(root_store))
+    }
+
+    /// Configures the client to trust exactly the root certificates contained in a PEM bundle
+    /// (e.g., the content of an enterprise CA file).
+    ///
+    /// Multiple concatenated certificates are supported. PEM blocks not containing a
+    /// certificate (e.g., private keys) are skipped, as well as certificates that
+    /// cannot be parsed. It fails if no valid certificate is found.
+    ///
+    /// As [`with_root_certificates`](Self::with_root_certificates), native root certificates
+    /// are not loaded, and safe default TLS configuration is used.
+    pub fn with_root_certificates_pem(
+        self,
+        pem: &[u8],
+    ) -> Result<ClientConfigBuilder<states::WantsTransportConfigClient>, PemRootCertsError> {
+        let certs = rustls_pemfile::certs(&mut &*pem).map_err(PemRootCertsError::Pem)?;
+
+        let mut root_store = RootCertStore::empty();
+        root_store.add_parsable_certificates(&certs);
+
+        if root_store.is_empty() {
+            return Err(PemRootCertsError::Empty);
+        }
+
+        Ok(self.with_root_certificates(root_store))
+    }
+
+    /// Configures the client to validate server certificates against `root_store`, delegating
+    /// the hostname check to `verifier`.
+    ///
+    /// The certificate chain is validated first, as with
+    /// [`with_root_certificates`](Self::with_root_certificates): an untrusted, expired or
+    /// otherwise invalid chain is always rejected, and `verifier` is never called for it.
+    /// Then, `verifier` is invoked with the server name (taken from the URL being connected to)
+    /// and the end-entity certificate (DER encoded), and it decides whether the certificate is
+    /// acceptable for that name, *replacing* the default hostname matching. This allows
+    /// custom name-matching policies (e.g., multi-level wildcards, or tenant certificates
+    /// shared by many hostnames).
+    ///
+    /// # Security Note
+    ///
+    /// The verifier is in charge of ensuring the certificate actually belongs to the server
+    /// being connected to. Accepting a name the certificate was not issued for allows any
+    /// holder of a certificate signed by a trusted authority to impersonate that server.
+    ///
+    /// With [`strict_hostname_verification`](ClientConfigBuilder::strict_hostname_verification),
+    /// a rejected name is reported as
+    /// [`ConnectingError::HostnameMismatch`](crate::error::ConnectingError::HostnameMismatch).
+    #[cfg(feature = "dangerous-configuration")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dangerous-configuration")))]
+    pub fn with_hostname_verifier<F>(
+        self,
+        root_store: RootCertStore,
+        verifier: F,
+    ) -> ClientConfigBuilder<states::WantsTransportConfigClient>
+    where
+        F: Fn(&rustls::ServerName, &rustls::Certificate) -> bool + Send + Sync + 'static,
+    {
+        let mut tls_config = Self::build_tls_config(RootCertStore::empty());
+        tls_config.dangerous().set_certificate_verifier(Arc::new(
+            dangerous_configuration::HostnameVerifier {
+                inner: rustls::client::WebPkiVerifier::new(root_store, None),
+                verifier: Box::new(verifier),
+            },
+        ));
+
+        self.with_custom_tls(tls_config)
+    }
+
+    /// Configures the client to trust exactly one server certificate (DER encoded), i.e.,
+    /// *certificate pinning*.
+    ///
+    /// The connection is accepted only if the end-entity certificate presented by the server
+    /// is byte-for-byte identical to `certificate`. Chain validation is bypassed: neither
+    /// issuer, validity period nor server name are checked. This is the simplest model for
+    /// fixed deployments using a self-signed certificate (e.g., generated with
+    /// [`Certificate::self_signed`](crate::tls::Certificate::self_signed)). The server must
+    /// still prove it holds the private key of the certificate during the TLS handshake.
+    ///
+    /// # Rotation
+    ///
+    /// Any change of the server certificate, including a renewal with the same key, makes
+    /// connections fail until clients are updated with the new certificate. Hence, clients
+    /// must be deployed with the new certificate before the server switches to it. When
+    /// certificate renewals are frequent, pinning the certificate hash (see
+    /// [`Certificate::hashes`](crate::tls::Certificate::hashes)) or a private root
+    /// certificate (see [`with_root_certificates`](Self::with_root_certificates)) is less
+    /// disruptive.
+    #[cfg(feature = "dangerous-configuration")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dangerous-configuration")))]
+    pub fn with_pinned_certificate(
+        self,
+        certificate: rustls::Certificate,
+    ) -> ClientConfigBuilder<states::WantsTransportConfigClient> {
+        let mut tls_config = Self::build_tls_config(RootCertStore::empty());
+        tls_config.dangerous().set_certificate_verifier(Arc::new(
+            dangerous_configuration::PinnedCertificate(certificate),
+        ));
+
+        self.with_custom_tls(tls_config)
     }
 
     /// Allows for manual configuration of a custom TLS setup using a provided
@@ -671,6 +1554,9 @@ impl ClientConfigBuilder<states::WantsRootStore> {
     ///
     /// For most use cases, it is recommended to use the [`with_native_certs`](Self::with_native_certs)
     /// method to configure TLS with safe defaults.
+    ///
+    /// **Note**: the supported version of [`rustls`] does not implement *Encrypted Client
+    /// Hello* (ECH), hence the server name is always sent in clear text during the handshake.
     pub fn with_custom_tls(
         self,
         tls_config: rustls::ClientConfig,
@@ -680,9 +1566,22 @@ impl ClientConfigBuilder<states::WantsRootStore> {
         ClientConfigBuilder(states::WantsTransportConfigClient {
             bind_address: self.0.bind_address,
             dual_stack_config: self.0.dual_stack_config,
+            bind_port_range: self.0.bind_port_range,
             tls_config,
+            #[cfg(feature = "dangerous-configuration")]
+            root_store: None,
             transport_config,
             dns_resolver: Box::<TokioDnsResolver>::default(),
+            dns_resolve_timeout: None,
+            address_family: AddressFamily::default(),
+            handshake_timeout: None,
+            session_setup_timeout: None,
+            max_field_section_size: None,
+            strict_hostname_verification: false,
+            receive_window: VarInt::MAX,
+            datagram_receive_buffer_size: DEFAULT_DATAGRAM_RECEIVE_BUFFER_SIZE,
+            endpoint_config: quinn::EndpointConfig::default(),
+            bind_device: None,
         })
     }
 
@@ -722,27 +1621,41 @@ impl ClientConfigBuilder<states::WantsRootStore> {
         ClientConfigBuilder(states::WantsTransportConfigClient {
             bind_address: self.0.bind_address,
             dual_stack_config: self.0.dual_stack_config,
+            bind_port_range: self.0.bind_port_range,
             tls_config,
+            #[cfg(feature = "dangerous-configuration")]
+            root_store: None,
             transport_config,
             dns_resolver: Box::<TokioDnsResolver>::default(),
+            dns_resolve_timeout: None,
+            address_family: AddressFamily::default(),
+            handshake_timeout: None,
+            session_setup_timeout: None,
+            max_field_section_size: None,
+            strict_hostname_verification: false,
+            receive_window: VarInt::MAX,
+            datagram_receive_buffer_size: DEFAULT_DATAGRAM_RECEIVE_BUFFER_SIZE,
+            endpoint_config: quinn::EndpointConfig::default(),
+            bind_device: None,
         })
     }
 
-    fn native_cert_store() -> RootCertStore {
+    fn native_cert_store() -> Result<RootCertStore, NativeCertsError> {
         let mut root_store = RootCertStore::empty();
 
         let _var_restore_guard = utils::remove_var_tmp("SSL_CERT_FILE");
 
-        match rustls_native_certs::load_native_certs() {
-            Ok(certs) => {
-                for c in certs {
-                    let _ = root_store.add(&rustls::Certificate(c.0));
-                }
-            }
-            Err(_error) => {}
+        let certs = rustls_native_certs::load_native_certs().map_err(NativeCertsError::Load)?;
+
+        for c in certs {
+            let _ = root_store.add(&rustls::Certificate(c.0));
+        }
+
+        if root_store.is_empty() {
+            return Err(NativeCertsError::Empty);
         }
 
-        root_store
+        Ok(root_store)
     }
 
     fn build_tls_config(root_store: RootCertStore) -> TlsClientConfig {
@@ -762,15 +1675,48 @@ impl ClientConfigBuilder<states::WantsRootStore> {
 impl ClientConfigBuilder<states::WantsTransportConfigClient> {
     /// Completes configuration process.
     #[must_use]
-    pub fn build(self) -> ClientConfig {
+    #[cfg_attr(not(feature = "dangerous-configuration"), allow(unused_mut))]
+    pub fn build(mut self) -> ClientConfig {
+        #[cfg(feature = "dangerous-configuration")]
+        let rejected_names = RejectedNames::default();
+
+        #[cfg(feature = "dangerous-configuration")]
+        if let (true, Some(root_store)) = (
+            self.0.strict_hostname_verification,
+            self.0.root_store.take(),
+        ) {
+            self.0
+                .tls_config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(
+                    dangerous_configuration::NameRecordingVerifier {
+                        inner: rustls::client::WebPkiVerifier::new(root_store, None),
+                        rejected_names: rejected_names.clone(),
+                    },
+                ));
+        }
+
         let mut quic_config = QuicClientConfig::new(Arc::new(self.0.tls_config));
         quic_config.transport_config(Arc::new(self.0.transport_config));
 
         ClientConfig {
             bind_address: self.0.bind_address,
             dual_stack_config: self.0.dual_stack_config,
+            bind_port_range: self.0.bind_port_range,
             quic_config,
             dns_resolver: self.0.dns_resolver,
+            dns_resolve_timeout: self.0.dns_resolve_timeout,
+            address_family: self.0.address_family,
+            handshake_timeout: self.0.handshake_timeout,
+            session_setup_timeout: self.0.session_setup_timeout,
+            max_field_section_size: self.0.max_field_section_size,
+            strict_hostname_verification: self.0.strict_hostname_verification,
+            #[cfg(feature = "dangerous-configuration")]
+            rejected_names,
+            receive_window: self.0.receive_window,
+            datagram_receive_buffer_size: self.0.datagram_receive_buffer_size,
+            endpoint_config: self.0.endpoint_config,
+            bind_device: self.0.bind_device,
         }
     }
 
@@ -807,9 +1753,280 @@ impl ClientConfigBuilder<states::WantsTransportConfigClient> {
         self
     }
 
+    /// Whether to use UDP generic segmentation offload (GSO) when sending.
+    ///
+    /// With GSO, a batch of packets is passed to the kernel (or to the network interface) in a
+    /// single call, which greatly reduces the CPU cost of bulk transfers. It is only supported
+    /// on Linux: on other platforms, this option has no effect.
+    ///
+    /// Enabled by default. If the network interface driver does not support it, the first
+    /// packets sent fail, and GSO is automatically disabled for the socket (this can cause
+    /// spurious packet loss at startup). Disable it where offloads are known to be unavailable
+    /// or harmful (e.g., with some packet inspection tools).
+    ///
+    /// **Note**: generic receive offload (GRO) is always enabled opportunistically on Linux,
+    /// and it is transparently unused where unsupported.
+    pub fn enable_gso(mut self, enabled: bool) -> Self {
+        self.0.transport_config.enable_segmentation_offload(enabled);
+        self
+    }
+
+    /// Maximum reordering, in packets, tolerated before a packet is declared lost.
+    ///
+    /// A packet is considered lost once a packet sent this many packets later has been
+    /// acknowledged. On paths reordering packets (e.g., some wireless or satellite links),
+    /// a higher threshold avoids spurious retransmissions and congestion window reductions,
+    /// at the cost of slower recovery of actually lost packets. Unacknowledged packets are
+    /// tracked longer, which slightly increases memory usage.
+    ///
+    /// The value must be at least `3` (RFC 9002), which is the default.
+    ///
+    /// **Note**: the number of ACK ranges tracked by the receiver is not configurable.
+    pub fn packet_reordering_threshold(
+        mut self,
+        packets: u32,
+    ) -> Result<Self, InvalidReorderingThreshold> {
+        if packets < 3 {
+            return Err(InvalidReorderingThreshold);
+        }
+
+        self.0.transport_config.packet_threshold(packets);
+
+        Ok(self)
+    }
+
+    /// Maximum reordering, in time, tolerated before a packet is declared lost, as a factor
+    /// of the round-trip time.
+    ///
+    /// A packet is considered lost once this fraction of the RTT has elapsed since a later
+    /// packet was acknowledged. As for
+    /// [`packet_reordering_threshold`](Self::packet_reordering_threshold), a higher value
+    /// tolerates more reordering, but delays loss recovery.
+    ///
+    /// The value must be finite and at least `1.0`. Defaults to `1.125` (RFC 9002).
+    pub fn time_reordering_threshold(
+        mut self,
+        factor: f32,
+    ) -> Result<Self, InvalidReorderingThreshold> {
+        if !factor.is_finite() || factor < 1.0 {
+            return Err(InvalidReorderingThreshold);
+        }
+
+        self.0.transport_config.time_threshold(factor);
+
+        Ok(self)
+    }
+
+    /// Maximum amount of out-of-order cryptographic handshake data buffered, in bytes.
+    ///
+    /// Handshake messages (e.g., large certificate chains) received out of order beyond this
+    /// amount cause the connection to be closed with a protocol error.
+    ///
+    /// The value must be at least `1200` bytes (the minimum QUIC packet size). Defaults to
+    /// `16 KiB`.
+    ///
+    /// **Note**: once established, connections update their traffic keys automatically,
+    /// before reaching the confidentiality limit of the negotiated cipher
+    /// (2<sup>23</sup> packets for AES-GCM, RFC 9001). This threshold is not configurable.
+    pub fn crypto_buffer_size(mut self, bytes: usize) -> Result<Self, InvalidCryptoBufferSize> {
+        if bytes < 1200 {
+            return Err(InvalidCryptoBufferSize);
+        }
+
+        self.0.transport_config.crypto_buffer_size(bytes);
+
+        Ok(self)
+    }
+
+    /// Reports a server certificate not valid for the host of the connect URL with a
+    /// dedicated error.
+    ///
+    /// The hostname is always checked by the TLS certificate verifier (e.g., the one
+    /// configured with [`with_native_certs`](ClientConfigBuilder::with_native_certs)), which
+    /// fails the handshake on mismatch. When enabled, such a failure makes
+    /// [`Endpoint::connect`](crate::Endpoint::connect) return
+    /// [`ConnectingError::HostnameMismatch`](crate::error::ConnectingError::HostnameMismatch),
+    /// rather than a generic TLS error. This also applies to a custom verifier (e.g., with
+    /// [`with_custom_tls`](ClientConfigBuilder::with_custom_tls)) rejecting the certificate
+    /// with [`CertificateError::NotValidForName`](rustls::CertificateError::NotValidForName).
+    ///
+    /// With the `dangerous-configuration` feature, the verifier configured with root
+    /// certificates also records the names the certificate was issued for, reported in the
+    /// error.
+    ///
+    /// Disabled by default.
+    pub fn strict_hostname_verification(mut self, enabled: bool) -> Self {
+        self.0.strict_hostname_verification = enabled;
+        self
+    }
+
+    /// Maximum amount of received data (in bytes) buffered across all the streams of a
+    /// connection.
+    ///
+    /// This is the connection-level QUIC flow-control window: once the peer has sent this
+    /// many bytes the application has not read yet, it must wait for the application to
+    /// consume some data. No data is dropped; backpressure applies to all streams at once.
+    ///
+    /// The limit applies together with the per-stream receive window (fixed by the QUIC
+    /// stack): a single stream cannot buffer more than the smaller of the two. Thus, it
+    /// bounds the memory used by many streams, each within its own window. It also
+    /// accounts for the internal HTTP/3 streams, so it should not be set too small.
+    ///
+    /// The window can be changed later with
+    /// [`Connection::set_receive_window`](crate::Connection::set_receive_window).
+    /// A finite limit is required for
+    /// [`Connection::pause_incoming`](crate::Connection::pause_incoming) to apply backpressure.
+    ///
+    /// By default, no limit is applied (only per-stream windows).
+    pub fn max_connection_receive_memory(mut self, max: usize) -> Self {
+        let receive_window = VarInt::try_from_u64(max as u64).unwrap_or(VarInt::MAX);
+
+        self.0.receive_window = receive_window;
+        self.0
+            .transport_config
+            .receive_window(varint_w2q(receive_window));
+        self
+    }
+
+    /// Maximum amount of incoming datagrams data (in bytes) buffered, waiting to be read by
+    /// the application.
+    ///
+    /// When the application does not read datagrams fast enough and the buffer is full, the
+    /// oldest buffered datagrams are discarded to make room for new ones. Discarded datagrams
+    /// are counted by [`Connection::datagrams_dropped_rx`](crate::Connection::datagrams_dropped_rx),
+    /// and a warning is logged the first time it happens on a connection.
+    ///
+    /// A good size is the expected incoming datagram rate (in bytes per second) multiplied by
+    /// the longest pause of the application between two reads. The value also bounds the size
+    /// of datagrams the peer is allowed to send, so it should not be smaller than the largest
+    /// expected datagram: the advertised `max_datagram_frame_size` transport parameter is
+    /// the minimum of this value and `65535`. The peer's limit is reflected by
+    /// [`Connection::max_datagram_size`](crate::Connection::max_datagram_size).
+    ///
+    /// Default: 1.25 MB.
+    pub fn datagram_receive_buffer_size(mut self, size: usize) -> Self {
+        self.0.datagram_receive_buffer_size = size;
+        self.0
+            .transport_config
+            .datagram_receive_buffer_size(Some(size));
+        self
+    }
+
+    /// Maximum UDP payload size (in bytes) accepted from the peer.
+    ///
+    /// The value is advertised to the peer (`max_udp_payload_size` transport parameter), and
+    /// it bounds the size of incoming packets, hence the size of the receive buffer allocated
+    /// by the endpoint. It does not affect the size of outgoing packets, which is
+    /// determined by path MTU discovery.
+    ///
+    /// Valid range is from `1200` (the minimum QUIC packet size) to `65527` (the largest UDP
+    /// payload over IPv6). Values outside this range result in an error.
+    ///
+    /// Defaults to `1472`, which is the largest UDP payload that fits in the typical 1500 bytes
+    /// Ethernet MTU. Links with larger MTUs (e.g., loopback) can raise it.
+    ///
+    /// **Note**: other QUIC transport parameters, such as `active_connection_id_limit`, are
+    /// fixed by the QUIC stack and they cannot be configured.
+    pub fn max_udp_payload_size(mut self, size: u16) -> Result<Self, InvalidMaxUdpPayloadSize> {
+        self.0
+            .endpoint_config
+            .max_udp_payload_size(size)
+            .map_err(|_| InvalidMaxUdpPayloadSize)?;
+
+        Ok(self)
+    }
+
+    /// Initial estimate of the round-trip time (RTT), used before any RTT sample is available.
+    ///
+    /// The initial RTT drives the loss-detection timers of the early connection
+    /// (i.e., the handshake): a value much lower than the actual RTT causes spurious
+    /// retransmissions (probe timeouts), while a value much higher delays the recovery
+    /// of actually lost packets. On high-latency links (e.g., satellite, with ~600ms RTT),
+    /// setting a realistic value (e.g., 500ms) avoids spurious retransmissions.
+    ///
+    /// Once RTT samples are available, the estimate converges to the measured value.
+    ///
+    /// Defaults to 333ms, as recommended by RFC 9002.
+    ///
+    /// **Note**: the anti-amplification limit (a server sends at most three times the
+    /// amount of data received from an unvalidated client address) is mandated by QUIC,
+    /// and it cannot be configured.
+    pub fn initial_rtt(mut self, rtt: Duration) -> Self {
+        self.0.transport_config.initial_rtt(rtt);
+        self
+    }
+
+    /// Binds the socket to a specific network interface (e.g., `"eth0"`, or a VRF device),
+    /// by means of the `SO_BINDTODEVICE` socket option.
+    ///
+    /// Only packets received from the given interface are processed, and packets are sent
+    /// out through it regardless of the routing table. This is commonly used for VRF-based
+    /// network segmentation.
+    ///
+    /// The option is applied on endpoint creation: failures (e.g., unknown interface, or
+    /// missing privileges) are reported as an I/O error at that point.
+    ///
+    /// **Note**: this is Linux-specific. Setting this option usually requires the
+    /// `CAP_NET_RAW` capability.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    pub fn bind_device(mut self, interface: &str) -> Self {
+        self.0.bind_device = Some(interface.as_bytes().to_vec());
+        self
+    }
+
+    /// Maximum duration allowed for the QUIC/TLS handshake when connecting.
+    ///
+    /// If the handshake does not complete within this time, the connection attempt is
+    /// aborted and [`Endpoint::connect`](crate::Endpoint::connect) fails with
+    /// [`ConnectionError::HandshakeTimedOut`](crate::error::ConnectionError::HandshakeTimedOut).
+    ///
+    /// This is independent of [`max_idle_timeout`](Self::max_idle_timeout).
+    ///
+    /// `None` to disable, which is the default.
+    pub fn handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.0.handshake_timeout = timeout;
+        self
+    }
+
+    /// Maximum duration allowed for establishing the WebTransport session, once the QUIC
+    /// handshake is complete.
+    ///
+    /// This bounds the HTTP/3 settings exchange and the session request (`CONNECT`) until
+    /// the server response: if the server stalls, the connection is dropped and
+    /// [`Endpoint::connect`](crate::Endpoint::connect) fails with
+    /// [`ConnectionError::SessionSetupTimedOut`](crate::error::ConnectionError::SessionSetupTimedOut).
+    ///
+    /// This is independent of both the [`handshake_timeout`](Self::handshake_timeout) and
+    /// the [`max_idle_timeout`](Self::max_idle_timeout).
+    ///
+    /// `None` to disable, which is the default.
+    pub fn session_setup_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.0.session_setup_timeout = timeout;
+        self
+    }
+
+    /// Maximum size (in bytes) of the HTTP/3 header field section the client accepts.
+    ///
+    /// The limit is advertised to servers (`SETTINGS_MAX_FIELD_SECTION_SIZE`). If the session
+    /// response exceeds it, [`Endpoint::connect`](crate::Endpoint::connect) fails with
+    /// [`ConnectingError::HeadersTooLarge`](crate::error::ConnectingError::HeadersTooLarge).
+    ///
+    /// Note that the request headers sent by the client are bounded by the limit advertised
+    /// by the server, not by this one.
+    ///
+    /// By default, no limit is applied.
+    pub fn max_field_section_size(mut self, size: u64) -> Self {
+        self.0.max_field_section_size = Some(VarInt::try_from_u64(size).unwrap_or(VarInt::MAX));
+        self
+    }
+
     /// Sets the *DNS* resolver used during [`Endpoint::connect`](crate::Endpoint::connect).
     ///
-    /// Default configuration uses [`TokioDnsResolver`].
+    /// Default configuration uses [`TokioDnsResolver`], which requires a *Tokio* runtime.
+    /// When running the endpoint on a different runtime (see
+    /// [`Endpoint::client_with_runtime`](crate::Endpoint::client_with_runtime)), consider
+    /// using [`StdDnsResolver`] or a custom implementation.
     pub fn dns_resolver<R>(mut self, dns_resolver: R) -> Self
     where
         R: DnsResolver + Send + Sync + Unpin + 'static,
@@ -818,6 +2035,41 @@ impl ClientConfigBuilder<states::WantsTransportConfigClient> {
         self
     }
 
+    /// Maximum duration allowed for the *DNS* resolution during
+    /// [`Endpoint::connect`](crate::Endpoint::connect).
+    ///
+    /// If the resolution does not complete within this time, the connection attempt fails with
+    /// [`ConnectingError::DnsLookup`](crate::error::ConnectingError::DnsLookup) (with error kind
+    /// [`TimedOut`](std::io::ErrorKind::TimedOut)). It applies to any configured
+    /// [`dns_resolver`](Self::dns_resolver).
+    ///
+    /// `None` to disable, which is the default.
+    pub fn dns_resolve_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.0.dns_resolve_timeout = timeout;
+        self
+    }
+
+    /// Sets which address family to use when a domain resolves to both IPv4 and IPv6
+    /// addresses, during [`Endpoint::connect`](crate::Endpoint::connect).
+    ///
+    /// For instance, [`AddressFamily::Ipv4Only`] works around networks with a broken IPv6
+    /// connectivity. If no resolved address matches the preference, the connection attempt
+    /// fails with [`ConnectingError::DnsNotFound`](crate::error::ConnectingError::DnsNotFound).
+    ///
+    /// The preference does not apply to URLs specifying an IP address. Moreover, when the
+    /// client is bound to an IPv4 address (see [`IpBindConfig`]), only IPv4 addresses can
+    /// be reached, whatever the preference: use a dual-stack bind configuration (e.g.,
+    /// [`IpBindConfig::InAddrAnyDual`]) to reach both families.
+    ///
+    /// The resolver must return all the addresses of a domain (see
+    /// [`DnsResolver::poll_resolve_all`]), as [`TokioDnsResolver`] and [`StdDnsResolver`] do.
+    ///
+    /// Defaults to [`AddressFamily::System`].
+    pub fn address_family_preference(mut self, address_family: AddressFamily) -> Self {
+        self.0.address_family = address_family;
+        self
+    }
+
     /// Writes key material for debugging into file provided by `SSLKEYLOGFILE` environment variable.
     pub fn enable_key_log(mut self) -> Self {
         self.0.tls_config.key_log = Arc::new(rustls::KeyLogFile::new());
@@ -853,6 +2105,7 @@ pub mod states {
     /// Config builder state where the caller must supply TLS root store.
     pub struct WantsRootStore {
         pub(super) bind_address: SocketAddr,
+        pub(super) bind_port_range: Option<RangeInclusive<u16>>,
         pub(super) dual_stack_config: Ipv6DualStackConfig,
     }
 
@@ -863,15 +2116,42 @@ pub mod states {
         pub(super) tls_config: TlsServerConfig,
         pub(super) transport_config: quinn::TransportConfig,
         pub(super) migration: bool,
+        pub(super) migration_policy: Option<MigrationPolicy>,
+        pub(super) migration_rate_limit: Option<(u32, Duration)>,
+        pub(super) handshake_timeout: Option<Duration>,
+        pub(super) session_setup_timeout: Option<Duration>,
+        pub(super) max_field_section_size: Option<VarInt>,
+        pub(super) max_request_headers: Option<usize>,
+        pub(super) max_request_header_bytes: Option<usize>,
+        pub(super) allowed_origins: Option<Arc<[String]>>,
+        pub(super) max_concurrent_connections: Option<u32>,
+        pub(super) max_connection_lifetime: Option<Duration>,
+        pub(super) receive_window: VarInt,
+        pub(super) datagram_receive_buffer_size: usize,
+        pub(super) endpoint_config: quinn::EndpointConfig,
+        pub(super) bind_device: Option<Vec<u8>>,
     }
 
     /// Config builder state where transport properties can be set.
     pub struct WantsTransportConfigClient {
         pub(super) bind_address: SocketAddr,
+        pub(super) bind_port_range: Option<RangeInclusive<u16>>,
         pub(super) dual_stack_config: Ipv6DualStackConfig,
         pub(super) tls_config: TlsClientConfig,
+        #[cfg(feature = "dangerous-configuration")]
+        pub(super) root_store: Option<RootCertStore>,
         pub(super) transport_config: quinn::TransportConfig,
         pub(super) dns_resolver: Box<dyn DnsResolver + Send + Sync + Unpin>,
+        pub(super) dns_resolve_timeout: Option<Duration>,
+        pub(super) address_family: AddressFamily,
+        pub(super) handshake_timeout: Option<Duration>,
+        pub(super) session_setup_timeout: Option<Duration>,
+        pub(super) max_field_section_size: Option<VarInt>,
+        pub(super) strict_hostname_verification: bool,
+        pub(super) receive_window: VarInt,
+        pub(super) datagram_receive_buffer_size: usize,
+        pub(super) endpoint_config: quinn::EndpointConfig,
+        pub(super) bind_device: Option<Vec<u8>>,
     }
 }
 
@@ -879,6 +2159,8 @@ pub mod states {
 mod dangerous_configuration {
     use rustls::client::ServerCertVerified;
     use rustls::client::ServerCertVerifier;
+    use rustls::client::WebPkiVerifier;
+    use rustls::CertificateError;
 
     pub(super) struct NoServerVerification;
 
@@ -895,8 +2177,129 @@ mod dangerous_configuration {
             Ok(ServerCertVerified::assertion())
         }
     }
+
+    /// Accepts exactly one end-entity certificate.
+    pub(super) struct PinnedCertificate(pub(super) rustls::Certificate);
+
+    impl ServerCertVerifier for PinnedCertificate {
+        fn verify_server_cert(
+            &self,
+            end_entity: &rustls::Certificate,
+            _intermediates: &[rustls::Certificate],
+            _server_name: &rustls::ServerName,
+            _scts: &mut dyn Iterator<Item = &[u8]>,
+            _ocsp_response: &[u8],
+            _now: std::time::SystemTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            if end_entity == &self.0 {
+                Ok(ServerCertVerified::assertion())
+            } else {
+                Err(rustls::Error::InvalidCertificate(
+                    CertificateError::ApplicationVerificationFailure,
+                ))
+            }
+        }
+    }
+
+    /// Chain validation by `inner`, with hostname matching by `verifier`.
+    #[allow(clippy::type_complexity)]
+    pub(super) struct HostnameVerifier {
+        pub(super) inner: WebPkiVerifier,
+        pub(super) verifier:
+            Box<dyn Fn(&rustls::ServerName, &rustls::Certificate) -> bool + Send + Sync>,
+    }
+
+    impl ServerCertVerifier for HostnameVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &rustls::Certificate,
+            intermediates: &[rustls::Certificate],
+            server_name: &rustls::ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: std::time::SystemTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            // The chain is checked before the name: a name mismatch implies a trusted chain
+            match self.inner.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            ) {
+                Ok(_)
+                | Err(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)) => {
+                    if (self.verifier)(server_name, end_entity) {
+                        Ok(ServerCertVerified::assertion())
+                    } else {
+                        Err(rustls::Error::InvalidCertificate(
+                            CertificateError::NotValidForName,
+                        ))
+                    }
+                }
+                Err(error) => Err(error),
+            }
+        }
+    }
+
+    /// Chain and hostname validation by `inner`, recording the names of the certificates not
+    /// valid for the server name.
+    pub(super) struct NameRecordingVerifier {
+        pub(super) inner: WebPkiVerifier,
+        pub(super) rejected_names: super::RejectedNames,
+    }
+
+    impl ServerCertVerifier for NameRecordingVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &rustls::Certificate,
+            intermediates: &[rustls::Certificate],
+            server_name: &rustls::ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: std::time::SystemTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            let result = self.inner.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            );
+
+            if let Err(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)) =
+                &result
+            {
+                let server_name = match server_name {
+                    rustls::ServerName::DnsName(name) => name.as_ref().to_string(),
+                    rustls::ServerName::IpAddress(ip) => ip.to_string(),
+                    _ => return result,
+                };
+
+                self.rejected_names
+                    .lock()
+                    .expect("Mutex is not poisoned")
+                    .insert(server_name, crate::tls::subject_alt_names(&end_entity.0));
+            }
+
+            result
+        }
+    }
 }
 
+/// Names found in the certificates rejected as not valid for the server name, by server name.
+///
+/// Filled by the verifier installed with
+/// [`strict_hostname_verification`](ClientConfigBuilder::strict_hostname_verification), and
+/// consumed by the endpoint to report [`ConnectingError::HostnameMismatch`].
+///
+/// [`ConnectingError::HostnameMismatch`]: crate::error::ConnectingError::HostnameMismatch
+#[cfg(feature = "dangerous-configuration")]
+pub(crate) type RejectedNames =
+    Arc<std::sync::Mutex<std::collections::HashMap<String, Vec<String>>>>;
+
 /// A trait for asynchronously resolving domain names to IP addresses using DNS.
 ///
 /// Utilities for working with `DnsResolver` values are provided by [`DnsResolverExt`].
@@ -907,12 +2310,30 @@ pub trait DnsResolver {
         cx: &mut Context<'_>,
         host: &str,
     ) -> Poll<std::io::Result<Option<SocketAddr>>>;
+
+    /// Resolves a domain name to all its IP addresses.
+    ///
+    /// This allows applying the
+    /// [`address_family_preference`](ClientConfigBuilder::address_family_preference).
+    /// The default implementation only returns the address of
+    /// [`poll_resolve`](Self::poll_resolve).
+    fn poll_resolve_all(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+    ) -> Poll<std::io::Result<Vec<SocketAddr>>> {
+        self.poll_resolve(cx, host)
+            .map(|result| result.map(|address| address.into_iter().collect()))
+    }
 }
 
 /// Extension trait for [`DnsResolver`].
 pub trait DnsResolverExt: DnsResolver {
     /// Resolves a domain name to one IP address.
     fn resolve(&mut self, host: &str) -> Resolve<Self>;
+
+    /// Resolves a domain name to all its IP addresses.
+    fn resolve_all(&mut self, host: &str) -> ResolveAll<'_, Self>;
 }
 
 impl<T> DnsResolverExt for T
@@ -925,6 +2346,13 @@ where
             host: host.to_string(),
         }
     }
+
+    fn resolve_all(&mut self, host: &str) -> ResolveAll<'_, Self> {
+        ResolveAll {
+            resolver: self,
+            host: host.to_string(),
+        }
+    }
 }
 
 /// Future resolving domain name.
@@ -951,41 +2379,262 @@ where
     }
 }
 
+/// Future resolving all the addresses of a domain name.
+///
+/// See [`DnsResolverExt::resolve_all`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ResolveAll<'a, R>
+where
+    R: ?Sized,
+{
+    resolver: &'a mut R,
+    host: String,
+}
+
+impl<'a, R> Future for ResolveAll<'a, R>
+where
+    R: DnsResolver + Unpin + ?Sized,
+{
+    type Output = std::io::Result<Vec<SocketAddr>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        DnsResolver::poll_resolve_all(Pin::new(this.resolver), cx, &this.host)
+    }
+}
+
+/// A DNS resolution in progress, along with the host it was started for.
+type PendingResolution = (
+    String,
+    Pin<Box<dyn Future<Output = std::io::Result<Vec<SocketAddr>>> + Send + Sync>>,
+);
+
+/// Polls the resolution of `host`, started with `resolve` unless already pending.
+fn poll_pending_resolution<F>(
+    pending: &mut Option<PendingResolution>,
+    cx: &mut Context<'_>,
+    host: &str,
+    resolve: impl FnOnce(String) -> F,
+) -> Poll<std::io::Result<Vec<SocketAddr>>>
+where
+    F: Future<Output = std::io::Result<Vec<SocketAddr>>> + Send + Sync + 'static,
+{
+    // A pending resolution for a different host might be left by a cancelled request.
+    if !matches!(pending, Some((pending_host, _)) if pending_host == host) {
+        *pending = Some((host.to_string(), Box::pin(resolve(host.to_string()))));
+    }
+
+    let (_, fut) = pending.as_mut().expect("Future has been just set");
+    let result = std::task::ready!(Future::poll(fut.as_mut(), cx));
+    *pending = None;
+
+    Poll::Ready(result)
+}
+
 /// A DNS resolver implementation using the *Tokio* asynchronous runtime.
 ///
 /// Internally, it uses [`tokio::net::lookup_host`].
 #[derive(Default)]
 pub struct TokioDnsResolver {
-    #[allow(clippy::type_complexity)]
-    fut: Option<Pin<Box<dyn Future<Output = std::io::Result<Option<SocketAddr>>> + Send + Sync>>>,
+    pending: Option<PendingResolution>,
 }
 
 impl DnsResolver for TokioDnsResolver {
     fn poll_resolve(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         host: &str,
     ) -> Poll<std::io::Result<Option<SocketAddr>>> {
-        let fut = self.fut.get_or_insert_with(|| {
-            let host = host.to_string();
-            Box::pin(async move { Ok(tokio::net::lookup_host(host).await?.next()) })
+        self.poll_resolve_all(cx, host)
+            .map(|result| result.map(|addresses| addresses.into_iter().next()))
+    }
+
+    fn poll_resolve_all(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+    ) -> Poll<std::io::Result<Vec<SocketAddr>>> {
+        poll_pending_resolution(&mut self.pending, cx, host, |host| async move {
+            Ok(tokio::net::lookup_host(host).await?.collect())
+        })
+    }
+}
+
+/// A runtime-agnostic DNS resolver implementation.
+///
+/// Internally, it uses [`std::net::ToSocketAddrs`] on a small pool of threads shared by all
+/// the resolvers, so that the asynchronous runtime is never blocked. Lookups abandoned
+/// before a thread picks them up (e.g., after a [timeout](ClientConfigBuilder::dns_resolve_timeout))
+/// are skipped.
+#[derive(Default)]
+pub struct StdDnsResolver {
+    pending: Option<PendingResolution>,
+}
+
+impl StdDnsResolver {
+    /// Number of threads performing the lookups.
+    const THREADS: usize = 4;
+
+    /// Queues the lookup of `host` on the shared threads.
+    fn lookup(host: String) -> oneshot::Receiver<std::io::Result<Vec<SocketAddr>>> {
+        type Lookup = (String, oneshot::Sender<std::io::Result<Vec<SocketAddr>>>);
+
+        static LOOKUPS: std::sync::OnceLock<std::sync::Mutex<std::sync::mpsc::Sender<Lookup>>> =
+            std::sync::OnceLock::new();
+
+        let lookups = LOOKUPS.get_or_init(|| {
+            let (lookups_tx, lookups_rx) = std::sync::mpsc::channel::<Lookup>();
+            let lookups_rx = Arc::new(std::sync::Mutex::new(lookups_rx));
+
+            for _ in 0..Self::THREADS {
+                let lookups_rx = lookups_rx.clone();
+
+                let spawned = std::thread::Builder::new()
+                    .name("wtransport-dns".to_string())
+                    .spawn(move || loop {
+                        let lookup = lookups_rx.lock().expect("Lookups lock poisoned").recv();
+                        let Ok((host, tx)) = lookup else {
+                            return;
+                        };
+
+                        if !tx.is_closed() {
+                            let _ = tx.send(host.to_socket_addrs().map(|addrs| addrs.collect()));
+                        }
+                    });
+
+                if let Err(error) = spawned {
+                    warn!("Cannot spawn DNS resolver thread: {error}");
+                }
+            }
+
+            std::sync::Mutex::new(lookups_tx)
         });
 
-        Future::poll(fut.as_mut(), cx)
+        let (tx, rx) = oneshot::channel();
+        let _ = lookups
+            .lock()
+            .expect("Lookups lock poisoned")
+            .send((host, tx));
+
+        rx
     }
 }
 
-impl std::error::Error for InvalidIdleTimeout {}
+impl DnsResolver for StdDnsResolver {
+    fn poll_resolve(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+    ) -> Poll<std::io::Result<Option<SocketAddr>>> {
+        self.poll_resolve_all(cx, host)
+            .map(|result| result.map(|addresses| addresses.into_iter().next()))
+    }
 
-impl Debug for InvalidIdleTimeout {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("idle timeout value configuration is invalid")
+    fn poll_resolve_all(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+    ) -> Poll<std::io::Result<Vec<SocketAddr>>> {
+        poll_pending_resolution(&mut self.pending, cx, host, |host| {
+            let rx = Self::lookup(host);
+
+            async move {
+                rx.await.unwrap_or_else(|_| {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        "DNS resolver thread terminated unexpectedly",
+                    ))
+                })
+            }
+        })
     }
 }
 
-impl Display for InvalidIdleTimeout {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(self, f)
+#[cfg(all(test, feature = "self-signed"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn std_dns_resolver() {
+        let mut resolver = StdDnsResolver::default();
+
+        let addresses = resolver.resolve_all("localhost:4433").await.unwrap();
+        assert!(!addresses.is_empty());
+        assert!(addresses.iter().all(|address| address.ip().is_loopback()));
+
+        // An abandoned lookup does not prevent the next ones.
+        let _ = futures_util::poll!(resolver.resolve_all("localhost:4434"));
+        let address = resolver.resolve("localhost:4433").await.unwrap().unwrap();
+        assert_eq!(address.port(), 4433);
+    }
+
+    #[test]
+    fn address_family_select() {
+        let v4: SocketAddr = "127.0.0.1:4433".parse().unwrap();
+        let v6: SocketAddr = "[::1]:4433".parse().unwrap();
+
+        let addresses = [v6, v4];
+        assert_eq!(AddressFamily::System.select(&addresses, false), Some(v6));
+        assert_eq!(AddressFamily::PreferV4.select(&addresses, false), Some(v4));
+        assert_eq!(AddressFamily::PreferV6.select(&[v4], false), Some(v4));
+        assert_eq!(AddressFamily::Ipv6Only.select(&[v4], false), None);
+        assert_eq!(AddressFamily::Ipv4Only.select(&addresses, false), Some(v4));
+        assert_eq!(AddressFamily::PreferV6.select(&addresses, true), Some(v4));
+        assert_eq!(AddressFamily::Ipv6Only.select(&addresses, true), None);
+        assert_eq!(AddressFamily::System.select(&[], false), None);
+    }
+
+    #[test]
+    fn reordering_thresholds() {
+        let builder = || {
+            ClientConfig::builder()
+                .with_bind_default()
+                .with_root_certificates(RootCertStore::empty())
+        };
+
+        assert!(builder().packet_reordering_threshold(2).is_err());
+        assert!(builder().packet_reordering_threshold(3).is_ok());
+        assert!(builder().time_reordering_threshold(0.5).is_err());
+        assert!(builder().time_reordering_threshold(f32::NAN).is_err());
+        assert!(builder().time_reordering_threshold(1.5).is_ok());
+    }
+
+    #[test]
+    fn crypto_buffer_size() {
+        let builder = || {
+            ClientConfig::builder()
+                .with_bind_default()
+                .with_root_certificates(RootCertStore::empty())
+        };
+
+        assert!(builder().crypto_buffer_size(0).is_err());
+        assert!(builder().crypto_buffer_size(1199).is_err());
+        assert!(builder().crypto_buffer_size(64 * 1024).is_ok());
+    }
+
+    #[test]
+    fn root_certificates_pem() {
+        let first = rcgen::generate_simple_self_signed(["first".to_string()]).unwrap();
+        let second = rcgen::generate_simple_self_signed(["second".to_string()]).unwrap();
+
+        let pem = [
+            first.serialize_pem().unwrap(),
+            first.serialize_private_key_pem(),
+            second.serialize_pem().unwrap(),
+        ]
+        .concat();
+
+        assert!(ClientConfig::builder()
+            .with_bind_default()
+            .with_root_certificates_pem(pem.as_bytes())
+            .is_ok());
+
+        assert!(matches!(
+            ClientConfig::builder()
+                .with_bind_default()
+                .with_root_certificates_pem(first.serialize_private_key_pem().as_bytes()),
+            Err(PemRootCertsError::Empty)
+        ));
     }
 }
 
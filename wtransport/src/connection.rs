@@ -91,21 +91,71 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Dropping a connection
+//! A [`Connection`] is not a shared handle: dropping it stops processing the session
+//! (no further streams or datagrams are accepted). The underlying QUIC connection is
+//! implicitly closed (with error code `0`) as soon as the [`Connection`] *and* all its
+//! streams are dropped.
+//!
+//! Streams moved into detached tasks keep the connection alive until they are dropped.
+//! In order to keep the whole connection alive without holding the handle, use
+//! [`Connection::forget`].
 
+use crate::config::H3_INTERNAL_BIDI_STREAMS;
+use crate::config::H3_INTERNAL_UNI_STREAMS;
 use crate::datagram::Datagram;
+use crate::datagram::DatagramPriority;
 use crate::driver::utils::varint_w2q;
 use crate::driver::Driver;
+use crate::endpoint::normalize_addr;
 use crate::error::ConnectionError;
+use crate::error::ExportKeyingMaterialError;
+use crate::error::PauseIncomingError;
+use crate::error::ReceiveDatagramError;
 use crate::error::SendDatagramError;
 use crate::stream::OpeningBiStream;
 use crate::stream::OpeningUniStream;
 use crate::stream::RecvStream;
 use crate::stream::SendStream;
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
+use std::time::Instant;
 use wtransport_proto::ids::SessionId;
 use wtransport_proto::varint::VarInt;
 
+const MAX_DATAGRAM_SIZE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+const BANDWIDTH_ESTIMATE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Maximum size of keying material (see [`Connection::export_keying_material`]).
+///
+/// It is the HKDF output limit (255 times the hash length) for SHA-256, the smallest hash
+/// of TLS 1.3 cipher suites.
+pub const MAX_KEYING_MATERIAL_SIZE: usize = 255 * 32;
+
+/// Error code used to close connections exceeding their maximum lifetime (see
+/// [`ServerConfigBuilder::max_connection_lifetime`](crate::config::ServerConfigBuilder::max_connection_lifetime)).
+///
+/// Its value (`0x10000`) lies outside the ranges reserved by HTTP/3, QPACK and
+/// WebTransport, so clients can tell it apart and reconnect (e.g., re-authenticating).
+pub const LIFETIME_EXCEEDED_ERROR_CODE: VarInt = VarInt::from_u32(0x10000);
+
+/// Error code used to close connections whose migration is denied (see
+/// [`ServerConfigBuilder::migration_policy`](crate::config::ServerConfigBuilder::migration_policy)
+/// and [`ServerConfigBuilder::migration_rate_limit`](crate::config::ServerConfigBuilder::migration_rate_limit)).
+///
+/// Like [`LIFETIME_EXCEEDED_ERROR_CODE`], its value (`0x10001`) lies outside the ranges
+/// reserved by HTTP/3, QPACK and WebTransport.
+pub const MIGRATION_DENIED_ERROR_CODE: VarInt = VarInt::from_u32(0x10001);
+
 /// A WebTransport session connection.
 ///
 /// For more details, see the [module documentation](crate::connection).
@@ -114,6 +164,9 @@ pub struct Connection {
     quic_connection: quinn::Connection,
     driver: Driver,
     session_id: SessionId,
+    flow_control: Mutex<FlowControl>,
+    extensions: Mutex<Extensions>,
+    established_at: Instant,
 }
 
 impl Connection {
@@ -121,14 +174,27 @@ impl Connection {
         quic_connection: quinn::Connection,
         driver: Driver,
         session_id: SessionId,
+        receive_window: VarInt,
     ) -> Self {
         Self {
             quic_connection,
             driver,
             session_id,
+            flow_control: Mutex::new(FlowControl {
+                receive_window,
+                bounded: receive_window != VarInt::MAX,
+                paused: false,
+            }),
+            extensions: Mutex::new(Extensions::default()),
+            established_at: Instant::now(),
         }
     }
 
+    /// Closes the connection at `deadline`, with [`LIFETIME_EXCEEDED_ERROR_CODE`].
+    pub(crate) fn close_at(&self, deadline: Instant) {
+        self.driver.close_at(deadline);
+    }
+
     /// Asynchronously accepts a unidirectional stream.
     ///
     /// This method is used to accept incoming unidirectional streams that have been initiated
@@ -149,7 +215,49 @@ impl Connection {
             })?
             .into_stream();
 
-        Ok(RecvStream::new(stream))
+        Ok(RecvStream::new(stream, self.driver.bytes_counter().clone()))
+    }
+
+    /// Returns a [`Stream`](futures_core::Stream) of the unidirectional streams opened by the
+    /// peer.
+    ///
+    /// Each item is the outcome of [`accept_uni`](Self::accept_uni). Once an error is yielded
+    /// (i.e., the connection is closed), the stream terminates.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use wtransport::Connection;
+    /// # async fn run(connection: Connection) {
+    /// use futures_util::StreamExt;
+    ///
+    /// connection
+    ///     .incoming_uni()
+    ///     .for_each_concurrent(16, |stream| async move {
+    ///         if let Ok(stream) = stream {
+    ///             // ... read from `stream` ...
+    ///         }
+    ///     })
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg(feature = "futures")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+    pub fn incoming_uni(&self) -> IncomingStreams<'_, RecvStream> {
+        IncomingStreams::new(self, |connection| Box::pin(connection.accept_uni()))
+    }
+
+    /// Returns a [`Stream`](futures_core::Stream) of the bidirectional streams opened by the
+    /// peer.
+    ///
+    /// Each item is the outcome of [`accept_bi`](Self::accept_bi). Once an error is yielded
+    /// (i.e., the connection is closed), the stream terminates.
+    ///
+    /// See [`incoming_uni`](Self::incoming_uni) for an example.
+    #[cfg(feature = "futures")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+    pub fn incoming_bi(&self) -> IncomingStreams<'_, (SendStream, RecvStream)> {
+        IncomingStreams::new(self, |connection| Box::pin(connection.accept_bi()))
     }
 
     /// Asynchronously accepts a bidirectional stream.
@@ -173,7 +281,16 @@ impl Connection {
             })?
             .into_stream();
 
-        Ok((SendStream::new(stream.0), RecvStream::new(stream.1)))
+        let bytes_counter = self.driver.bytes_counter();
+
+        Ok((
+            SendStream::new(
+                stream.0,
+                bytes_counter.clone(),
+                self.driver.pending_send_streams().track(),
+            ),
+            RecvStream::new(stream.1, bytes_counter.clone()),
+        ))
     }
 
     /// Asynchronously opens a new unidirectional stream.
@@ -252,6 +369,9 @@ impl Connection {
     /// peer over the connection.
     /// It waits for a datagram to become available and returns the received [`Datagram`].
     ///
+    /// Only datagrams associated with the session of this connection are returned (see
+    /// [`Datagram`] for the encoding).
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -271,12 +391,79 @@ impl Connection {
             })
     }
 
+    /// Asynchronously receives an application datagram, copying its payload into `buffer`.
+    ///
+    /// Returns the size of the payload. This avoids allocations on the receive path, as the
+    /// same buffer can be reused for every datagram.
+    ///
+    /// Datagrams are atomic: they are never truncated nor partially delivered. If the next
+    /// datagram does not fit into `buffer`, [`ReceiveDatagramError::BufferTooSmall`] is
+    /// returned with the needed size, and the datagram is left in the receive buffer: it is
+    /// returned by the next call with a large enough buffer (or by
+    /// [`receive_datagram`](Self::receive_datagram)).
+    ///
+    /// A buffer of [`max_datagram_size`](Self::max_datagram_size) bytes is not necessarily
+    /// large enough, as it refers to outgoing datagrams.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use wtransport::Connection;
+    /// # use anyhow::Result;
+    /// # async fn run(connection: Connection) -> Result<()> {
+    /// let mut buffer = [0; 1500];
+    /// let len = connection.receive_datagram_into(&mut buffer).await?;
+    /// println!("Received: {:?}", &buffer[..len]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn receive_datagram_into(
+        &self,
+        buffer: &mut [u8],
+    ) -> Result<usize, ReceiveDatagramError> {
+        match self
+            .driver
+            .receive_datagram_into(self.session_id, buffer)
+            .await
+        {
+            Ok(Ok(len)) => Ok(len),
+            Ok(Err(needed)) => Err(ReceiveDatagramError::BufferTooSmall { needed }),
+            Err(driver_error) => Err(ReceiveDatagramError::Connection(
+                ConnectionError::with_driver_error(driver_error, &self.quic_connection),
+            )),
+        }
+    }
+
+    /// Returns the number of incoming datagrams discarded because the receive buffer was full.
+    ///
+    /// Datagrams are discarded (oldest first) when the application does not call
+    /// [`receive_datagram`](Self::receive_datagram) fast enough. The buffer size can be
+    /// configured with `datagram_receive_buffer_size` on
+    /// [`ServerConfigBuilder`](crate::config::ServerConfigBuilder::datagram_receive_buffer_size)
+    /// and [`ClientConfigBuilder`](crate::config::ClientConfigBuilder::datagram_receive_buffer_size).
+    ///
+    /// The counter is shared by all the sessions of the underlying QUIC connection.
+    pub fn datagrams_dropped_rx(&self) -> u64 {
+        self.driver.datagrams_dropped_rx()
+    }
+
     /// Sends an application datagram to the remote peer.
     ///
     /// This method is used to send an application datagram to the remote peer
     /// over the connection.
     /// The datagram payload is provided as a reference to a slice of bytes.
     ///
+    /// # Ownership
+    ///
+    /// `payload` is only borrowed for the duration of the call: it is copied once, right
+    /// after the session header, into the buffer handed over to the QUIC stack (a single
+    /// allocation per datagram). There is no need to convert it into an owned buffer (e.g.,
+    /// [`Bytes`](bytes::Bytes)) beforehand, which would only add a copy. A scratch buffer can be
+    /// reused for the next datagram as soon as this method returns.
+    ///
+    /// The same applies to [`send_datagram_with_priority`](Self::send_datagram_with_priority)
+    /// and [`send_datagram_wait`](Self::send_datagram_wait).
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -294,11 +481,416 @@ impl Connection {
         self.driver.send_datagram(self.session_id, payload.as_ref())
     }
 
+    /// Sends an application datagram to the remote peer, with a given [`DatagramPriority`].
+    ///
+    /// When the datagram send buffer fills up (i.e., datagrams are produced faster than
+    /// they can be transmitted), the priority decides whether `payload` is enqueued:
+    ///
+    /// - [`DatagramPriority::High`] datagrams are always enqueued, discarding the oldest ones
+    ///   still buffered if needed (as [`send_datagram`](Self::send_datagram) does).
+    /// - [`DatagramPriority::Normal`] datagrams are enqueued only if they fit in the buffer.
+    /// - [`DatagramPriority::Low`] datagrams are enqueued only if at least half of the buffer
+    ///   stays free.
+    ///
+    /// Otherwise, the datagram is discarded and [`SendDatagramError::BufferFull`] is returned.
+    /// Thus, low priority traffic (e.g., telemetry) cannot fill up the buffer at the expense of
+    /// higher priority datagrams (e.g., audio).
+    ///
+    /// **Note**: buffered datagrams are transmitted in FIFO order, regardless of their
+    /// priority: the priority only governs admission to the buffer. The buffer size is set
+    /// by the QUIC transport configuration (`datagram_send_buffer_size`, 1MiB by default):
+    /// a smaller buffer bounds the latency of the queued datagrams.
+    pub fn send_datagram_with_priority<D>(
+        &self,
+        payload: D,
+        priority: DatagramPriority,
+    ) -> Result<(), SendDatagramError>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.driver
+            .send_datagram_with_priority(self.session_id, payload.as_ref(), priority)
+    }
+
+    /// Sends an application datagram to the remote peer, waiting for send buffer space.
+    ///
+    /// Unlike [`send_datagram`](Self::send_datagram), which discards the oldest datagrams
+    /// still buffered when the send buffer is full, this method waits until there is
+    /// enough space to enqueue `payload` without dropping any previously sent datagram.
+    ///
+    /// Fails with [`SendDatagramError::TooLarge`] if `payload` does not fit even in an empty
+    /// send buffer (`datagram_send_buffer_size` of the QUIC transport configuration).
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. The datagram is enqueued only when the returned future
+    /// resolves: dropping it before completion guarantees the datagram has not been sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use wtransport::Connection;
+    /// # use anyhow::Result;
+    /// # async fn run(connection: Connection) -> Result<()> {
+    /// connection.send_datagram_wait(b"Hello, wtransport!").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_datagram_wait<D>(&self, payload: D) -> Result<(), SendDatagramError>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.driver
+            .send_datagram_wait(self.session_id, payload.as_ref())
+            .await
+    }
+
+    /// Releases this handle while keeping the connection alive.
+    ///
+    /// By default, dropping a [`Connection`] (and all its streams) closes it (see
+    /// [module documentation](crate::connection#dropping-a-connection)). Instead, after
+    /// calling this method, the connection is kept open in background until it is closed for
+    /// any other reason (e.g., by the peer, or because of the idle timeout).
+    ///
+    /// **Note**: as this consumes `self`, the connection can be no longer closed locally.
+    pub fn forget(self) {
+        let runtime = self.driver.runtime().clone();
+
+        runtime.spawn(Box::pin(async move {
+            self.closed().await;
+        }));
+    }
+
+    /// Splits this connection into a sending half and a receiving half.
+    ///
+    /// The [`ConnectionSender`] opens streams and sends datagrams, while the
+    /// [`ConnectionReceiver`] accepts streams and receives datagrams. Both halves are `Send`,
+    /// so they can be moved to different tasks, making task responsibilities explicit.
+    ///
+    /// The connection is closed once both halves (and all streams) are dropped, or explicitly
+    /// with [`ConnectionSender::close`]. The halves can be joined back with
+    /// [`ConnectionSender::reunite`].
+    pub fn split(self) -> (ConnectionSender, ConnectionReceiver) {
+        let connection = Arc::new(self);
+        (
+            ConnectionSender(connection.clone()),
+            ConnectionReceiver(connection),
+        )
+    }
+
+    /// Pauses receiving data from the peer, applying backpressure.
+    ///
+    /// The connection stops granting the peer additional flow-control credit, so the peer
+    /// cannot send more stream data than it is already allowed to. No data is dropped:
+    /// transmission is only suspended until [`resume_incoming`](Self::resume_incoming)
+    /// is called.
+    ///
+    /// **Note**: while paused, internal HTTP/3 streams are affected as well. Datagrams are
+    /// not subject to flow control, and they are still received.
+    ///
+    /// # Receive window
+    ///
+    /// This leverages QUIC connection-level flow control, and flow-control credit cannot be
+    /// revoked. Hence, pausing requires a finite connection receive window, configured with
+    /// [`ServerConfigBuilder::max_connection_receive_memory`](crate::config::ServerConfigBuilder::max_connection_receive_memory)
+    /// or [`ClientConfigBuilder::max_connection_receive_memory`](crate::config::ClientConfigBuilder::max_connection_receive_memory):
+    /// while paused, the peer can send at most one window of data.
+    ///
+    /// The default window is unlimited ([`VarInt::MAX`]): the peer was granted unlimited
+    /// credit during the handshake, and it is only bound by the per-stream windows. In that
+    /// case, this method fails with [`PauseIncomingError`], and the connection is not paused.
+    /// Setting a finite window later with [`set_receive_window`](Self::set_receive_window)
+    /// does not reduce that credit either.
+    pub fn pause_incoming(&self) -> Result<(), PauseIncomingError> {
+        let mut flow_control = self.flow_control.lock().expect("Mutex is not poisoned");
+
+        if !flow_control.bounded {
+            return Err(PauseIncomingError);
+        }
+
+        if !flow_control.paused {
+            flow_control.paused = true;
+            self.quic_connection
+                .set_receive_window(quinn::VarInt::from_u32(0));
+        }
+
+        Ok(())
+    }
+
+    /// Resumes receiving data from the peer, after [`pause_incoming`](Self::pause_incoming).
+    ///
+    /// The connection receive window is restored, and the peer is granted credit again.
+    ///
+    /// **Note**: the restored window is the one last set with
+    /// [`set_receive_window`](Self::set_receive_window), or configured with
+    /// `max_connection_receive_memory`. A receive window set directly on the QUIC transport
+    /// configuration cannot be inspected, hence it is not restored: by default, the window
+    /// becomes unlimited.
+    pub fn resume_incoming(&self) {
+        let mut flow_control = self.flow_control.lock().expect("Mutex is not poisoned");
+
+        if flow_control.paused {
+            flow_control.paused = false;
+            self.quic_connection
+                .set_receive_window(varint_w2q(flow_control.receive_window));
+        }
+    }
+
+    /// Returns `true` if receiving has been paused with
+    /// [`pause_incoming`](Self::pause_incoming).
+    pub fn is_incoming_paused(&self) -> bool {
+        self.flow_control
+            .lock()
+            .expect("Mutex is not poisoned")
+            .paused
+    }
+
+    /// Sets the connection-level receive window (in bytes).
+    ///
+    /// This is the maximum amount of data, across all streams, the peer is allowed to send
+    /// beyond what the application already consumed. Raising it grows the peer's send
+    /// allowance immediately (e.g., a download discovering a high bandwidth-delay product
+    /// can avoid being flow-control bound).
+    ///
+    /// Flow-control credit cannot be revoked: lowering the window does not reduce the
+    /// allowance already granted to the peer, and it only takes effect as data is consumed.
+    ///
+    /// If receiving is [paused](Self::pause_incoming), the new window is applied on
+    /// [resume](Self::resume_incoming).
+    ///
+    /// Values larger than [`VarInt::MAX`] are clamped.
+    pub fn set_receive_window(&self, receive_window: u64) {
+        let receive_window = VarInt::try_from_u64(receive_window).unwrap_or(VarInt::MAX);
+
+        let mut flow_control = self.flow_control.lock().expect("Mutex is not poisoned");
+        flow_control.receive_window = receive_window;
+
+        if !flow_control.paused {
+            self.quic_connection
+                .set_receive_window(varint_w2q(receive_window));
+        }
+    }
+
+    /// Sets the maximum number of bidirectional streams the peer may concurrently open.
+    ///
+    /// Raising the limit grants an updated stream credit (`MAX_STREAMS`) to the peer. For
+    /// instance, a server can start with a conservative
+    /// [`initial_max_bidi_streams`](crate::config::ServerConfigBuilder::initial_max_bidi_streams)
+    /// and grant more streams once a client is authenticated.
+    ///
+    /// Stream credit cannot be revoked: limits can effectively only be raised. Lowering the
+    /// limit does not affect the streams already granted to the peer, and it only takes effect
+    /// as open streams are closed.
+    ///
+    /// Only WebTransport streams are accounted: internal HTTP/3 streams are granted in
+    /// addition.
+    ///
+    /// **Note**: the QUIC stack sends the new credit along with the response to the next
+    /// packet received from the peer. On an idle connection, this may be delayed until the
+    /// peer sends anything (e.g., keep-alive packets, see
+    /// [`keep_alive_interval`](crate::config::ServerConfigBuilder::keep_alive_interval)).
+    pub fn set_max_concurrent_bidi_streams(&self, max: u32) {
+        // The session stream is opened by the client
+        let internal = if self.driver.is_server() {
+            H3_INTERNAL_BIDI_STREAMS
+        } else {
+            0
+        };
+
+        self.quic_connection.set_max_concurrent_bi_streams(
+            quinn::VarInt::from_u64(u64::from(max) + internal).expect("Value fits varint"),
+        );
+    }
+
+    /// Sets the maximum number of unidirectional streams the peer may concurrently open.
+    ///
+    /// See [`set_max_concurrent_bidi_streams`](Self::set_max_concurrent_bidi_streams).
+    ///
+    /// Only WebTransport streams are accounted: the HTTP/3 control and QPACK streams of the
+    /// peer are granted in addition.
+    pub fn set_max_concurrent_uni_streams(&self, max: u32) {
+        self.quic_connection.set_max_concurrent_uni_streams(
+            quinn::VarInt::from_u64(u64::from(max) + H3_INTERNAL_UNI_STREAMS)
+                .expect("Value fits varint"),
+        );
+    }
+
+    /// Sends an HTTP/3 GOAWAY frame to the peer, initiating a graceful shutdown.
+    ///
+    /// The current WebTransport session is not affected: streams and datagrams keep flowing
+    /// until the connection is eventually closed. Any further session request (CONNECT)
+    /// arriving on this connection is rejected.
+    ///
+    /// The frame is sent in background, at most once. Calling this method multiple times
+    /// has no additional effect.
+    ///
+    /// This is useful for rolling restarts: stop accepting new connections, send GOAWAY
+    /// to existing ones, and then wait for them to terminate
+    /// (see [`Endpoint::wait_idle`](crate::Endpoint::wait_idle)).
+    pub fn goaway(&self) {
+        self.driver.goaway();
+    }
+
+    /// Returns `true` if the peer has sent a GOAWAY frame.
+    ///
+    /// The peer is shutting down gracefully: the current session can be completed,
+    /// but new sessions should be established with a different connection.
+    pub fn goaway_received(&self) -> bool {
+        self.driver.goaway_received()
+    }
+
+    /// Sends a `DRAIN_WEBTRANSPORT_SESSION` capsule to the peer, requesting a graceful
+    /// wind-down of this session.
+    ///
+    /// Unlike [`close`](Self::close), the session is not terminated: it is a hint for the peer
+    /// to stop opening new streams, while finishing existing ones. It is up to the
+    /// application to eventually close the session.
+    ///
+    /// Unlike [`goaway`](Self::goaway), which concerns new sessions on the whole HTTP/3
+    /// connection, this only applies to the current WebTransport session.
+    ///
+    /// The capsule is sent in background, at most once. Calling this method multiple times
+    /// has no additional effect.
+    pub fn drain(&self) {
+        self.driver.drain();
+    }
+
+    /// Returns `true` if the peer has sent a `DRAIN_WEBTRANSPORT_SESSION` capsule.
+    pub fn drain_received(&self) -> bool {
+        self.driver.drain_received()
+    }
+
+    /// Waits for the peer to request a graceful wind-down of this session.
+    ///
+    /// It completes as soon as a `DRAIN_WEBTRANSPORT_SESSION` capsule is received
+    /// (see [`drain`](Self::drain)), or immediately if it was already received.
+    ///
+    /// Returns an error if the connection is closed before.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn on_drain(&self) -> Result<(), ConnectionError> {
+        self.driver.on_drain().await.map_err(|driver_error| {
+            ConnectionError::with_driver_error(driver_error, &self.quic_connection)
+        })
+    }
+
+    /// Attaches an application-defined value to the connection, keyed by its type.
+    ///
+    /// This allows storing per-session context (e.g., user identifier, authentication claims)
+    /// that can be retrieved later with [`get_extension`](Self::get_extension), from any task
+    /// sharing the connection.
+    ///
+    /// At most one value per type is stored: if a value of the same type was already
+    /// present, it is replaced and returned.
+    pub fn set_extension<T>(&self, value: T) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.extensions
+            .lock()
+            .expect("Mutex is not poisoned")
+            .0
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    /// Returns a clone of the value of type `T` attached with
+    /// [`set_extension`](Self::set_extension), if any.
+    ///
+    /// Values that are expensive to clone (or not clonable at all) can be wrapped in
+    /// an [`Arc`](std::sync::Arc).
+    pub fn get_extension<T>(&self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.extensions
+            .lock()
+            .expect("Mutex is not poisoned")
+            .0
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    }
+
+    /// Removes and returns the value of type `T` attached with
+    /// [`set_extension`](Self::set_extension), if any.
+    pub fn remove_extension<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.extensions
+            .lock()
+            .expect("Mutex is not poisoned")
+            .0
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Returns the current state of the connection.
+    ///
+    /// A [`Connection`] is only available once the WebTransport session is established:
+    /// the *connecting* phase is represented by the future returned by
+    /// [`Endpoint::connect`](crate::Endpoint::connect) (client) or by
+    /// [`IncomingSession`](crate::endpoint::IncomingSession) and
+    /// [`SessionRequest`](crate::endpoint::SessionRequest) (server).
+    pub fn state(&self) -> ConnectionState {
+        if self.quic_connection.close_reason().is_some() {
+            ConnectionState::Closed
+        } else if self.driver.goaway_sent()
+            || self.driver.goaway_received()
+            || self.driver.drain_sent()
+            || self.driver.drain_received()
+        {
+            ConnectionState::Draining
+        } else {
+            ConnectionState::Connected
+        }
+    }
+
     /// Closes the connection immediately.
     pub fn close(&self, error_code: VarInt, reason: &[u8]) {
         self.quic_connection.close(varint_w2q(error_code), reason);
     }
 
+    /// Closes the connection once all send streams are finished, or after `timeout`.
+    ///
+    /// Unlike [`close`](Self::close), which discards any data not yet delivered, this waits
+    /// for every open [`SendStream`] to be [finished](SendStream::finish) (i.e., all its
+    /// data acknowledged by the peer), [reset](SendStream::reset), or dropped. Then the
+    /// connection is closed with `error_code` and `reason`.
+    ///
+    /// Note that the streams are not finished by this method: it is up to their owners to do
+    /// so (e.g., when an upload is complete), while no new stream should be opened.
+    ///
+    /// If `timeout` expires first, the connection is closed abruptly anyway, as with
+    /// [`close`](Self::close): the remaining stream data may be truncated.
+    ///
+    /// Returns `true` if all send streams were finished in time, `false` on timeout.
+    ///
+    /// **Note**: a stream dropped without being finished only *schedules* its finish
+    /// (see [`SendStream`]), hence its data is not awaited.
+    pub async fn close_gracefully(
+        &self,
+        timeout: Duration,
+        error_code: VarInt,
+        reason: &[u8],
+    ) -> bool {
+        let mut timer = self.driver.runtime().new_timer(Instant::now() + timeout);
+        let timeout = std::future::poll_fn(|cx| timer.as_mut().poll(cx));
+
+        let finished = tokio::select! {
+            _ = self.driver.pending_send_streams().wait_none() => true,
+            _ = timeout => false,
+        };
+
+        self.close(error_code, reason);
+        finished
+    }
+
     /// Waits for the connection to be closed for any reason.
     pub async fn closed(&self) {
         let _ = self.quic_connection.closed().await;
@@ -312,22 +904,129 @@ impl Connection {
 
     /// Returns the peer's UDP address.
     ///
+    /// IPv4-mapped IPv6 addresses (e.g., `::ffff:1.2.3.4`, as reported by dual-stack
+    /// sockets for IPv4 peers) are normalized to plain IPv4 (see
+    /// [`normalize_addr`](crate::endpoint::normalize_addr)).
+    ///
     /// **Note**: as QUIC supports migration, remote address may change
     /// during connection.
+    ///
+    /// The new address is reported as soon as the peer migrates, that is *before* the new
+    /// path is validated: the QUIC stack limits the amount of data sent to an
+    /// unvalidated address, but does not expose the validation state.
     #[inline(always)]
     pub fn remote_address(&self) -> SocketAddr {
-        self.quic_connection.remote_address()
+        normalize_addr(self.quic_connection.remote_address())
+    }
+
+    /// Returns the local IP address which was used when the peer established the connection.
+    ///
+    /// This can be different from the address the endpoint is bound to, in case the
+    /// endpoint is bound to a wildcard address like `0.0.0.0` or `::`.
+    ///
+    /// IPv4-mapped IPv6 addresses are normalized to plain IPv4.
+    ///
+    /// Returns `None` for client connections, or if the platform does not support it.
+    #[inline(always)]
+    pub fn local_ip(&self) -> Option<IpAddr> {
+        self.quic_connection
+            .local_ip()
+            .map(|ip| normalize_addr(SocketAddr::new(ip, 0)).ip())
+    }
+
+    /// Returns `true` if the connection is established over IPv6.
+    ///
+    /// An IPv4-mapped IPv6 peer address (e.g., `::ffff:1.2.3.4`, as reported by dual-stack
+    /// sockets for IPv4 peers) is considered IPv4.
+    ///
+    /// **Note**: as QUIC supports migration, this might change during connection.
+    pub fn is_ipv6(&self) -> bool {
+        self.remote_address().is_ipv6()
     }
 
     /// A stable identifier for this connection.
     ///
     /// Peer addresses and connection IDs can change, but this value will remain
     /// fixed for the lifetime of the connection.
+    ///
+    /// The value is unique among the connections of the process, and it is reported
+    /// as `quic_id` in the connection tracing spans. It is *not* a QUIC connection ID:
+    /// it never appears on the wire, so it cannot be matched against the connection IDs
+    /// logged by a load balancer. Wire connection IDs are not exposed by the QUIC stack.
     #[inline(always)]
     pub fn stable_id(&self) -> usize {
         self.quic_connection.stable_id()
     }
 
+    /// Returns the instant the WebTransport session was established.
+    ///
+    /// This is when the session request was accepted by the server (server side), or when
+    /// the session response was received (client side).
+    #[inline(always)]
+    pub fn established_at(&self) -> Instant {
+        self.established_at
+    }
+
+    /// Returns the time elapsed since the WebTransport session was established.
+    ///
+    /// This can be used, for instance, to close sessions older than a maximum lifetime.
+    #[inline(always)]
+    pub fn uptime(&self) -> Duration {
+        self.established_at.elapsed()
+    }
+
+    /// Derives keying material from the TLS session of this connection (RFC 5705, as
+    /// defined for TLS 1.3 by RFC 8446, Section 7.5), filling `output`.
+    ///
+    /// Both peers obtain the same value for the same `label` and `context`, while it is
+    /// unique to this TLS session. This allows binding application-level credentials
+    /// (e.g., authentication tokens) to the connection, preventing their replay on other
+    /// connections.
+    ///
+    /// In TLS 1.3, no `context` is equivalent to an empty one.
+    ///
+    /// Returns an error if `output` is larger than [`MAX_KEYING_MATERIAL_SIZE`] bytes.
+    pub fn export_keying_material(
+        &self,
+        label: &[u8],
+        context: Option<&[u8]>,
+        output: &mut [u8],
+    ) -> Result<(), ExportKeyingMaterialError> {
+        if output.len() > MAX_KEYING_MATERIAL_SIZE {
+            return Err(ExportKeyingMaterialError);
+        }
+
+        self.quic_connection
+            .export_keying_material(output, label, context.unwrap_or_default())
+            .map_err(|_| ExportKeyingMaterialError)
+    }
+
+    /// Returns the certificate chain presented by the peer during the TLS handshake, leaf
+    /// certificate first, followed by the intermediates (in the order sent by the peer).
+    ///
+    /// On a client, this is the chain of the server. On a server, it is `None` unless the
+    /// client authenticated with a certificate.
+    ///
+    /// Certificates are DER encoded. The chain is exactly what the peer sent: it is not
+    /// necessarily the path validated against the trusted root certificates.
+    pub fn peer_certificates(&self) -> Option<Vec<rustls::Certificate>> {
+        self.quic_connection
+            .peer_identity()?
+            .downcast::<Vec<rustls::Certificate>>()
+            .ok()
+            .map(|certificates| *certificates)
+    }
+
+    /// Returns `true` if datagrams can be exchanged on this connection.
+    ///
+    /// Both peers must support QUIC datagrams (see
+    /// [`max_datagram_size`](Self::max_datagram_size)), and the peer must have enabled
+    /// HTTP/3 datagrams (`SETTINGS_H3_DATAGRAM`). This allows an application to decide
+    /// upfront whether to use datagrams, or to fall back to streams.
+    pub fn datagrams_supported(&self) -> bool {
+        self.quic_connection.max_datagram_size().is_some() && self.driver.peer_h3_datagrams()
+    }
+
     /// Computes the maximum size of datagrams that may be passed to
     /// [`send_datagram`](Self::send_datagram).
     ///
@@ -337,6 +1036,9 @@ impl Connection {
     /// estimate. The peer can also enforce an arbitrarily small fixed limit, but if the peer's
     /// limit is large this is guaranteed to be a little over a kilobyte at minimum.
     ///
+    /// The peer's limit is its `max_datagram_frame_size` transport parameter, minus the
+    /// frame and WebTransport headers. The negotiated parameter itself is not exposed.
+    ///
     /// Not necessarily the maximum size of received datagrams.
     #[inline(always)]
     pub fn max_datagram_size(&self) -> Option<usize> {
@@ -345,9 +1047,1036 @@ impl Connection {
             .map(|quic_max_size| quic_max_size - Datagram::header_size(self.session_id))
     }
 
+    /// Waits for [`max_datagram_size`](Self::max_datagram_size) to differ from `last`,
+    /// and returns the new value.
+    ///
+    /// The maximum datagram size changes as MTU discovery refines the path MTU estimate
+    /// (or if the path changes). This allows, for instance, an encoder to adapt its packet
+    /// size, by calling this method in a loop with the last observed value.
+    ///
+    /// Returns an error if the connection is closed.
+    ///
+    /// **Note**: the QUIC stack does not notify MTU updates, hence the value is checked
+    /// periodically.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn max_datagram_size_changed(
+        &self,
+        last: Option<usize>,
+    ) -> Result<Option<usize>, ConnectionError> {
+        loop {
+            if let Some(close_reason) = self.quic_connection.close_reason() {
+                return Err(close_reason.into());
+            }
+
+            let current = self.max_datagram_size();
+            if current != last {
+                return Ok(current);
+            }
+
+            let mut timer = self
+                .driver
+                .runtime()
+                .new_timer(Instant::now() + MAX_DATAGRAM_SIZE_CHECK_INTERVAL);
+            std::future::poll_fn(|cx| timer.as_mut().poll(cx)).await;
+        }
+    }
+
     /// Current best estimate of this connection's latency (round-trip-time).
     #[inline(always)]
     pub fn rtt(&self) -> Duration {
         self.quic_connection.rtt()
     }
+
+    /// Current estimate of the bandwidth available for sending (in bytes per second).
+    ///
+    /// This is derived from the congestion controller state, as the congestion window over
+    /// the round-trip time. It can drive adaptive applications, e.g., a video encoder picking
+    /// its bitrate.
+    ///
+    /// **Note**: this is a rough estimate. At the beginning of the connection (slow start),
+    /// and when the application does not send enough data to fill the congestion window, the
+    /// estimate does not reflect the path capacity, and it might be largely over- or
+    /// under-estimated. Loss-based congestion controllers also periodically shrink the window
+    /// to probe the path, so short-term fluctuations are expected.
+    pub fn bandwidth_estimate(&self) -> u64 {
+        let path_stats = self.quic_connection.stats().path;
+        let rtt_nanos = path_stats.rtt.as_nanos().max(1);
+
+        (u128::from(path_stats.cwnd) * 1_000_000_000 / rtt_nanos)
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Waits for [`bandwidth_estimate`](Self::bandwidth_estimate) to significantly differ
+    /// from `last` (by 10% or more), and returns the new value.
+    ///
+    /// This allows an application to adapt its sending rate by calling this method in a
+    /// loop with the last observed value, rather than polling the estimate.
+    ///
+    /// Returns an error if the connection is closed.
+    ///
+    /// **Note**: the QUIC stack does not notify congestion window updates, hence the value is
+    /// checked periodically.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn bandwidth_estimate_changed(&self, last: u64) -> Result<u64, ConnectionError> {
+        loop {
+            if let Some(close_reason) = self.quic_connection.close_reason() {
+                return Err(close_reason.into());
+            }
+
+            let current = self.bandwidth_estimate();
+            if current.abs_diff(last) >= (last / 10).max(1) {
+                return Ok(current);
+            }
+
+            let mut timer = self
+                .driver
+                .runtime()
+                .new_timer(Instant::now() + BANDWIDTH_ESTIMATE_CHECK_INTERVAL);
+            std::future::poll_fn(|cx| timer.as_mut().poll(cx)).await;
+        }
+    }
+
+    /// Returns the number of application payload bytes sent on this session.
+    ///
+    /// It accounts for data written on streams and datagrams sent, excluding any protocol
+    /// overhead (e.g., stream headers, QUIC framing or retransmissions).
+    ///
+    /// **Note**: data written directly on the underlying QUIC streams (see `quic_stream_mut`)
+    /// is not accounted.
+    pub fn bytes_sent(&self) -> u64 {
+        self.driver.bytes_counter().sent()
+    }
+
+    /// Returns the number of application payload bytes received on this session.
+    ///
+    /// It accounts for data read from streams and datagrams received, excluding any protocol
+    /// overhead.
+    ///
+    /// **Note**: data read directly from the underlying QUIC streams (see `quic_stream_mut`)
+    /// is not accounted.
+    pub fn bytes_received(&self) -> u64 {
+        self.driver.bytes_counter().received()
+    }
+
+    /// Returns the number of keep-alive (QUIC `PING`) frames sent on this connection.
+    ///
+    /// Keep-alive frames are emitted according to the configured `keep_alive_interval`.
+    /// This can be used to correlate radio wakeups with keep-alive configuration.
+    ///
+    /// **Note**: the QUIC stack may also send `PING` frames for other purposes
+    /// (e.g., loss-detection probes), which are included in this count.
+    pub fn keep_alive_sent(&self) -> u64 {
+        self.quic_connection.stats().frame_tx.ping
+    }
+
+    /// Returns the number of times the peer reported being blocked by the stream limits.
+    ///
+    /// This counts the `STREAMS_BLOCKED` frames (both directions) received from the peer: a
+    /// peer sends them when it wants to open a stream, but the maximum number of concurrent
+    /// streams has been reached. A growing count means the peer is throttled by the stream
+    /// limits (see [`initial_max_bidi_streams`](crate::config::ServerConfigBuilder::initial_max_bidi_streams)
+    /// and [`set_max_concurrent_bidi_streams`](Self::set_max_concurrent_bidi_streams)), that is,
+    /// the application does not accept and complete streams fast enough, or the limits are too
+    /// low for the workload.
+    ///
+    /// **Note**: sending these frames is optional. Browsers do, but peers using this crate
+    /// (or, in general, the QUIC stack it is built on) never do, hence the counter stays zero.
+    pub fn stream_limit_reached_count(&self) -> u64 {
+        let frame_rx = self.quic_connection.stats().frame_rx;
+        frame_rx.streams_blocked_bidi + frame_rx.streams_blocked_uni
+    }
+
+    /// Returns a snapshot of the packet counters of this connection.
+    ///
+    /// Counters are cumulative since the connection establishment: the loss over a
+    /// measurement window (e.g., one frame of a media encoder) is obtained sampling them
+    /// at the window boundaries (see [`PacketCounters::loss_rate_since`]).
+    ///
+    /// Sampling is cheap, so it can be performed frequently.
+    ///
+    /// **Note**: a packet is declared lost by the QUIC loss detection, that is, roughly
+    /// one round-trip time after it has been sent. Thus, loss is reported with such delay.
+    pub fn packet_counters(&self) -> PacketCounters {
+        let path_stats = self.quic_connection.stats().path;
+
+        PacketCounters {
+            sent: path_stats.sent_packets,
+            lost: path_stats.lost_packets,
+        }
+    }
+
+    /// Returns a snapshot of the statistics of this connection.
+    pub fn stats(&self) -> ConnectionStats {
+        let quic_stats = self.quic_connection.stats();
+
+        ConnectionStats {
+            rtt: quic_stats.path.rtt,
+            congestion_window: quic_stats.path.cwnd,
+            bytes_sent: self.bytes_sent(),
+            bytes_received: self.bytes_received(),
+            packets: PacketCounters {
+                sent: quic_stats.path.sent_packets,
+                lost: quic_stats.path.lost_packets,
+            },
+            datagrams: DatagramStats {
+                sent: quic_stats.frame_tx.datagram,
+                received: quic_stats.frame_rx.datagram,
+                dropped_rx: self.datagrams_dropped_rx(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+type AcceptFuture<'a, T> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, ConnectionError>> + Send + 'a>>;
+
+/// A [`Stream`](futures_core::Stream) of the streams opened by the peer.
+///
+/// See [`Connection::incoming_uni`] and [`Connection::incoming_bi`].
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "streams do nothing unless polled"]
+pub struct IncomingStreams<'a, T> {
+    connection: &'a Connection,
+    accept: fn(&'a Connection) -> AcceptFuture<'a, T>,
+    pending: Option<AcceptFuture<'a, T>>,
+    terminated: bool,
+}
+
+#[cfg(feature = "futures")]
+impl<'a, T> IncomingStreams<'a, T> {
+    fn new(connection: &'a Connection, accept: fn(&'a Connection) -> AcceptFuture<'a, T>) -> Self {
+        Self {
+            connection,
+            accept,
+            pending: None,
+            terminated: false,
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<'a, T> futures_core::Stream for IncomingStreams<'a, T> {
+    type Item = Result<T, ConnectionError>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        if self.terminated {
+            return std::task::Poll::Ready(None);
+        }
+
+        let connection = self.connection;
+        let accept = self.accept;
+        let pending = self.pending.get_or_insert_with(|| accept(connection));
+
+        let result = std::task::ready!(pending.as_mut().poll(cx));
+        self.pending = None;
+        self.terminated = result.is_err();
+
+        std::task::Poll::Ready(Some(result))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<'a, T> futures_core::FusedStream for IncomingStreams<'a, T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<'a, T> Debug for IncomingStreams<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncomingStreams")
+            .field("terminated", &self.terminated)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a [`Connection`].
+///
+/// See [`Connection::split`].
+#[derive(Debug)]
+pub struct ConnectionSender(Arc<Connection>);
+
+impl ConnectionSender {
+    /// See [`Connection::open_uni`].
+    pub async fn open_uni(&self) -> Result<OpeningUniStream, ConnectionError> {
+        self.0.open_uni().await
+    }
+
+    /// See [`Connection::open_bi`].
+    pub async fn open_bi(&self) -> Result<OpeningBiStream, ConnectionError> {
+        self.0.open_bi().await
+    }
+
+    /// See [`Connection::send_datagram`].
+    pub fn send_datagram<D>(&self, payload: D) -> Result<(), SendDatagramError>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.0.send_datagram(payload)
+    }
+
+    /// See [`Connection::send_datagram_with_priority`].
+    pub fn send_datagram_with_priority<D>(
+        &self,
+        payload: D,
+        priority: DatagramPriority,
+    ) -> Result<(), SendDatagramError>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.0.send_datagram_with_priority(payload, priority)
+    }
+
+    /// See [`Connection::send_datagram_wait`].
+    pub async fn send_datagram_wait<D>(&self, payload: D) -> Result<(), SendDatagramError>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.0.send_datagram_wait(payload).await
+    }
+
+    /// See [`Connection::datagrams_supported`].
+    pub fn datagrams_supported(&self) -> bool {
+        self.0.datagrams_supported()
+    }
+
+    /// See [`Connection::max_datagram_size`].
+    pub fn max_datagram_size(&self) -> Option<usize> {
+        self.0.max_datagram_size()
+    }
+
+    /// See [`Connection::close`].
+    pub fn close(&self, error_code: VarInt, reason: &[u8]) {
+        self.0.close(error_code, reason)
+    }
+
+    /// See [`Connection::session_id`].
+    pub fn session_id(&self) -> SessionId {
+        self.0.session_id()
+    }
+
+    /// Joins the two halves back into the original [`Connection`].
+    ///
+    /// Fails, giving the halves back, if they come from different connections.
+    pub fn reunite(
+        self,
+        receiver: ConnectionReceiver,
+    ) -> Result<Connection, (ConnectionSender, ConnectionReceiver)> {
+        if !Arc::ptr_eq(&self.0, &receiver.0) {
+            return Err((self, receiver));
+        }
+
+        drop(receiver);
+
+        Ok(Arc::try_unwrap(self.0).expect("The other half has been dropped"))
+    }
+}
+
+/// The receiving half of a [`Connection`].
+///
+/// See [`Connection::split`].
+#[derive(Debug)]
+pub struct ConnectionReceiver(Arc<Connection>);
+
+impl ConnectionReceiver {
+    /// See [`Connection::accept_uni`].
+    pub async fn accept_uni(&self) -> Result<RecvStream, ConnectionError> {
+        self.0.accept_uni().await
+    }
+
+    /// See [`Connection::accept_bi`].
+    pub async fn accept_bi(&self) -> Result<(SendStream, RecvStream), ConnectionError> {
+        self.0.accept_bi().await
+    }
+
+    /// See [`Connection::receive_datagram`].
+    pub async fn receive_datagram(&self) -> Result<Datagram, ConnectionError> {
+        self.0.receive_datagram().await
+    }
+
+    /// See [`Connection::receive_datagram_into`].
+    pub async fn receive_datagram_into(
+        &self,
+        buffer: &mut [u8],
+    ) -> Result<usize, ReceiveDatagramError> {
+        self.0.receive_datagram_into(buffer).await
+    }
+
+    /// See [`Connection::closed`].
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+
+    /// See [`Connection::session_id`].
+    pub fn session_id(&self) -> SessionId {
+        self.0.session_id()
+    }
+}
+
+/// Snapshot of connection statistics.
+///
+/// See [`Connection::stats`].
+///
+/// With the `serde` feature enabled, it implements `serde::Serialize`, so it can be
+/// exported directly to telemetry pipelines.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConnectionStats {
+    /// Current best estimate of the round-trip time.
+    pub rtt: Duration,
+
+    /// Current congestion window (in bytes).
+    pub congestion_window: u64,
+
+    /// Application payload bytes sent (see [`Connection::bytes_sent`]).
+    pub bytes_sent: u64,
+
+    /// Application payload bytes received (see [`Connection::bytes_received`]).
+    pub bytes_received: u64,
+
+    /// Packet counters (see [`Connection::packet_counters`]).
+    pub packets: PacketCounters,
+
+    /// Datagram counters.
+    pub datagrams: DatagramStats,
+}
+
+/// Datagram counters of a connection.
+///
+/// See [`ConnectionStats`].
+///
+/// With the `serde` feature enabled, it implements `serde::Serialize`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DatagramStats {
+    /// Number of datagrams sent.
+    pub sent: u64,
+
+    /// Number of datagrams received.
+    pub received: u64,
+
+    /// Number of received datagrams discarded (see [`Connection::datagrams_dropped_rx`]).
+    pub dropped_rx: u64,
+}
+
+/// Snapshot of connection packet counters.
+///
+/// See [`Connection::packet_counters`].
+///
+/// With the `serde` feature enabled, it implements `serde::Serialize`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PacketCounters {
+    /// Number of UDP packets sent.
+    pub sent: u64,
+
+    /// Number of packets declared lost.
+    pub lost: u64,
+}
+
+impl PacketCounters {
+    /// Returns the fraction (from `0.0` to `1.0`) of packets lost between `earlier` and `self`.
+    ///
+    /// Returns `0.0` if no packet has been sent in the meantime.
+    pub fn loss_rate_since(&self, earlier: &PacketCounters) -> f64 {
+        let sent = self.sent.saturating_sub(earlier.sent);
+        let lost = self.lost.saturating_sub(earlier.lost);
+
+        if sent == 0 {
+            return 0.0;
+        }
+
+        (lost as f64 / sent as f64).min(1.0)
+    }
+}
+
+/// State of a [`Connection`].
+///
+/// See [`Connection::state`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConnectionState {
+    /// The WebTransport session is established.
+    Connected,
+
+    /// The connection is shutting down gracefully, as a GOAWAY frame (see
+    /// [`Connection::goaway`]) or a `DRAIN_WEBTRANSPORT_SESSION` capsule (see
+    /// [`Connection::drain`]) has been sent or received. The current session is still
+    /// operative.
+    Draining,
+
+    /// The connection is closed (locally, by the peer, or because of an error).
+    /// No more data can be exchanged.
+    Closed,
+}
+
+/// Connection-level receive flow control state.
+#[derive(Debug)]
+struct FlowControl {
+    receive_window: VarInt,
+    /// Whether the credit granted to the peer during the handshake is bounded.
+    bounded: bool,
+    paused: bool,
+}
+
+/// Application-defined values attached to a connection, keyed by type.
+#[derive(Default)]
+struct Extensions(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "self-signed"))]
+mod tests {
+    use super::*;
+    use crate::endpoint::endpoint_side::Server;
+    use crate::test_util::connected_pair;
+    use crate::test_util::connected_pair_with;
+    use crate::test_util::connected_pair_with_transport;
+    use crate::Endpoint;
+    use futures_util::FutureExt;
+    use tokio::time::timeout;
+    use wtransport_proto::ids::StreamId;
+
+    #[tokio::test]
+    async fn send_datagram_wait() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        client.send_datagram_wait(b"payload").await.unwrap();
+
+        let datagram = server.receive_datagram().await.unwrap();
+        assert_eq!(&*datagram, b"payload");
+    }
+
+    #[tokio::test]
+    async fn send_datagram_with_priority() {
+        let (_endpoint, _server, client) = connected_pair_with_send_buffer(1000).await;
+
+        assert!(matches!(
+            client.send_datagram_with_priority([0; 600], DatagramPriority::Low),
+            Err(SendDatagramError::BufferFull)
+        ));
+        client
+            .send_datagram_with_priority([0; 100], DatagramPriority::Low)
+            .unwrap();
+
+        client
+            .send_datagram_with_priority([0; 600], DatagramPriority::Normal)
+            .unwrap();
+        assert!(matches!(
+            client.send_datagram_with_priority([0; 600], DatagramPriority::Normal),
+            Err(SendDatagramError::BufferFull)
+        ));
+
+        client
+            .send_datagram_with_priority([0; 600], DatagramPriority::High)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn datagram_session_routing() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let other_session_id = SessionId::try_from_session_stream(StreamId::new(
+            VarInt::try_from_u64(client.session_id().into_u64() + 4).unwrap(),
+        ))
+        .unwrap();
+
+        let send_raw = |session_id, payload: &'static [u8]| {
+            client
+                .quic_connection
+                .send_datagram(Datagram::write(session_id, payload).into_quic_bytes())
+                .unwrap();
+        };
+
+        send_raw(other_session_id, b"other");
+        send_raw(client.session_id(), b"first");
+        send_raw(other_session_id, b"other");
+        send_raw(client.session_id(), b"second");
+
+        let datagram = server.receive_datagram().await.unwrap();
+        assert_eq!(datagram.session_id(), server.session_id());
+        assert_eq!(&*datagram, b"first");
+
+        let mut buffer = [0; 16];
+        let len = server.receive_datagram_into(&mut buffer).await.unwrap();
+        assert_eq!(&buffer[..len], b"second");
+    }
+
+    #[tokio::test]
+    async fn receive_datagram_into() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        client.send_datagram(b"hello world").unwrap();
+
+        let mut small = [0; 5];
+        assert!(matches!(
+            server.receive_datagram_into(&mut small).await,
+            Err(ReceiveDatagramError::BufferTooSmall { needed: 11 })
+        ));
+
+        let mut buffer = [0; 64];
+        let len = server.receive_datagram_into(&mut buffer).await.unwrap();
+        assert_eq!(&buffer[..len], b"hello world");
+    }
+
+    #[tokio::test]
+    async fn send_datagram_wait_full() {
+        let (_endpoint, server, client) = connected_pair_with_send_buffer(1000).await;
+
+        // The second datagram fits only once the first one is transmitted.
+        client.send_datagram([1; 600]).unwrap();
+        timeout(Duration::from_secs(5), client.send_datagram_wait([2; 600]))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*server.receive_datagram().await.unwrap(), [1; 600]);
+        assert_eq!(*server.receive_datagram().await.unwrap(), [2; 600]);
+    }
+
+    #[tokio::test]
+    async fn send_datagram_wait_too_large() {
+        // Send buffer smaller than the datagram: it would never fit.
+        let (_endpoint, _server, client) = connected_pair_with_send_buffer(64).await;
+
+        assert!(matches!(
+            client.send_datagram_wait([0; 128]).await,
+            Err(SendDatagramError::TooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn send_datagram_wait_cancel() {
+        let (_endpoint, server, client) = connected_pair_with_send_buffer(1000).await;
+
+        // Nothing is transmitted without yielding: the send buffer stays full.
+        client.send_datagram([1; 600]).unwrap();
+        assert!(client.send_datagram_wait([2; 600]).now_or_never().is_none());
+
+        client.send_datagram(b"marker").unwrap();
+
+        assert_eq!(*server.receive_datagram().await.unwrap(), [1; 600]);
+        assert_eq!(&*server.receive_datagram().await.unwrap(), b"marker");
+        assert_eq!(client.bytes_sent(), 600 + b"marker".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn drop_closes() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        drop(client);
+
+        timeout(Duration::from_secs(5), server.closed())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn forget_keeps_alive() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        client.forget();
+
+        let result = timeout(Duration::from_millis(50), server.closed()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn goaway() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        server.goaway();
+
+        timeout(Duration::from_secs(5), async {
+            while !client.goaway_received() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        client.send_datagram(b"payload").unwrap();
+
+        let datagram = server.receive_datagram().await.unwrap();
+        assert_eq!(&*datagram, b"payload");
+    }
+
+    #[cfg(feature = "futures")]
+    #[tokio::test]
+    async fn incoming_streams() {
+        use futures_util::StreamExt;
+
+        let (_endpoint, server, client) = connected_pair().await;
+
+        for index in 0..3u8 {
+            let mut stream = client.open_uni().await.unwrap().await.unwrap();
+            stream.write_all(&[index]).await.unwrap();
+            stream.finish().await.unwrap();
+        }
+
+        let mut received = Vec::new();
+        let mut incoming = server.incoming_uni().take(3);
+        while let Some(stream) = timeout(Duration::from_secs(5), incoming.next())
+            .await
+            .unwrap()
+        {
+            let mut buffer = [0; 1];
+            stream.unwrap().read_exact(&mut buffer).await.unwrap();
+            received.push(buffer[0]);
+        }
+        received.sort();
+        assert_eq!(received, [0, 1, 2]);
+
+        client.close(VarInt::from_u32(0), b"");
+
+        let mut incoming = server.incoming_bi();
+        assert!(incoming.next().await.unwrap().is_err());
+        assert!(incoming.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn split() {
+        let (_endpoint, server, client) = connected_pair().await;
+        let (client_sender, client_receiver) = client.split();
+        let (server_sender, server_receiver) = server.split();
+
+        let receiving = tokio::spawn(async move {
+            let datagram = server_receiver.receive_datagram().await.unwrap();
+            assert_eq!(&*datagram, b"ping");
+            server_receiver
+        });
+
+        client_sender.send_datagram(b"ping").unwrap();
+        let server_receiver = timeout(Duration::from_secs(5), receiving)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut stream = server_sender.open_uni().await.unwrap().await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+        stream.finish().await.unwrap();
+
+        let mut stream = client_receiver.accept_uni().await.unwrap();
+        let mut buffer = [0; 4];
+        stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"pong");
+
+        let (client_sender, server_receiver) = client_sender.reunite(server_receiver).unwrap_err();
+        let client = client_sender.reunite(client_receiver).unwrap();
+        assert!(server_sender.reunite(server_receiver).is_ok());
+        assert_eq!(client.state(), ConnectionState::Connected);
+    }
+
+    #[tokio::test]
+    async fn drain() {
+        let (_endpoint, server, client) = connected_pair().await;
+        assert!(!client.drain_received());
+
+        server.drain();
+        assert_eq!(server.state(), ConnectionState::Draining);
+
+        timeout(Duration::from_secs(5), client.on_drain())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(client.drain_received());
+        assert_eq!(client.state(), ConnectionState::Draining);
+
+        let mut stream = client.open_uni().await.unwrap().await.unwrap();
+        stream.write_all(b"payload").await.unwrap();
+
+        let mut stream = server.accept_uni().await.unwrap();
+        let mut buffer = [0; 7];
+        stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"payload");
+
+        server.close(VarInt::from_u32(0), b"");
+        assert!(server.on_drain().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn state() {
+        let (_endpoint, server, client) = connected_pair().await;
+        assert_eq!(client.state(), ConnectionState::Connected);
+        assert_eq!(server.state(), ConnectionState::Connected);
+
+        server.goaway();
+        assert_eq!(server.state(), ConnectionState::Draining);
+
+        client.close(VarInt::from_u32(0), b"");
+        assert_eq!(client.state(), ConnectionState::Closed);
+
+        timeout(Duration::from_secs(5), server.closed())
+            .await
+            .unwrap();
+        assert_eq!(server.state(), ConnectionState::Closed);
+    }
+
+    #[tokio::test]
+    async fn datagrams_supported() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        assert!(client.datagrams_supported());
+        assert!(server.datagrams_supported());
+    }
+
+    #[tokio::test]
+    async fn max_datagram_size_changed() {
+        let (_endpoint, _server, client) = connected_pair().await;
+
+        let current = client.max_datagram_size();
+        assert!(current.is_some());
+        assert_eq!(
+            client.max_datagram_size_changed(None).await.unwrap(),
+            current
+        );
+
+        client.close(VarInt::from_u32(0), b"");
+        assert!(client.max_datagram_size_changed(current).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn packet_counters() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let earlier = client.packet_counters();
+        client.send_datagram(b"payload").unwrap();
+        server.receive_datagram().await.unwrap();
+
+        let now = client.packet_counters();
+        assert!(now.sent > earlier.sent);
+        assert_eq!(now.loss_rate_since(&earlier), 0.0);
+
+        let earlier = PacketCounters { sent: 10, lost: 1 };
+        let now = PacketCounters { sent: 20, lost: 3 };
+        assert_eq!(now.loss_rate_since(&earlier), 0.2);
+        assert_eq!(now.loss_rate_since(&now), 0.0);
+    }
+
+    #[tokio::test]
+    async fn stats() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        client.send_datagram(b"payload").unwrap();
+        server.receive_datagram().await.unwrap();
+
+        let stats = client.stats();
+        assert_eq!(stats.bytes_sent, b"payload".len() as u64);
+        assert_eq!(stats.datagrams.sent, 1);
+        assert!(stats.packets.sent > 0);
+        assert_eq!(server.stats().datagrams.received, 1);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(stats).unwrap();
+            assert_eq!(json["bytes_sent"], b"payload".len() as u64);
+            assert_eq!(json["datagrams"]["sent"], 1);
+            assert_eq!(json["packets"]["sent"], stats.packets.sent);
+        }
+    }
+
+    #[tokio::test]
+    async fn bandwidth_estimate() {
+        let (_endpoint, _server, client) = connected_pair().await;
+
+        let estimate = client.bandwidth_estimate();
+        assert!(estimate > 0);
+
+        let changed = timeout(
+            Duration::from_secs(1),
+            client.bandwidth_estimate_changed(estimate * 2),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(changed < estimate * 2);
+    }
+
+    #[tokio::test]
+    async fn close_gracefully() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut stream = client.open_uni().await.unwrap().await.unwrap();
+        stream.write_all(b"payload").await.unwrap();
+
+        let (finished, _) = tokio::join!(
+            client.close_gracefully(Duration::from_secs(5), VarInt::from_u32(0), b""),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                stream.finish().await.unwrap();
+            }
+        );
+        assert!(finished);
+
+        let mut stream = server.accept_uni().await.unwrap();
+        let mut buffer = [0; 7];
+        stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"payload");
+
+        let (_endpoint, _server, client) = connected_pair().await;
+
+        let _stream = client.open_uni().await.unwrap().await.unwrap();
+        let finished = client
+            .close_gracefully(Duration::from_millis(50), VarInt::from_u32(0), b"")
+            .await;
+        assert!(!finished);
+        assert_eq!(client.state(), ConnectionState::Closed);
+    }
+
+    #[tokio::test]
+    async fn uptime() {
+        let before = Instant::now();
+        let (_endpoint, _server, client) = connected_pair().await;
+
+        assert!(client.established_at() >= before);
+        assert!(client.established_at() <= Instant::now());
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(client.uptime() >= Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn peer_certificates() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let certificates = client.peer_certificates().unwrap();
+        assert_eq!(certificates.len(), 1);
+        assert_eq!(
+            crate::tls::subject_alt_names(&certificates[0].0),
+            ["localhost"]
+        );
+
+        assert!(server.peer_certificates().is_none());
+    }
+
+    #[tokio::test]
+    async fn export_keying_material() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut client_output = [0; 32];
+        let mut server_output = [0; 32];
+        client
+            .export_keying_material(b"EXPORTER-test", None, &mut client_output)
+            .unwrap();
+        server
+            .export_keying_material(b"EXPORTER-test", Some(b""), &mut server_output)
+            .unwrap();
+        assert_eq!(client_output, server_output);
+
+        server
+            .export_keying_material(b"EXPORTER-test", Some(b"context"), &mut server_output)
+            .unwrap();
+        assert_ne!(client_output, server_output);
+
+        assert!(client
+            .export_keying_material(b"EXPORTER-test", None, &mut [0; MAX_KEYING_MATERIAL_SIZE])
+            .is_ok());
+        assert!(client
+            .export_keying_material(
+                b"EXPORTER-test",
+                None,
+                &mut [0; MAX_KEYING_MATERIAL_SIZE + 1]
+            )
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn extensions() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct UserId(u64);
+
+        let (_endpoint, server, _client) = connected_pair().await;
+
+        assert_eq!(server.get_extension::<UserId>(), None);
+        assert_eq!(server.set_extension(UserId(1)), None);
+        assert_eq!(server.set_extension(UserId(2)), Some(UserId(1)));
+        assert_eq!(server.set_extension("claims"), None);
+        assert_eq!(server.get_extension::<UserId>(), Some(UserId(2)));
+        assert_eq!(server.get_extension::<&str>(), Some("claims"));
+        assert_eq!(server.remove_extension::<UserId>(), Some(UserId(2)));
+        assert_eq!(server.get_extension::<UserId>(), None);
+    }
+
+    #[tokio::test]
+    async fn pause_incoming() {
+        // No backpressure can be applied with the default unlimited receive window.
+        let (_endpoint, _server, client) = connected_pair().await;
+        assert!(client.pause_incoming().is_err());
+        assert!(!client.is_incoming_paused());
+
+        let (_endpoint, server, client) = connected_pair_with(
+            |server| server,
+            |client| client.max_connection_receive_memory(16 * 1024),
+        )
+        .await;
+
+        client.pause_incoming().unwrap();
+        assert!(client.is_incoming_paused());
+
+        let payload = vec![0; 64 * 1024];
+        let mut send_stream = server.open_uni().await.unwrap().await.unwrap();
+
+        let reader = {
+            let mut write = std::pin::pin!(send_stream.write_all(&payload));
+
+            let mut recv_stream = tokio::select! {
+                recv_stream = client.accept_uni() => recv_stream.unwrap(),
+                _ = &mut write => panic!("Write completed while paused"),
+            };
+
+            let reader = tokio::spawn(async move {
+                let mut received = 0;
+                let mut buffer = [0; 4096];
+                while let Some(read) = recv_stream.read(&mut buffer).await.unwrap() {
+                    received += read;
+                }
+                received
+            });
+
+            // Though data is read, the peer is blocked once the initial window is consumed.
+            assert!(timeout(Duration::from_millis(200), &mut write)
+                .await
+                .is_err());
+
+            client.resume_incoming();
+            assert!(!client.is_incoming_paused());
+
+            timeout(Duration::from_secs(5), write)
+                .await
+                .unwrap()
+                .unwrap();
+
+            reader
+        };
+
+        send_stream.finish().await.unwrap();
+        assert_eq!(reader.await.unwrap(), payload.len());
+    }
+
+    async fn connected_pair_with_send_buffer(
+        datagram_send_buffer_size: usize,
+    ) -> (Endpoint<Server>, Connection, Connection) {
+        let mut transport_config = quinn::TransportConfig::default();
+        transport_config.datagram_send_buffer_size(datagram_send_buffer_size);
+        connected_pair_with_transport(transport_config).await
+    }
 }
@@ -6,6 +6,13 @@ use wtransport_proto::ids::QStreamId;
 use wtransport_proto::ids::SessionId;
 
 /// An application Datagram.
+///
+/// On the wire, each QUIC datagram starts with the *quarter stream ID* of its WebTransport
+/// session (the [`SessionId`] divided by four), encoded as a variable-length integer,
+/// followed by the payload (see RFC 9297, HTTP Datagrams). This prefix identifies the
+/// session a datagram belongs to, hence datagrams of different sessions sharing the same
+/// QUIC connection are demultiplexed: a [`Connection`](crate::Connection) only receives the
+/// datagrams of its own session, and the others are discarded.
 #[derive(Debug)]
 pub struct Datagram {
     quic_dgram: Bytes,
@@ -60,12 +67,37 @@ impl Datagram {
         self.session_id
     }
 
+    /// Returns the size of the whole QUIC datagram (header included).
+    #[inline(always)]
+    pub(crate) fn quic_size(&self) -> usize {
+        self.quic_dgram.len()
+    }
+
     #[inline(always)]
     pub(crate) fn into_quic_bytes(self) -> Bytes {
         self.quic_dgram
     }
 }
 
+/// Priority of an outgoing datagram.
+///
+/// See [`Connection::send_datagram_with_priority`](crate::Connection::send_datagram_with_priority).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DatagramPriority {
+    /// The datagram is buffered only if at least half of the send buffer stays free,
+    /// leaving room for higher priority datagrams.
+    Low,
+
+    /// The datagram is buffered only if it fits in the send buffer, without discarding
+    /// any previously buffered datagram.
+    #[default]
+    Normal,
+
+    /// The datagram is always buffered: if the send buffer is full, the oldest buffered
+    /// datagrams are discarded to make room.
+    High,
+}
+
 impl Deref for Datagram {
     type Target = [u8];
 
@@ -1,4 +1,8 @@
+use crate::config::MigrationPolicy;
+use crate::connection::LIFETIME_EXCEEDED_ERROR_CODE;
+use crate::connection::MIGRATION_DENIED_ERROR_CODE;
 use crate::datagram::Datagram;
+use crate::datagram::DatagramPriority;
 use crate::driver::streams::biremote::StreamBiRemoteH3;
 use crate::driver::streams::biremote::StreamBiRemoteWT;
 use crate::driver::streams::session::StreamSession;
@@ -6,13 +10,26 @@ use crate::driver::streams::uniremote::StreamUniRemoteWT;
 use crate::driver::streams::Stream;
 use crate::driver::utils::bichannel;
 use crate::driver::utils::shared_result;
+use crate::driver::utils::BytesCounter;
+use crate::driver::utils::DatagramQueue;
+use crate::driver::utils::PeerAddresses;
+use crate::driver::utils::PendingSendStreams;
 use crate::driver::utils::SendError;
 use crate::driver::utils::SharedResultGet;
 use crate::driver::utils::SharedResultSet;
+use crate::endpoint::normalize_addr;
 use crate::error::SendDatagramError;
 use crate::stream::OpeningBiStream;
 use crate::stream::OpeningUniStream;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
 use tracing::debug;
 use tracing::debug_span;
@@ -24,7 +41,20 @@ use wtransport_proto::error::ErrorCode;
 use wtransport_proto::frame::Frame;
 use wtransport_proto::ids::SessionId;
 use wtransport_proto::session::SessionRequest;
+use wtransport_proto::settings::SettingId;
 use wtransport_proto::settings::Settings;
+use wtransport_proto::varint::VarInt;
+
+/// Polling interval of datagram send buffer space in [`Driver::send_datagram_wait`].
+const DATAGRAM_WAIT_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Controls applied to the migrations of a server connection.
+#[derive(Clone)]
+pub struct MigrationControl {
+    pub policy: Option<MigrationPolicy>,
+    pub rate_limit: Option<(u32, Duration)>,
+    pub peer_addresses: Arc<PeerAddresses>,
+}
 
 #[derive(Copy, Clone, Debug)]
 pub enum DriverError {
@@ -36,44 +66,91 @@ pub enum DriverError {
 pub struct Driver {
     quic_connection: quinn::Connection,
     ready_settings: Mutex<mpsc::Receiver<Settings>>,
+    peer_h3_datagrams: AtomicBool,
     ready_sessions: BiChannelEndpoint<StreamSession>,
     ready_uni_wt_streams: Mutex<mpsc::Receiver<StreamUniRemoteWT>>,
     ready_bi_wt_streams: Mutex<mpsc::Receiver<StreamBiRemoteWT>>,
-    ready_datagrams: Mutex<mpsc::Receiver<Datagram>>,
+    ready_datagrams: Arc<DatagramQueue>,
     driver_result: SharedResultGet<DriverError>,
+    bytes_counter: Arc<BytesCounter>,
+    pending_send_streams: PendingSendStreams,
+    datagram_send_buffer_size: usize,
+    runtime: Arc<dyn quinn::Runtime>,
+    is_server: bool,
+    goaway_request: watch::Sender<bool>,
+    peer_goaway: watch::Receiver<Option<VarInt>>,
+    drain_request: watch::Sender<bool>,
+    peer_drain: watch::Receiver<bool>,
+    lifetime_request: watch::Sender<Option<Instant>>,
 }
 
 impl Driver {
-    pub fn init(quic_connection: quinn::Connection) -> Self {
+    pub fn init(
+        quic_connection: quinn::Connection,
+        runtime: Arc<dyn quinn::Runtime>,
+        max_field_section_size: Option<VarInt>,
+        is_server: bool,
+        migration_control: Option<MigrationControl>,
+        datagram_receive_buffer_size: usize,
+    ) -> Self {
         let ready_settings = mpsc::channel(1);
         let ready_sessions = bichannel(1);
         let ready_uni_wt_streams = mpsc::channel(4);
         let ready_bi_wt_streams = mpsc::channel(1);
-        let ready_datagrams = mpsc::channel(1);
+        let ready_datagrams = Arc::new(DatagramQueue::new(datagram_receive_buffer_size));
         let driver_result = shared_result();
-
-        tokio::spawn(
-            worker::Worker::new(
-                quic_connection.clone(),
-                ready_settings.0,
-                ready_sessions.0,
-                ready_uni_wt_streams.0,
-                ready_bi_wt_streams.0,
-                ready_datagrams.0,
-                driver_result.0,
-            )
-            .run()
-            .instrument(debug_span!("Driver", quic_id = quic_connection.stable_id())),
+        let goaway_request = watch::channel(false);
+        let drain_request = watch::channel(false);
+        let peer_drain = watch::channel(false);
+        let lifetime_request = watch::channel(None);
+
+        let worker = worker::Worker::new(
+            quic_connection.clone(),
+            runtime.clone(),
+            max_field_section_size,
+            is_server,
+            ready_settings.0,
+            ready_sessions.0,
+            ready_uni_wt_streams.0,
+            ready_bi_wt_streams.0,
+            ready_datagrams.clone(),
+            driver_result.0,
+            goaway_request.1,
+            drain_request.1,
+            peer_drain.0,
+            lifetime_request.1,
+            migration_control,
         );
 
+        let peer_goaway = worker.subscribe_goaway();
+
+        runtime.spawn(Box::pin(worker.run().instrument(debug_span!(
+            "Driver",
+            quic_id = quic_connection.stable_id()
+        ))));
+
+        // No datagram sent yet: the whole send buffer is available.
+        let datagram_send_buffer_size = quic_connection.datagram_send_buffer_space();
+
         Self {
             quic_connection,
             ready_settings: Mutex::new(ready_settings.1),
+            peer_h3_datagrams: AtomicBool::new(false),
             ready_sessions: ready_sessions.1,
             ready_uni_wt_streams: Mutex::new(ready_uni_wt_streams.1),
             ready_bi_wt_streams: Mutex::new(ready_bi_wt_streams.1),
-            ready_datagrams: Mutex::new(ready_datagrams.1),
+            ready_datagrams,
             driver_result: driver_result.1,
+            bytes_counter: Arc::default(),
+            pending_send_streams: PendingSendStreams::new(),
+            datagram_send_buffer_size,
+            runtime,
+            is_server,
+            goaway_request: goaway_request.0,
+            peer_goaway,
+            drain_request: drain_request.0,
+            peer_drain: peer_drain.1,
+            lifetime_request: lifetime_request.0,
         }
     }
 
@@ -81,11 +158,24 @@ impl Driver {
         let mut lock = self.ready_settings.lock().await;
 
         match lock.recv().await {
-            Some(settings) => Ok(settings),
+            Some(settings) => {
+                let h3_datagrams = settings.get(SettingId::H3Datagram) == Some(VarInt::from_u32(1));
+                self.peer_h3_datagrams
+                    .store(h3_datagrams, Ordering::Relaxed);
+                Ok(settings)
+            }
             None => Err(self.result().await),
         }
     }
 
+    /// Whether the peer enabled HTTP/3 datagrams in its settings.
+    ///
+    /// Only meaningful once settings have been accepted.
+    #[inline(always)]
+    pub fn peer_h3_datagrams(&self) -> bool {
+        self.peer_h3_datagrams.load(Ordering::Relaxed)
+    }
+
     pub async fn accept_session(&self) -> Result<StreamSession, DriverError> {
         match self.ready_sessions.recv().await {
             Some(session) => Ok(session),
@@ -170,10 +260,8 @@ impl Driver {
     }
 
     pub async fn receive_datagram(&self, session_id: SessionId) -> Result<Datagram, DriverError> {
-        let mut lock = self.ready_datagrams.lock().await;
-
         loop {
-            let datagram = match lock.recv().await {
+            let datagram = match self.ready_datagrams.pop().await {
                 Some(datagram) => datagram,
                 None => {
                     return Err(self.result().await);
@@ -181,6 +269,7 @@ impl Driver {
             };
 
             if datagram.session_id() == session_id {
+                self.bytes_counter.add_received(datagram.len());
                 return Ok(datagram);
             }
 
@@ -191,12 +280,56 @@ impl Driver {
         }
     }
 
+    /// Copies the payload of the next datagram into `buffer`.
+    ///
+    /// Returns `Ok(Err(needed))` if the datagram does not fit, leaving it in the queue.
+    pub async fn receive_datagram_into(
+        &self,
+        session_id: SessionId,
+        buffer: &mut [u8],
+    ) -> Result<Result<usize, usize>, DriverError> {
+        loop {
+            let mut needed = 0;
+
+            let datagram = match self
+                .ready_datagrams
+                .pop_if(|datagram| {
+                    needed = datagram.len();
+                    datagram.session_id() != session_id || datagram.len() <= buffer.len()
+                })
+                .await
+            {
+                Some(Ok(datagram)) => datagram,
+                Some(Err(())) => return Ok(Err(needed)),
+                None => {
+                    return Err(self.result().await);
+                }
+            };
+
+            if datagram.session_id() == session_id {
+                self.bytes_counter.add_received(datagram.len());
+                buffer[..datagram.len()].copy_from_slice(&datagram);
+                return Ok(Ok(datagram.len()));
+            }
+
+            debug!(
+                "Incoming datagram discarded (session_id: {})",
+                datagram.session_id()
+            );
+        }
+    }
+
     pub async fn open_uni(&self, session_id: SessionId) -> Result<OpeningUniStream, DriverError> {
         let quic_stream = Stream::open_uni(&self.quic_connection)
             .await
             .ok_or(DriverError::NotConnected)?;
 
-        Ok(OpeningUniStream::new(session_id, quic_stream))
+        Ok(OpeningUniStream::new(
+            session_id,
+            quic_stream,
+            self.bytes_counter.clone(),
+            self.pending_send_streams.clone(),
+        ))
     }
 
     pub async fn open_bi(&self, session_id: SessionId) -> Result<OpeningBiStream, DriverError> {
@@ -204,7 +337,12 @@ impl Driver {
             .await
             .ok_or(DriverError::NotConnected)?;
 
-        Ok(OpeningBiStream::new(session_id, quic_stream))
+        Ok(OpeningBiStream::new(
+            session_id,
+            quic_stream,
+            self.bytes_counter.clone(),
+            self.pending_send_streams.clone(),
+        ))
     }
 
     pub fn send_datagram(
@@ -215,7 +353,10 @@ impl Driver {
         let quic_datagram = Datagram::write(session_id, payload).into_quic_bytes();
 
         match self.quic_connection.send_datagram(quic_datagram) {
-            Ok(()) => Ok(()),
+            Ok(()) => {
+                self.bytes_counter.add_sent(payload.len());
+                Ok(())
+            }
             Err(quinn::SendDatagramError::UnsupportedByPeer) => {
                 Err(SendDatagramError::UnsupportedByPeer)
             }
@@ -230,6 +371,139 @@ impl Driver {
         }
     }
 
+    pub fn send_datagram_with_priority(
+        &self,
+        session_id: SessionId,
+        payload: &[u8],
+        priority: DatagramPriority,
+    ) -> Result<(), SendDatagramError> {
+        let quic_datagram_size = Datagram::header_size(session_id) + payload.len();
+        let space = self.quic_connection.datagram_send_buffer_space();
+
+        let admitted = match priority {
+            DatagramPriority::Low => {
+                space.saturating_sub(quic_datagram_size) >= self.datagram_send_buffer_size / 2
+            }
+            DatagramPriority::Normal => space >= quic_datagram_size,
+            DatagramPriority::High => true,
+        };
+
+        if !admitted {
+            trace!("Datagram discarded (priority: {priority:?}): send buffer full");
+            return Err(SendDatagramError::BufferFull);
+        }
+
+        self.send_datagram(session_id, payload)
+    }
+
+    pub async fn send_datagram_wait(
+        &self,
+        session_id: SessionId,
+        payload: &[u8],
+    ) -> Result<(), SendDatagramError> {
+        let quic_datagram_size = Datagram::header_size(session_id) + payload.len();
+
+        // It would never fit, even with an empty send buffer
+        if quic_datagram_size > self.datagram_send_buffer_size {
+            return Err(SendDatagramError::TooLarge);
+        }
+
+        loop {
+            if self.quic_connection.close_reason().is_some() {
+                return Err(SendDatagramError::NotConnected);
+            }
+
+            match self.quic_connection.max_datagram_size() {
+                Some(max_size) if max_size < quic_datagram_size => {
+                    return Err(SendDatagramError::TooLarge);
+                }
+                Some(_) => {}
+                None => return Err(SendDatagramError::UnsupportedByPeer),
+            }
+
+            // Datagram is enqueued only here, synchronously: cancellation cannot leave it
+            // partially sent.
+            if self.quic_connection.datagram_send_buffer_space() >= quic_datagram_size {
+                return self.send_datagram(session_id, payload);
+            }
+
+            // QUIC stack does not notify when buffer space is released: poll it periodically.
+            let mut timer = self
+                .runtime
+                .new_timer(Instant::now() + DATAGRAM_WAIT_INTERVAL);
+            tokio::select! {
+                () = std::future::poll_fn(|cx| timer.as_mut().poll(cx)) => {}
+                _ = self.quic_connection.closed() => {}
+            }
+        }
+    }
+
+    pub fn goaway(&self) {
+        self.goaway_request.send_replace(true);
+    }
+
+    pub fn goaway_received(&self) -> bool {
+        self.peer_goaway.borrow().is_some()
+    }
+
+    pub fn goaway_sent(&self) -> bool {
+        *self.goaway_request.borrow()
+    }
+
+    pub fn drain(&self) {
+        self.drain_request.send_replace(true);
+    }
+
+    /// Closes the connection at `deadline` (see [`LIFETIME_EXCEEDED_ERROR_CODE`]).
+    pub fn close_at(&self, deadline: Instant) {
+        self.lifetime_request.send_replace(Some(deadline));
+    }
+
+    pub fn drain_sent(&self) -> bool {
+        *self.drain_request.borrow()
+    }
+
+    pub fn drain_received(&self) -> bool {
+        *self.peer_drain.borrow()
+    }
+
+    pub async fn on_drain(&self) -> Result<(), DriverError> {
+        let mut peer_drain = self.peer_drain.clone();
+
+        while !*peer_drain.borrow_and_update() {
+            if peer_drain.changed().await.is_err() {
+                return Err(self.result().await);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn runtime(&self) -> &Arc<dyn quinn::Runtime> {
+        &self.runtime
+    }
+
+    #[inline(always)]
+    pub fn is_server(&self) -> bool {
+        self.is_server
+    }
+
+    #[inline(always)]
+    pub fn bytes_counter(&self) -> &Arc<BytesCounter> {
+        &self.bytes_counter
+    }
+
+    #[inline(always)]
+    pub fn pending_send_streams(&self) -> &PendingSendStreams {
+        &self.pending_send_streams
+    }
+
+    #[inline(always)]
+    pub fn datagrams_dropped_rx(&self) -> u64 {
+        self.ready_datagrams.dropped()
+    }
+
     async fn result(&self) -> DriverError {
         match self.driver_result.result().await {
             Some(error) => error,
@@ -248,7 +522,15 @@ mod worker {
     use crate::driver::streams::ProtoReadError;
     use crate::driver::streams::ProtoWriteError;
     use crate::driver::utils::TrySendError;
+    use crate::error::StreamReadError;
+    use std::borrow::Cow;
+    use std::collections::VecDeque;
+    use std::future::pending;
+    use tracing::warn;
     use utils::varint_w2q;
+    use wtransport_proto::capsule::Capsule;
+    use wtransport_proto::capsule::CapsuleDecoder;
+    use wtransport_proto::capsule::CapsuleKind;
     use wtransport_proto::frame::FrameKind;
     use wtransport_proto::headers::Headers;
     use wtransport_proto::session::HeadersParseError;
@@ -257,45 +539,93 @@ mod worker {
 
     pub struct Worker {
         quic_connection: quinn::Connection,
+        runtime: Arc<dyn quinn::Runtime>,
         ready_settings: mpsc::Sender<Settings>,
         ready_sessions: BiChannelEndpoint<StreamSession>,
         ready_uni_wt_streams: mpsc::Sender<StreamUniRemoteWT>,
         ready_bi_wt_streams: mpsc::Sender<StreamBiRemoteWT>,
-        ready_datagrams: mpsc::Sender<Datagram>,
+        ready_datagrams: Arc<DatagramQueue>,
         driver_result: SharedResultSet<DriverError>,
         local_settings_stream: LocalSettingsStream,
         remote_settings_stream: RemoteSettingsStream,
         remote_qpack_enc_stream: RemoteQPackEncStream,
         remote_qpack_dec_stream: RemoteQPackDecStream,
         stream_session: Option<StreamSession>,
+        session_capsules: CapsuleDecoder,
+        is_server: bool,
+        goaway_request: watch::Receiver<bool>,
+        goaway_sent: bool,
+        drain_request: watch::Receiver<bool>,
+        drain_sent: bool,
+        peer_drain: watch::Sender<bool>,
+        lifetime_request: watch::Receiver<Option<Instant>>,
+        lifetime_timer: Option<Pin<Box<dyn quinn::AsyncTimer>>>,
+        migration_control: Option<MigrationControl>,
+        unknown_address: Option<watch::Receiver<()>>,
+        remote_address: SocketAddr,
+        migrations: VecDeque<Instant>,
     }
 
     impl Worker {
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             quic_connection: quinn::Connection,
+            runtime: Arc<dyn quinn::Runtime>,
+            max_field_section_size: Option<VarInt>,
+            is_server: bool,
             ready_settings: mpsc::Sender<Settings>,
             ready_sessions: BiChannelEndpoint<StreamSession>,
             ready_uni_wt_streams: mpsc::Sender<StreamUniRemoteWT>,
             ready_bi_wt_streams: mpsc::Sender<StreamBiRemoteWT>,
-            ready_datagrams: mpsc::Sender<Datagram>,
+            ready_datagrams: Arc<DatagramQueue>,
             driver_result: SharedResultSet<DriverError>,
+            goaway_request: watch::Receiver<bool>,
+            drain_request: watch::Receiver<bool>,
+            peer_drain: watch::Sender<bool>,
+            lifetime_request: watch::Receiver<Option<Instant>>,
+            migration_control: Option<MigrationControl>,
         ) -> Self {
+            let remote_address = quic_connection.remote_address();
+
+            let unknown_address = migration_control.as_ref().map(|control| {
+                control.peer_addresses.register(remote_address);
+                control.peer_addresses.subscribe()
+            });
+
             Self {
                 quic_connection,
+                runtime,
                 ready_settings,
                 ready_sessions,
                 ready_uni_wt_streams,
                 ready_bi_wt_streams,
                 ready_datagrams,
                 driver_result,
-                local_settings_stream: LocalSettingsStream::empty(),
+                local_settings_stream: LocalSettingsStream::empty(max_field_section_size),
                 remote_settings_stream: RemoteSettingsStream::empty(),
                 remote_qpack_enc_stream: RemoteQPackEncStream::empty(),
                 remote_qpack_dec_stream: RemoteQPackDecStream::empty(),
                 stream_session: None,
+                session_capsules: CapsuleDecoder::new(),
+                is_server,
+                goaway_request,
+                goaway_sent: false,
+                drain_request,
+                drain_sent: false,
+                peer_drain,
+                lifetime_request,
+                lifetime_timer: None,
+                migration_control,
+                unknown_address,
+                remote_address,
+                migrations: VecDeque::new(),
             }
         }
 
+        pub fn subscribe_goaway(&self) -> watch::Receiver<Option<VarInt>> {
+            self.remote_settings_stream.subscribe_goaway()
+        }
+
         pub async fn run(mut self) {
             debug!("Started");
 
@@ -311,6 +641,10 @@ mod worker {
                     .close(varint_w2q(error_code.to_code()), b"");
             }
 
+            if let Some(control) = &self.migration_control {
+                control.peer_addresses.unregister(self.remote_address);
+            }
+
             self.driver_result.set(error);
         }
 
@@ -324,12 +658,14 @@ mod worker {
             loop {
                 tokio::select! {
                     result = Self::accept_uni(&self.quic_connection,
+                                              &*self.runtime,
                                               &ready_uni_h3_streams.0,
                                               &self.ready_uni_wt_streams) => {
                         result?;
                     }
 
                     result = Self::accept_bi(&self.quic_connection,
+                                             &*self.runtime,
                                              &ready_bi_h3_streams.0,
                                              &self.ready_bi_wt_streams) => {
                         result?;
@@ -371,10 +707,41 @@ mod worker {
                                                       &mut self.remote_settings_stream,
                                                       &mut self.remote_qpack_enc_stream,
                                                       &mut self.remote_qpack_dec_stream,
-                                                      &mut self.stream_session) => {
+                                                      &mut self.stream_session,
+                                                      &mut self.session_capsules,
+                                                      &self.peer_drain) => {
                         return Err(error);
                     }
 
+                    Ok(()) = self.goaway_request.changed(), if !self.goaway_sent => {
+                        self.send_goaway().await?;
+                    }
+
+                    Ok(()) = self.drain_request.changed(),
+                        if !self.drain_sent && self.stream_session.is_some() => {
+                        self.send_drain().await?;
+                    }
+
+                    () = Self::unknown_address_sent(&mut self.unknown_address) => {
+                        self.check_migration();
+                    }
+
+                    Ok(()) = self.lifetime_request.changed() => {
+                        let deadline = *self.lifetime_request.borrow_and_update();
+                        self.lifetime_timer = deadline.map(|deadline| self.runtime.new_timer(deadline));
+                    }
+
+                    () = std::future::poll_fn(|cx| {
+                        self.lifetime_timer.as_mut().expect("Timer is set").as_mut().poll(cx)
+                    }), if self.lifetime_timer.is_some() => {
+                        debug!("Maximum lifetime exceeded: closing connection");
+                        self.lifetime_timer = None;
+                        self.quic_connection.close(
+                            varint_w2q(LIFETIME_EXCEEDED_ERROR_CODE),
+                            b"maximum lifetime exceeded",
+                        );
+                    }
+
                     () = self.driver_result.closed() => {
                         return Err(DriverError::NotConnected);
                     }
@@ -402,8 +769,119 @@ mod worker {
             self.local_settings_stream.send_settings().await
         }
 
+        async fn send_goaway(&mut self) -> Result<(), DriverError> {
+            // Server: first request stream ID that will not be processed (i.e., all but the
+            // current session). Client: push ID, as server push is not supported.
+            let id = match (&self.stream_session, self.is_server) {
+                (Some(stream_session), true) => {
+                    VarInt::try_from_u64(stream_session.id().into_u64() + 4)
+                        .expect("Stream ID is lower than varint max")
+                }
+                _ => VarInt::from_u32(0),
+            };
+
+            debug!("Sending GOAWAY (id: {id})");
+
+            self.goaway_sent = true;
+            self.local_settings_stream.send_goaway(id).await
+        }
+
+        async fn send_drain(&mut self) -> Result<(), DriverError> {
+            let stream_session = self
+                .stream_session
+                .as_mut()
+                .expect("Cannot send drain without session");
+
+            let capsule = Capsule::new_drain_webtransport_session();
+            let mut payload = Vec::with_capacity(capsule.write_size());
+            capsule
+                .write(&mut payload)
+                .expect("Vec does not have capacity limit");
+
+            debug!("Sending DRAIN_WEBTRANSPORT_SESSION");
+
+            self.drain_sent = true;
+
+            match stream_session
+                .write_frame(Frame::new_data(Cow::Owned(payload)))
+                .await
+            {
+                Ok(()) => Ok(()),
+                Err(ProtoWriteError::NotConnected) => Err(DriverError::NotConnected),
+                Err(ProtoWriteError::Stopped) => {
+                    debug!("Session stream stopped: DRAIN_WEBTRANSPORT_SESSION not sent");
+                    Ok(())
+                }
+            }
+        }
+
+        async fn unknown_address_sent(unknown_address: &mut Option<watch::Receiver<()>>) {
+            match unknown_address {
+                Some(unknown_address) => {
+                    if unknown_address.changed().await.is_err() {
+                        pending().await
+                    }
+                }
+                None => pending().await,
+            }
+        }
+
+        fn check_migration(&mut self) {
+            let Some(control) = &self.migration_control else {
+                return;
+            };
+
+            let remote_address = self.quic_connection.remote_address();
+            if remote_address == self.remote_address {
+                return;
+            }
+
+            let old_address = normalize_addr(self.remote_address);
+            let new_address = normalize_addr(remote_address);
+
+            control.peer_addresses.unregister(self.remote_address);
+            control.peer_addresses.register(remote_address);
+            self.remote_address = remote_address;
+
+            if let Some(policy) = &control.policy {
+                if !policy(old_address, new_address) {
+                    debug!("Migration denied ({old_address} -> {new_address}): closing connection");
+                    self.quic_connection
+                        .close(varint_w2q(MIGRATION_DENIED_ERROR_CODE), b"migration denied");
+                    return;
+                }
+            }
+
+            if let Some((max, period)) = control.rate_limit {
+                let now = Instant::now();
+                while self
+                    .migrations
+                    .front()
+                    .is_some_and(|migration| now.duration_since(*migration) >= period)
+                {
+                    self.migrations.pop_front();
+                }
+
+                if self.migrations.len() >= max as usize {
+                    debug!(
+                        "Migration rate limit exceeded ({old_address} -> {new_address}): closing connection"
+                    );
+                    self.quic_connection.close(
+                        varint_w2q(MIGRATION_DENIED_ERROR_CODE),
+                        b"migration rate limit exceeded",
+                    );
+                    return;
+                }
+
+                self.migrations.push_back(now);
+            }
+
+            debug!("Migration approved ({old_address} -> {new_address})");
+        }
+
         async fn accept_uni(
             quic_connection: &quinn::Connection,
+            runtime: &dyn quinn::Runtime,
             ready_uni_h3_streams: &mpsc::Sender<Result<StreamUniRemoteH3, DriverError>>,
             ready_uni_wt_streams: &mpsc::Sender<StreamUniRemoteWT>,
         ) -> Result<(), DriverError> {
@@ -426,7 +904,7 @@ mod worker {
             let stream_id = stream_quic.id();
             debug!("New incoming uni stream ({})", stream_id);
 
-            tokio::spawn(
+            runtime.spawn(Box::pin(
                 async move {
                     let stream_h3 = match stream_quic.upgrade().await {
                         Ok(stream_h3) => stream_h3,
@@ -450,13 +928,14 @@ mod worker {
                     }
                 }
                 .instrument(debug_span!("Stream", "id={}", stream_id)),
-            );
+            ));
 
             Ok(())
         }
 
         async fn accept_bi(
             quic_connection: &quinn::Connection,
+            runtime: &dyn quinn::Runtime,
             ready_bi_h3_streams: &mpsc::Sender<
                 Result<(StreamBiRemoteH3, Frame<'static>), DriverError>,
             >,
@@ -481,7 +960,7 @@ mod worker {
             let stream_id = stream_quic.id();
             debug!("New incoming bi stream ({})", stream_id);
 
-            tokio::spawn(
+            runtime.spawn(Box::pin(
                 async move {
                     let mut stream_h3 = stream_quic.upgrade();
 
@@ -509,20 +988,15 @@ mod worker {
                     }
                 }
                 .instrument(debug_span!("Stream", "id={}", stream_id)),
-            );
+            ));
 
             Ok(())
         }
 
         async fn accept_datagram(
             quic_connection: &quinn::Connection,
-            ready_datagrams: &mpsc::Sender<Datagram>,
+            ready_datagrams: &DatagramQueue,
         ) -> Result<(), DriverError> {
-            let slot = match ready_datagrams.reserve().await {
-                Ok(slot) => slot,
-                Err(mpsc::error::SendError(_)) => return Err(DriverError::NotConnected),
-            };
-
             let quic_dgram = match quic_connection.read_datagram().await {
                 Ok(quic_dgram) => quic_dgram,
                 Err(_) => return Err(DriverError::NotConnected),
@@ -538,7 +1012,18 @@ mod worker {
                 datagram.session_id()
             );
 
-            slot.send(datagram);
+            let discarded = ready_datagrams.push(datagram);
+
+            if discarded > 0 {
+                if ready_datagrams.dropped() == discarded as u64 {
+                    warn!(
+                        "Incoming datagrams discarded as the application is not reading them fast \
+                         enough; consider increasing `datagram_receive_buffer_size`"
+                    );
+                } else {
+                    debug!("{} incoming datagrams discarded (buffer full)", discarded);
+                }
+            }
 
             Ok(())
         }
@@ -591,6 +1076,14 @@ mod worker {
 
                     debug!("Headers: {:?}", headers);
 
+                    if self.goaway_sent {
+                        debug!("Discarding session request: GOAWAY already sent");
+                        stream
+                            .stop(ErrorCode::RequestRejected.to_code())
+                            .expect("Stream not already stopped");
+                        return Ok(());
+                    }
+
                     let stream_session = match SessionRequest::try_from(headers) {
                         Ok(session_request) => stream.into_session(session_request),
                         Err(HeadersParseError::MethodNotConnect) => {
@@ -619,7 +1112,7 @@ mod worker {
                         Err(TrySendError::Closed(_)) => return Err(DriverError::NotConnected),
                     }
                 }
-                FrameKind::Settings => {
+                FrameKind::Settings | FrameKind::GoAway => {
                     return Err(DriverError::Proto(ErrorCode::FrameUnexpected));
                 }
                 FrameKind::WebTransport => unreachable!(),
@@ -634,14 +1127,50 @@ mod worker {
             remote_settings: &mut RemoteSettingsStream,
             remote_qpack_enc: &mut RemoteQPackEncStream,
             remote_qpack_dec: &mut RemoteQPackDecStream,
-            _stream_session: &mut Option<StreamSession>,
+            stream_session: &mut Option<StreamSession>,
+            session_capsules: &mut CapsuleDecoder,
+            peer_drain: &watch::Sender<bool>,
         ) -> DriverError {
-            // TODO(biagio): run stream_session
             tokio::select! {
                 error = local_settings.run() => error,
                 error = remote_settings.run() => error,
                 error = remote_qpack_enc.run() => error,
                 error = remote_qpack_dec.run() => error,
+                error = Self::run_stream_session(stream_session, session_capsules, peer_drain) => error,
+            }
+        }
+
+        async fn run_stream_session(
+            stream_session: &mut Option<StreamSession>,
+            session_capsules: &mut CapsuleDecoder,
+            peer_drain: &watch::Sender<bool>,
+        ) -> DriverError {
+            let Some(stream_session) = stream_session.as_mut() else {
+                return pending().await;
+            };
+
+            // The decoder lives in the worker: this future is dropped at every worker iteration
+            let mut buffer = [0; 4096];
+
+            loop {
+                match session_capsules.decode() {
+                    Ok(Some(capsule)) => {
+                        if matches!(capsule.kind(), Some(CapsuleKind::DrainWebTransportSession)) {
+                            debug!("Received DRAIN_WEBTRANSPORT_SESSION");
+                            peer_drain.send_replace(true);
+                        }
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(error_code) => return DriverError::Proto(error_code),
+                }
+
+                match stream_session.read(&mut buffer).await {
+                    Ok(Some(read)) => session_capsules.push(&buffer[..read]),
+                    Err(StreamReadError::NotConnected) => return DriverError::NotConnected,
+                    // Session stream closed by peer: no more capsules
+                    Ok(None) | Err(_) => return pending().await,
+                }
             }
         }
 
@@ -657,6 +1186,12 @@ mod worker {
             }
         }
     }
+
+    impl Drop for Worker {
+        fn drop(&mut self) {
+            self.ready_datagrams.close();
+        }
+    }
 }
 
 pub(crate) mod streams;
@@ -1,9 +1,11 @@
+use crate::driver::utils::noop_waker;
 use crate::driver::utils::streamid_q2w;
 use crate::driver::utils::varint_q2w;
 use crate::driver::utils::varint_w2q;
 use crate::error::StreamReadError;
 use crate::error::StreamReadExactError;
 use crate::error::StreamWriteError;
+use crate::stream::Chunk;
 use std::pin::Pin;
 use std::task::ready;
 use std::task::Context;
@@ -47,6 +49,18 @@ impl QuicSendStream {
         Ok(())
     }
 
+    /// Sends the end of the stream, without waiting for its acknowledgement.
+    ///
+    /// The QUIC stream keeps track of the pending finish: a later [`finish`](Self::finish)
+    /// only waits for the acknowledgement.
+    pub fn start_finish(&mut self) -> Result<(), StreamWriteError> {
+        let waker = noop_waker();
+        match self.0.poll_finish(&mut Context::from_waker(&waker)) {
+            Poll::Ready(result) => Ok(result?),
+            Poll::Pending => Ok(()),
+        }
+    }
+
     #[inline(always)]
     pub fn set_priority(&self, priority: i32) {
         let _ = self.0.set_priority(priority);
@@ -57,6 +71,10 @@ impl QuicSendStream {
         self.0.priority().expect("Stream has been reset")
     }
 
+    pub async fn wait_stopped(&mut self) -> Option<VarInt> {
+        self.0.stopped().await.ok().map(varint_q2w)
+    }
+
     pub async fn stopped(&mut self) -> StreamWriteError {
         match self.0.stopped().await {
             Ok(code) => StreamWriteError::Stopped(varint_q2w(code)),
@@ -68,9 +86,8 @@ impl QuicSendStream {
 
     #[inline(always)]
     pub fn reset(mut self, error_code: VarInt) {
-        self.0
-            .reset(varint_w2q(error_code))
-            .expect("Stream has been already reset");
+        // Fails only if the stream is already closed (e.g., finished and acknowledged)
+        let _ = self.0.reset(varint_w2q(error_code));
     }
 
     #[inline(always)]
@@ -143,12 +160,23 @@ impl tokio::io::AsyncWrite for QuicSendStream {
 }
 
 #[derive(Debug)]
-pub struct QuicRecvStream(quinn::RecvStream);
+pub struct QuicRecvStream {
+    stream: quinn::RecvStream,
+    proto_read: u64,
+}
 
 impl QuicRecvStream {
+    #[inline(always)]
+    fn new(stream: quinn::RecvStream) -> Self {
+        Self {
+            stream,
+            proto_read: 0,
+        }
+    }
+
     #[inline(always)]
     pub async fn read(&mut self, buf: &mut [u8]) -> Result<Option<usize>, StreamReadError> {
-        match self.0.read(buf).await? {
+        match self.stream.read(buf).await? {
             Some(read) => Ok(Some(read)),
             None => Ok(None),
         }
@@ -156,7 +184,7 @@ impl QuicRecvStream {
 
     #[inline(always)]
     pub async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), StreamReadExactError> {
-        self.0
+        self.stream
             .read_exact(buf)
             .await
             .map_err(|quic_error| match quic_error {
@@ -165,29 +193,50 @@ impl QuicRecvStream {
             })
     }
 
+    #[inline(always)]
+    pub async fn read_chunk(
+        &mut self,
+        max_length: usize,
+        ordered: bool,
+    ) -> Result<Option<Chunk>, StreamReadError> {
+        match self.stream.read_chunk(max_length, ordered).await? {
+            Some(chunk) => Ok(Some(Chunk::new(
+                chunk.offset - self.proto_read,
+                chunk.bytes,
+            ))),
+            None => Ok(None),
+        }
+    }
+
     #[inline(always)]
     pub fn stop(&mut self, error_code: VarInt) -> Result<(), AlreadyStop> {
-        self.0.stop(varint_w2q(error_code)).map_err(|_| AlreadyStop)
+        self.stream
+            .stop(varint_w2q(error_code))
+            .map_err(|_| AlreadyStop)
     }
 
     #[inline(always)]
     pub fn id(&self) -> StreamId {
-        streamid_q2w(self.0.id())
+        streamid_q2w(self.stream.id())
     }
 
     #[cfg(feature = "quinn")]
     #[inline(always)]
     pub fn quic_stream(&self) -> &quinn::RecvStream {
-        &self.0
+        &self.stream
     }
 
     #[cfg(feature = "quinn")]
     #[inline(always)]
     pub fn quic_stream_mut(&mut self) -> &mut quinn::RecvStream {
-        &mut self.0
+        &mut self.stream
     }
 }
 
+/// Protocol reads (stream header, WebTransport stream frame) go through this implementation.
+///
+/// They consume exactly the protocol bytes, which precede the application data: their
+/// total is the offset of the application data in the QUIC stream.
 impl wtransport_proto::bytes::AsyncRead for QuicRecvStream {
     #[inline(always)]
     fn poll_read(
@@ -198,11 +247,15 @@ impl wtransport_proto::bytes::AsyncRead for QuicRecvStream {
         let mut buffer = ReadBuf::new(buf);
 
         match ready!(tokio::io::AsyncRead::poll_read(
-            Pin::new(&mut self.0),
+            Pin::new(&mut self.stream),
             cx,
             &mut buffer
         )) {
-            Ok(()) => Poll::Ready(Ok(buffer.filled().len())),
+            Ok(()) => {
+                let read = buffer.filled().len();
+                self.proto_read += read as u64;
+                Poll::Ready(Ok(read))
+            }
             Err(io_error) => Poll::Ready(Err(io_error)),
         }
     }
@@ -215,7 +268,7 @@ impl tokio::io::AsyncRead for QuicRecvStream {
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        tokio::io::AsyncRead::poll_read(Pin::new(&mut self.0), cx, buf)
+        tokio::io::AsyncRead::poll_read(Pin::new(&mut self.stream), cx, buf)
     }
 }
 
@@ -241,7 +294,7 @@ pub mod biremote {
         pub async fn accept_bi(quic_connection: &quinn::Connection) -> Option<Self> {
             let stream = quic_connection.accept_bi().await.ok()?;
             Some(Self {
-                stream: (QuicSendStream(stream.0), QuicRecvStream(stream.1)),
+                stream: (QuicSendStream(stream.0), QuicRecvStream::new(stream.1)),
                 proto: StreamProto::accept_bi(),
             })
         }
@@ -321,7 +374,7 @@ pub mod bilocal {
         pub async fn open_bi(quic_connection: &quinn::Connection) -> Option<Self> {
             let stream = quic_connection.open_bi().await.ok()?;
             Some(Self {
-                stream: (QuicSendStream(stream.0), QuicRecvStream(stream.1)),
+                stream: (QuicSendStream(stream.0), QuicRecvStream::new(stream.1)),
                 proto: StreamProto::open_bi(),
             })
         }
@@ -379,7 +432,7 @@ pub mod uniremote {
         pub async fn accept_uni(quic_connection: &quinn::Connection) -> Option<Self> {
             let stream = quic_connection.accept_uni().await.ok()?;
             Some(Self {
-                stream: QuicRecvStream(stream),
+                stream: QuicRecvStream::new(stream),
                 proto: StreamProto::accept_uni(),
             })
         }
@@ -511,6 +564,13 @@ pub mod session {
             self.proto.read_frame_async(&mut self.stream.1).await
         }
 
+        /// Reads raw data (i.e., framed capsules) from the session stream.
+        ///
+        /// This is cancel-safe: data is consumed only when returned.
+        pub async fn read(&mut self, buf: &mut [u8]) -> Result<Option<usize>, StreamReadError> {
+            self.stream.1.read(buf).await
+        }
+
         pub async fn write_frame<'a>(&mut self, frame: Frame<'a>) -> Result<(), ProtoWriteError> {
             self.proto
                 .write_frame_async(frame, &mut self.stream.0)
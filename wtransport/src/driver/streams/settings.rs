@@ -6,6 +6,7 @@ use crate::driver::DriverError;
 use crate::error::StreamWriteError;
 use std::future::pending;
 use tokio::sync::watch;
+use tracing::debug;
 use wtransport_proto::bytes;
 use wtransport_proto::error::ErrorCode;
 use wtransport_proto::frame::Frame;
@@ -20,15 +21,20 @@ pub struct LocalSettingsStream {
 }
 
 impl LocalSettingsStream {
-    pub fn empty() -> Self {
-        let settings = Settings::builder()
+    pub fn empty(max_field_section_size: Option<VarInt>) -> Self {
+        let mut settings = Settings::builder()
             .qpack_max_table_capacity(VarInt::from_u32(0))
             .qpack_blocked_streams(VarInt::from_u32(0))
             .enable_connect_protocol() // TODO(biagio): it would be nice to have this only for server
             .enable_webtransport()
             .enable_h3_datagrams()
-            .webtransport_max_sessions(VarInt::from_u32(1))
-            .build();
+            .webtransport_max_sessions(VarInt::from_u32(1));
+
+        if let Some(max_field_section_size) = max_field_section_size {
+            settings = settings.max_field_section_size(max_field_section_size);
+        }
+
+        let settings = settings.build();
 
         Self {
             stream: None,
@@ -61,6 +67,22 @@ impl LocalSettingsStream {
         }
     }
 
+    pub async fn send_goaway(&mut self, id: VarInt) -> Result<(), DriverError> {
+        match self
+            .stream
+            .as_mut()
+            .expect("Cannot send goaway on empty stream")
+            .write_frame(Frame::new_goaway(id))
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(ProtoWriteError::NotConnected) => Err(DriverError::NotConnected),
+            Err(ProtoWriteError::Stopped) => {
+                Err(DriverError::Proto(ErrorCode::ClosedCriticalStream))
+            }
+        }
+    }
+
     pub async fn run(&mut self) -> DriverError {
         match self.stream.as_mut() {
             Some(stream) => match stream.stopped().await {
@@ -76,6 +98,7 @@ impl LocalSettingsStream {
 pub struct RemoteSettingsStream {
     stream: Option<StreamUniRemoteH3>,
     settings: watch::Sender<Option<Settings>>,
+    goaway: watch::Sender<Option<VarInt>>,
 }
 
 impl RemoteSettingsStream {
@@ -83,6 +106,7 @@ impl RemoteSettingsStream {
         Self {
             stream: None,
             settings: watch::channel(None).0,
+            goaway: watch::channel(None).0,
         }
     }
 
@@ -99,6 +123,10 @@ impl RemoteSettingsStream {
         RemoteSettingsWatcher(self.settings.subscribe())
     }
 
+    pub fn subscribe_goaway(&self) -> watch::Receiver<Option<VarInt>> {
+        self.goaway.subscribe()
+    }
+
     pub async fn run(&mut self) -> DriverError {
         loop {
             let frame = match self.read_frame().await {
@@ -117,6 +145,19 @@ impl RemoteSettingsStream {
                 };
 
                 self.settings.send_replace(Some(settings));
+            } else if matches!(frame.kind(), FrameKind::GoAway) {
+                let id = match frame.goaway_id() {
+                    Ok(id) => id,
+                    Err(error_code) => return DriverError::Proto(error_code),
+                };
+
+                // Identifier must not increase across GOAWAY frames (RFC 9114, Section 5.2)
+                if matches!(*self.goaway.borrow(), Some(last_id) if id > last_id) {
+                    return DriverError::Proto(ErrorCode::Id);
+                }
+
+                debug!("Received GOAWAY (id: {id})");
+                self.goaway.send_replace(Some(id));
             } else if !matches!(frame.kind(), FrameKind::Exercise(_)) {
                 return DriverError::Proto(ErrorCode::FrameUnexpected);
             }
@@ -1,7 +1,20 @@
+use crate::datagram::Datagram;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::IoSliceMut;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tokio::sync::Mutex;
+use tokio::sync::Notify;
 use wtransport_proto::ids::StreamId;
 use wtransport_proto::varint::VarInt;
 
@@ -33,6 +46,17 @@ pub fn streamid_q2w(stream_id: quinn::StreamId) -> StreamId {
     StreamId::new(varint)
 }
 
+/// Waker doing nothing, for polling futures only once.
+struct NoopWaker;
+
+impl std::task::Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+pub fn noop_waker() -> std::task::Waker {
+    Arc::new(NoopWaker).into()
+}
+
 pub fn shared_result<T>() -> (SharedResultSet<T>, SharedResultGet<T>)
 where
     T: Copy,
@@ -164,10 +188,363 @@ pub fn bichannel<T>(capacity: usize) -> (BiChannelEndpoint<T>, BiChannelEndpoint
     )
 }
 
+/// Application payload bytes exchanged on a session (streams and datagrams).
+#[derive(Debug, Default)]
+pub struct BytesCounter {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl BytesCounter {
+    #[inline(always)]
+    pub fn add_sent(&self, bytes: usize) {
+        self.sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub fn add_received(&self, bytes: usize) {
+        self.received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+}
+
+/// Send streams of a session that still have data to deliver.
+///
+/// A stream is pending from its creation until it is finished (and all data acknowledged),
+/// reset, or dropped.
+#[derive(Debug, Clone)]
+pub struct PendingSendStreams(Arc<watch::Sender<usize>>);
+
+impl PendingSendStreams {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::channel(0).0))
+    }
+
+    pub fn track(&self) -> PendingSendStream {
+        self.0.send_modify(|count| *count += 1);
+        PendingSendStream(Some(self.clone()))
+    }
+
+    pub async fn wait_none(&self) {
+        let mut receiver = self.0.subscribe();
+
+        while *receiver.borrow_and_update() > 0 {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// A send stream tracked by [`PendingSendStreams`].
+#[derive(Debug)]
+pub struct PendingSendStream(Option<PendingSendStreams>);
+
+impl PendingSendStream {
+    pub fn release(&mut self) {
+        if let Some(streams) = self.0.take() {
+            streams.0.send_modify(|count| *count -= 1);
+        }
+    }
+}
+
+impl Drop for PendingSendStream {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Remote addresses of the connections of a server endpoint.
+///
+/// Its sockets (see [`PeerAddressSocket`]) notify subscribers each time the QUIC stack sends
+/// a 1-RTT packet to an address not registered: this happens when a connection migrates,
+/// right away with the path validation challenge.
+#[derive(Debug)]
+pub struct PeerAddresses {
+    addresses: std::sync::Mutex<HashMap<SocketAddr, usize>>,
+    unknown_address: watch::Sender<()>,
+}
+
+impl PeerAddresses {
+    pub fn register(&self, address: SocketAddr) {
+        *self
+            .addresses
+            .lock()
+            .expect("Mutex is not poisoned")
+            .entry(address)
+            .or_default() += 1;
+    }
+
+    pub fn unregister(&self, address: SocketAddr) {
+        let mut addresses = self.addresses.lock().expect("Mutex is not poisoned");
+
+        if let Some(count) = addresses.get_mut(&address) {
+            *count -= 1;
+            if *count == 0 {
+                addresses.remove(&address);
+            }
+        }
+    }
+
+    /// Notified when a packet is sent to an address not registered.
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.unknown_address.subscribe()
+    }
+
+    fn check_transmits(&self, transmits: &[quinn::udp::Transmit]) {
+        // Handshake packets (long header) are sent to connections not established yet.
+        let mut short_header_destinations = transmits
+            .iter()
+            .filter(|transmit| transmit.contents.first().is_some_and(|b| b & 0x80 == 0))
+            .map(|transmit| transmit.destination)
+            .peekable();
+
+        if short_header_destinations.peek().is_none() {
+            return;
+        }
+
+        let addresses = self.addresses.lock().expect("Mutex is not poisoned");
+        if short_header_destinations.any(|destination| !addresses.contains_key(&destination)) {
+            self.unknown_address.send_replace(());
+        }
+    }
+}
+
+impl Default for PeerAddresses {
+    fn default() -> Self {
+        Self {
+            addresses: std::sync::Mutex::default(),
+            unknown_address: watch::channel(()).0,
+        }
+    }
+}
+
+/// A QUIC runtime whose UDP sockets track peer addresses (see [`PeerAddressSocket`]).
+///
+/// The QUIC endpoint wraps every socket it binds (including on rebind) through the runtime.
+#[derive(Debug)]
+pub struct PeerAddressRuntime {
+    runtime: Arc<dyn quinn::Runtime>,
+    peer_addresses: Arc<PeerAddresses>,
+}
+
+impl PeerAddressRuntime {
+    pub fn new(runtime: Arc<dyn quinn::Runtime>, peer_addresses: Arc<PeerAddresses>) -> Self {
+        Self {
+            runtime,
+            peer_addresses,
+        }
+    }
+}
+
+impl quinn::Runtime for PeerAddressRuntime {
+    fn new_timer(&self, i: Instant) -> Pin<Box<dyn quinn::AsyncTimer>> {
+        self.runtime.new_timer(i)
+    }
+
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        self.runtime.spawn(future)
+    }
+
+    fn wrap_udp_socket(
+        &self,
+        t: std::net::UdpSocket,
+    ) -> std::io::Result<Box<dyn quinn::AsyncUdpSocket>> {
+        Ok(Box::new(PeerAddressSocket::new(
+            self.runtime.wrap_udp_socket(t)?,
+            self.peer_addresses.clone(),
+        )))
+    }
+}
+
+/// A UDP socket checking the destinations of sent packets against [`PeerAddresses`].
+///
+/// Only installed on server endpoints with a migration policy: the QUIC stack does not
+/// notify connection migrations otherwise.
+#[derive(Debug)]
+pub struct PeerAddressSocket {
+    socket: Box<dyn quinn::AsyncUdpSocket>,
+    peer_addresses: Arc<PeerAddresses>,
+}
+
+impl PeerAddressSocket {
+    pub fn new(socket: Box<dyn quinn::AsyncUdpSocket>, peer_addresses: Arc<PeerAddresses>) -> Self {
+        Self {
+            socket,
+            peer_addresses,
+        }
+    }
+}
+
+impl quinn::AsyncUdpSocket for PeerAddressSocket {
+    fn poll_send(
+        &self,
+        state: &quinn::udp::UdpState,
+        cx: &mut Context<'_>,
+        transmits: &[quinn::udp::Transmit],
+    ) -> Poll<std::io::Result<usize>> {
+        let result = self.socket.poll_send(state, cx, transmits);
+        if let Poll::Ready(Ok(sent)) = result {
+            self.peer_addresses.check_transmits(&transmits[..sent]);
+        }
+        result
+    }
+
+    fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+        meta: &mut [quinn::udp::RecvMeta],
+    ) -> Poll<std::io::Result<usize>> {
+        self.socket.poll_recv(cx, bufs, meta)
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    fn may_fragment(&self) -> bool {
+        self.socket.may_fragment()
+    }
+}
+
+/// Incoming datagrams waiting to be read by the application.
+///
+/// The queue is bounded by the total size (in bytes) of the buffered datagrams: when a new
+/// datagram does not fit, the oldest ones are discarded to make room.
+#[derive(Debug)]
+pub struct DatagramQueue {
+    state: std::sync::Mutex<DatagramQueueState>,
+    notify: Notify,
+}
+
+#[derive(Debug)]
+struct DatagramQueueState {
+    datagrams: VecDeque<Datagram>,
+    size: usize,
+    capacity: usize,
+    dropped: u64,
+    closed: bool,
+}
+
+impl DatagramQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: std::sync::Mutex::new(DatagramQueueState {
+                datagrams: VecDeque::new(),
+                size: 0,
+                capacity,
+                dropped: 0,
+                closed: false,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Enqueues a datagram, discarding the oldest ones if the queue is full.
+    ///
+    /// A datagram larger than the whole capacity is discarded itself.
+    ///
+    /// Returns the number of datagrams discarded.
+    pub fn push(&self, datagram: Datagram) -> usize {
+        let mut state = self.state.lock().expect("Mutex not poisoned");
+        let size = datagram.quic_size();
+
+        if size > state.capacity {
+            state.dropped += 1;
+            return 1;
+        }
+
+        let mut discarded = 0;
+
+        while state.size + size > state.capacity {
+            let old = state
+                .datagrams
+                .pop_front()
+                .expect("Queue size is larger than zero");
+            state.size -= old.quic_size();
+            discarded += 1;
+        }
+
+        state.size += size;
+        state.dropped += discarded as u64;
+        state.datagrams.push_back(datagram);
+        drop(state);
+
+        self.notify.notify_one();
+
+        discarded
+    }
+
+    /// Awaits the next datagram.
+    ///
+    /// Returns `None` once the queue is closed and all datagrams have been consumed.
+    pub async fn pop(&self) -> Option<Datagram> {
+        self.pop_if(|_| true)
+            .await
+            .map(|datagram| datagram.expect("Every datagram is accepted"))
+    }
+
+    /// Awaits the next datagram, and dequeues it only if `accept` returns `true`.
+    ///
+    /// A rejected datagram is left at the front of the queue.
+    ///
+    /// Returns `None` once the queue is closed and all datagrams have been consumed.
+    pub async fn pop_if<F>(&self, mut accept: F) -> Option<Result<Datagram, ()>>
+    where
+        F: FnMut(&Datagram) -> bool,
+    {
+        loop {
+            let notified = self.notify.notified();
+
+            {
+                let mut state = self.state.lock().expect("Mutex not poisoned");
+
+                if let Some(datagram) = state.datagrams.front() {
+                    if !accept(datagram) {
+                        return Some(Err(()));
+                    }
+
+                    let datagram = state.datagrams.pop_front().expect("Queue is not empty");
+                    state.size -= datagram.quic_size();
+                    return Some(Ok(datagram));
+                }
+
+                if state.closed {
+                    return None;
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Closes the queue, waking up all pending [`pop`](Self::pop).
+    pub fn close(&self) {
+        self.state.lock().expect("Mutex not poisoned").closed = true;
+        self.notify.notify_waiters();
+    }
+
+    /// Returns the total number of datagrams discarded so far.
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().expect("Mutex not poisoned").dropped
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use utils::poll_once;
+    use wtransport_proto::ids::SessionId;
 
     #[tokio::test]
     async fn shared_result_double_set() {
@@ -202,6 +579,30 @@ mod tests {
         assert!(matches!(poll_once(get.result()).await.unwrap(), Some(1)));
     }
 
+    #[tokio::test]
+    async fn datagram_queue_drops_oldest() {
+        let session_id =
+            SessionId::try_from_session_stream(StreamId::new(VarInt::from_u32(0))).unwrap();
+        let datagram = |payload: &[u8]| Datagram::write(session_id, payload);
+        let size = datagram(b"aaaa").quic_size();
+
+        let queue = DatagramQueue::new(2 * size);
+        assert_eq!(queue.push(datagram(b"aaaa")), 0);
+        assert_eq!(queue.push(datagram(b"bbbb")), 0);
+        assert_eq!(queue.push(datagram(b"cccc")), 1);
+        assert_eq!(queue.push(datagram(&[0; 64])), 1);
+        assert_eq!(queue.dropped(), 2);
+
+        assert_eq!(&*queue.pop().await.unwrap(), b"bbbb");
+        assert_eq!(&*queue.pop().await.unwrap(), b"cccc");
+        assert!(poll_once(queue.pop()).await.is_none());
+
+        queue.push(datagram(b"dddd"));
+        queue.close();
+        assert_eq!(&*queue.pop().await.unwrap(), b"dddd");
+        assert!(queue.pop().await.is_none());
+    }
+
     mod utils {
         use std::future::Future;
         use std::pin::Pin;
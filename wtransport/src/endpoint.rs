@@ -1,14 +1,23 @@
+use crate::config::AddressFamily;
 use crate::config::ClientConfig;
 use crate::config::DnsResolver;
 use crate::config::DnsResolverExt;
 use crate::config::Ipv6DualStackConfig;
+use crate::config::MigrationPolicy;
+#[cfg(feature = "dangerous-configuration")]
+use crate::config::RejectedNames;
 use crate::config::ServerConfig;
 use crate::connection::Connection;
 use crate::driver::streams::session::StreamSession;
 use crate::driver::streams::ProtoReadError;
 use crate::driver::streams::ProtoWriteError;
 use crate::driver::utils::varint_w2q;
+use crate::driver::utils::PeerAddressRuntime;
+#[cfg(feature = "quinn")]
+use crate::driver::utils::PeerAddressSocket;
+use crate::driver::utils::PeerAddresses;
 use crate::driver::Driver;
+use crate::driver::MigrationControl;
 use crate::error::ConnectingError;
 use crate::error::ConnectionError;
 use quinn::TokioRuntime;
@@ -18,24 +27,32 @@ use socket2::Socket;
 use socket2::Type as SocketType;
 use std::collections::HashMap;
 use std::future::Future;
-use std::marker::PhantomData;
 use std::net::SocketAddr;
 use std::net::SocketAddrV4;
 use std::net::SocketAddrV6;
+use std::ops::RangeInclusive;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::Mutex;
 use tracing::debug;
+use tracing::warn;
 use url::Host;
 use url::Url;
 use wtransport_proto::error::ErrorCode;
 use wtransport_proto::frame::FrameKind;
 use wtransport_proto::headers::Headers;
+use wtransport_proto::ids::StatusCode;
 use wtransport_proto::session::ReservedHeader;
 use wtransport_proto::session::SessionRequest as SessionRequestProto;
 use wtransport_proto::session::SessionResponse as SessionResponseProto;
+use wtransport_proto::settings::SettingId;
+use wtransport_proto::varint::VarInt;
 
 /// Helper structure for Endpoint types.
 pub mod endpoint_side {
@@ -45,7 +62,10 @@ pub mod endpoint_side {
     ///
     /// Use [`Endpoint::server`] to create and server-endpoint.
     pub struct Server {
-        pub(super) _marker: PhantomData<()>,
+        pub(super) incoming_config: std::sync::Mutex<IncomingConfig>,
+        pub(super) quic_config: std::sync::Mutex<quinn::ServerConfig>,
+        pub(super) connection_callback: std::sync::Mutex<Option<ConnectionCallback>>,
+        pub(super) peer_addresses: Option<Arc<PeerAddresses>>,
     }
 
     /// Type of endpoint opening a WebTransport connection.
@@ -53,6 +73,16 @@ pub mod endpoint_side {
     /// Use [`Endpoint::client`] to create and client-endpoint.
     pub struct Client {
         pub(super) dns_resolver: Mutex<Box<dyn DnsResolver + Send + Sync + Unpin>>,
+        pub(super) dns_resolve_timeout: Option<Duration>,
+        pub(super) address_family: AddressFamily,
+        pub(super) handshake_timeout: Option<Duration>,
+        pub(super) session_setup_timeout: Option<Duration>,
+        pub(super) max_field_section_size: Option<VarInt>,
+        pub(super) strict_hostname_verification: bool,
+        #[cfg(feature = "dangerous-configuration")]
+        pub(super) rejected_names: RejectedNames,
+        pub(super) receive_window: VarInt,
+        pub(super) datagram_receive_buffer_size: usize,
     }
 }
 
@@ -104,6 +134,8 @@ pub mod endpoint_side {
 /// ```
 pub struct Endpoint<Side> {
     endpoint: quinn::Endpoint,
+    runtime: Arc<dyn quinn::Runtime>,
+    closed: Arc<AtomicBool>,
     side: Side,
 }
 
@@ -111,6 +143,7 @@ impl<Side> Endpoint<Side> {
     fn bind_socket(
         bind_address: SocketAddr,
         dual_stack_config: Ipv6DualStackConfig,
+        #[cfg_attr(not(target_os = "linux"), allow(unused_variables))] bind_device: Option<&[u8]>,
     ) -> std::io::Result<Socket> {
         let domain = match bind_address {
             SocketAddr::V4(_) => SocketDomain::IPV4,
@@ -125,11 +158,116 @@ impl<Side> Endpoint<Side> {
             Ipv6DualStackConfig::Allow => socket.set_only_v6(false)?,
         }
 
+        #[cfg(target_os = "linux")]
+        if let Some(device) = bind_device {
+            socket.bind_device(Some(device))?;
+        }
+
         socket.bind(&bind_address.into())?;
 
         Ok(socket)
     }
 
+    /// Binds the first available port of `ports`, on the IP address of `bind_address`.
+    fn bind_socket_in_range(
+        mut bind_address: SocketAddr,
+        ports: RangeInclusive<u16>,
+        dual_stack_config: Ipv6DualStackConfig,
+        bind_device: Option<&[u8]>,
+    ) -> std::io::Result<Socket> {
+        for port in ports.clone() {
+            bind_address.set_port(port);
+
+            match Self::bind_socket(bind_address, dual_stack_config, bind_device) {
+                Ok(socket) => return Ok(socket),
+                Err(error) if error.kind() == std::io::ErrorKind::AddrInUse => {
+                    debug!("Port {port} not available: {error}");
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            format!(
+                "no port available in range {}..={}",
+                ports.start(),
+                ports.end()
+            ),
+        ))
+    }
+
+    /// Closes the endpoint, and all its connections, as soon as `signal` completes.
+    ///
+    /// This allows wiring the endpoint shutdown to an external cancellation mechanism.
+    /// The `signal` future is driven in background: this method returns immediately.
+    /// Once it completes, all connections are closed immediately with `error_code` and
+    /// `reason`, and a server endpoint stops accepting new connections.
+    ///
+    /// After closing, [`wait_idle`](Self::wait_idle) can be used to wait until the peers
+    /// have been notified.
+    ///
+    /// Any future can be used as `signal`: for instance, with a
+    /// [`CancellationToken`](https://docs.rs/tokio-util/latest/tokio_util/sync/struct.CancellationToken.html),
+    /// pass `token.cancelled_owned()`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use wtransport::endpoint::endpoint_side::Server;
+    /// # use wtransport_proto::varint::VarInt;
+    /// # fn example(endpoint: wtransport::Endpoint<Server>) {
+    /// let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    ///
+    /// endpoint.close_on(
+    ///     async {
+    ///         let _ = shutdown_rx.await;
+    ///     },
+    ///     VarInt::from_u32(0),
+    ///     b"shutdown",
+    /// );
+    ///
+    /// // Somewhere else (e.g., on SIGTERM)...
+    /// let _ = shutdown_tx.send(());
+    /// # }
+    /// ```
+    pub fn close_on<F>(&self, signal: F, error_code: VarInt, reason: &[u8])
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let endpoint = self.endpoint.clone();
+        let closed = self.closed.clone();
+        let reason = reason.to_vec();
+
+        self.runtime.spawn(Box::pin(async move {
+            signal.await;
+            close_endpoint(&endpoint, &closed, error_code, &reason);
+        }));
+    }
+
+    /// Closes the endpoint, and all its connections, immediately.
+    ///
+    /// All connections are closed with `error_code` and `reason`. Afterwards, a server
+    /// endpoint stops accepting new connections, and [`connect`](Endpoint::connect) on a
+    /// client endpoint fails with [`ConnectingError::EndpointClosed`]. Closing is
+    /// irreversible: calling this method again has no additional effect.
+    ///
+    /// Note that an endpoint is *not* closed when its connections are: for instance, a
+    /// client endpoint stays bound and ready for new connections, until it is closed (or
+    /// dropped). So a single endpoint can back a long-lived pool of connections.
+    ///
+    /// After closing, [`wait_idle`](Self::wait_idle) can be used to wait until the peers
+    /// have been notified.
+    pub fn close(&self, error_code: VarInt, reason: &[u8]) {
+        close_endpoint(&self.endpoint, &self.closed, error_code, reason);
+    }
+
+    /// Returns `true` until the endpoint is closed, with [`close`](Self::close) or
+    /// [`close_on`](Self::close_on).
+    pub fn is_open(&self) -> bool {
+        !self.closed.load(Ordering::Acquire)
+    }
+
     /// Waits for all connections on the endpoint to be cleanly shut down.
     pub async fn wait_idle(&self) {
         self.endpoint.wait_idle().await;
@@ -144,37 +282,220 @@ impl<Side> Endpoint<Side> {
 impl Endpoint<endpoint_side::Server> {
     /// Constructs a *server* endpoint.
     pub fn server(server_config: ServerConfig) -> std::io::Result<Self> {
+        Self::server_impl(server_config, Arc::new(TokioRuntime))
+    }
+
+    /// Constructs a *server* endpoint driven by a custom QUIC runtime.
+    ///
+    /// The `runtime` is in charge of I/O operations and timers of the underlying QUIC
+    /// endpoint. This can be used, for instance, to control time in tests.
+    ///
+    /// [`Endpoint::server`] is equivalent to this method with [`quinn::TokioRuntime`].
+    #[cfg(feature = "quinn")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+    pub fn server_with_runtime(
+        server_config: ServerConfig,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self> {
+        Self::server_impl(server_config, runtime)
+    }
+
+    /// Constructs a *server* endpoint over a user-supplied UDP transport.
+    ///
+    /// Instead of binding an OS UDP socket, the endpoint sends and receives QUIC packets
+    /// through `socket`. This allows running the endpoint over a tunneled or relayed path
+    /// (e.g., a *CONNECT-UDP* proxy). The bind address of `server_config` is ignored.
+    ///
+    /// The `socket` must fulfill the [`quinn::AsyncUdpSocket`] contract:
+    ///   - each [`Transmit`](quinn::udp::Transmit) passed to `poll_send` carries UDP payloads
+    ///     (one or more QUIC packets) which must be delivered to
+    ///     [`destination`](quinn::udp::Transmit::destination) as a single datagram each.
+    ///     If `segment_size` is set, `contents` must be split in datagrams of that size.
+    ///     `poll_send` returns the number of transmits fully handed over;
+    ///   - `poll_recv` fills `bufs` with received datagrams (one per buffer) and the
+    ///     corresponding `meta` with the peer address and the datagram length,
+    ///     returning the number of datagrams received;
+    ///   - datagram boundaries must be preserved. Loss, duplication and reordering are
+    ///     tolerated, as for plain UDP;
+    ///   - `local_addr` returns the address used by the endpoint to identify itself, which
+    ///     must remain stable as long as the endpoint is alive.
+    ///
+    /// **Note**: calling [`reload_config`](Self::reload_config) with `rebind` replaces the
+    /// supplied transport with an OS UDP socket.
+    #[cfg(feature = "quinn")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+    pub fn server_with_socket<S>(
+        server_config: ServerConfig,
+        socket: S,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self>
+    where
+        S: quinn::AsyncUdpSocket,
+    {
+        let incoming_config = IncomingConfig::new(&server_config);
+        let peer_addresses = Self::peer_addresses(&server_config);
+
+        let quic_config = server_config.quic_config;
+
+        let endpoint = match &peer_addresses {
+            Some(peer_addresses) => quinn::Endpoint::new_with_abstract_socket(
+                server_config.endpoint_config,
+                Some(quic_config.clone()),
+                PeerAddressSocket::new(Box::new(socket), peer_addresses.clone()),
+                Arc::new(PeerAddressRuntime::new(
+                    runtime.clone(),
+                    peer_addresses.clone(),
+                )),
+            )?,
+            None => quinn::Endpoint::new_with_abstract_socket(
+                server_config.endpoint_config,
+                Some(quic_config.clone()),
+                socket,
+                runtime.clone(),
+            )?,
+        };
+
+        Ok(Self::server_from_quic(
+            endpoint,
+            runtime,
+            peer_addresses,
+            incoming_config,
+            quic_config,
+        ))
+    }
+
+    fn server_impl(
+        server_config: ServerConfig,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self> {
+        let incoming_config = IncomingConfig::new(&server_config);
+        let peer_addresses = Self::peer_addresses(&server_config);
         let quic_config = server_config.quic_config;
-        let socket =
-            Self::bind_socket(server_config.bind_address, server_config.dual_stack_config)?;
-        let runtime = Arc::new(TokioRuntime);
+        let socket = Self::bind_socket(
+            server_config.bind_address,
+            server_config.dual_stack_config,
+            server_config.bind_device.as_deref(),
+        )?;
+
+        let quic_runtime: Arc<dyn quinn::Runtime> = match &peer_addresses {
+            Some(peer_addresses) => Arc::new(PeerAddressRuntime::new(
+                runtime.clone(),
+                peer_addresses.clone(),
+            )),
+            None => runtime.clone(),
+        };
 
         let endpoint = quinn::Endpoint::new(
-            quinn::EndpointConfig::default(),
-            Some(quic_config),
+            server_config.endpoint_config,
+            Some(quic_config.clone()),
             socket.into(),
-            runtime,
+            quic_runtime,
         )?;
 
-        Ok(Self {
+        Ok(Self::server_from_quic(
+            endpoint,
+            runtime,
+            peer_addresses,
+            incoming_config,
+            quic_config,
+        ))
+    }
+
+    /// Peer addresses to track, if the server controls client migrations.
+    fn peer_addresses(server_config: &ServerConfig) -> Option<Arc<PeerAddresses>> {
+        (server_config.migration_policy.is_some() || server_config.migration_rate_limit.is_some())
+            .then(Arc::default)
+    }
+
+    fn server_from_quic(
+        endpoint: quinn::Endpoint,
+        runtime: Arc<dyn quinn::Runtime>,
+        peer_addresses: Option<Arc<PeerAddresses>>,
+        incoming_config: IncomingConfig,
+        quic_config: quinn::ServerConfig,
+    ) -> Self {
+        Self {
             endpoint,
+            runtime,
+            closed: Arc::default(),
             side: endpoint_side::Server {
-                _marker: PhantomData,
+                incoming_config: std::sync::Mutex::new(incoming_config),
+                quic_config: std::sync::Mutex::new(quic_config),
+                connection_callback: std::sync::Mutex::default(),
+                peer_addresses,
             },
-        })
+        }
     }
 
     /// Get the next incoming connection attempt from a client.
+    ///
+    /// If the endpoint has been closed (see [`close`](Self::close)), this never resolves.
     pub async fn accept(&self) -> IncomingSession {
-        let quic_connecting = self
-            .endpoint
-            .accept()
-            .await
-            .expect("Endpoint cannot be closed");
+        let Some(quic_connecting) = self.endpoint.accept().await else {
+            debug!("Endpoint closed: no more incoming connections");
+            return std::future::pending().await;
+        };
 
         debug!("New incoming QUIC connection");
 
-        IncomingSession::new(quic_connecting)
+        let mut incoming_config = self
+            .side
+            .incoming_config
+            .lock()
+            .expect("Mutex is not poisoned")
+            .clone();
+
+        incoming_config.connection_callback = self
+            .side
+            .connection_callback
+            .lock()
+            .expect("Mutex is not poisoned")
+            .clone();
+        incoming_config.peer_addresses = self.side.peer_addresses.clone();
+
+        IncomingSession::new(quic_connecting, self.runtime.clone(), incoming_config)
+    }
+
+    /// Sets a callback invoked on every established session.
+    ///
+    /// The `callback` runs in [`SessionRequest::accept`], once the session is established
+    /// and before the [`Connection`] is returned to the caller. This allows handling
+    /// cross-cutting concerns (metrics, logging, registering sessions) in a single place,
+    /// rather than at each accept site.
+    ///
+    /// The `callback` should return quickly, as it delays the accept caller. It replaces any
+    /// previously set callback, and applies to connection attempts accepted afterwards. It is
+    /// kept across [`reload_config`](Self::reload_config).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use wtransport::endpoint::endpoint_side::Server;
+    /// use std::sync::atomic::AtomicU64;
+    /// use std::sync::atomic::Ordering;
+    /// use std::sync::Arc;
+    ///
+    /// # fn run(endpoint: wtransport::Endpoint<Server>) {
+    /// let sessions = Arc::new(AtomicU64::new(0));
+    ///
+    /// endpoint.on_connection({
+    ///     let sessions = sessions.clone();
+    ///     move |connection| {
+    ///         sessions.fetch_add(1, Ordering::Relaxed);
+    ///         println!("New session from {}", connection.remote_address());
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn on_connection<F>(&self, callback: F)
+    where
+        F: Fn(&Connection) + Send + Sync + 'static,
+    {
+        *self
+            .side
+            .connection_callback
+            .lock()
+            .expect("Mutex is not poisoned") = Some(Arc::new(callback));
     }
 
     /// Reloads the server configuration.
@@ -187,43 +508,321 @@ impl Endpoint<endpoint_side::Server> {
     /// * `rebind` - A boolean indicating whether the server should rebind its socket.
     ///              If `true`, the server will bind to a new socket with the provided configuration.
     ///              If `false`, the bind address configuration will be ignored.
+    ///
+    /// **Note**: endpoint-wide options, such as
+    /// [`max_udp_payload_size`](crate::config::ServerConfigBuilder::max_udp_payload_size),
+    /// are not reloaded.
+    ///
+    /// Migration controls (see
+    /// [`migration_policy`](crate::config::ServerConfigBuilder::migration_policy) and
+    /// [`migration_rate_limit`](crate::config::ServerConfigBuilder::migration_rate_limit))
+    /// can be reloaded only if the endpoint was created with some: otherwise, this method
+    /// fails with [`std::io::ErrorKind::Unsupported`].
     pub fn reload_config(&self, server_config: ServerConfig, rebind: bool) -> std::io::Result<()> {
+        if self.side.peer_addresses.is_none() && Self::peer_addresses(&server_config).is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "migration policy requires an endpoint created with one",
+            ));
+        }
+
         if rebind {
-            let socket =
-                Self::bind_socket(server_config.bind_address, server_config.dual_stack_config)?;
+            let socket = Self::bind_socket(
+                server_config.bind_address,
+                server_config.dual_stack_config,
+                server_config.bind_device.as_deref(),
+            )?;
             self.endpoint.rebind(socket.into())?;
         }
 
+        *self
+            .side
+            .incoming_config
+            .lock()
+            .expect("Mutex is not poisoned") = IncomingConfig::new(&server_config);
+
         let quic_config = server_config.quic_config;
+        *self.side.quic_config.lock().expect("Mutex is not poisoned") = quic_config.clone();
         self.endpoint.set_server_config(Some(quic_config));
 
         Ok(())
     }
+
+    /// Replaces the QUIC transport configuration applied to newly accepted connections.
+    ///
+    /// Existing connections keep the parameters negotiated during their handshake. The rest
+    /// of the server configuration (e.g., the TLS certificate) is unchanged.
+    ///
+    /// The transport configuration replaces the one derived from the
+    /// [`ServerConfigBuilder`](crate::config::ServerConfigBuilder) options as a whole. Note
+    /// that WebTransport requires some streams for its own purposes: the peer must be allowed
+    /// to open at least 3 unidirectional streams (HTTP/3 control and QPACK streams) and one
+    /// bidirectional stream (the session stream), in addition to those for the application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use wtransport::endpoint::endpoint_side::Server;
+    /// # fn run(endpoint: wtransport::Endpoint<Server>) {
+    /// let mut transport_config = wtransport::quinn::TransportConfig::default();
+    /// transport_config.max_concurrent_bidi_streams(17u32.into());
+    ///
+    /// endpoint.set_default_transport_config(transport_config);
+    /// # }
+    /// ```
+    #[cfg(feature = "quinn")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+    pub fn set_default_transport_config(&self, transport_config: quinn::TransportConfig) {
+        let mut quic_config = self.side.quic_config.lock().expect("Mutex is not poisoned");
+        quic_config.transport_config(Arc::new(transport_config));
+        self.endpoint.set_server_config(Some(quic_config.clone()));
+    }
 }
 
 impl Endpoint<endpoint_side::Client> {
     /// Constructs a *client* endpoint.
     pub fn client(client_config: ClientConfig) -> std::io::Result<Self> {
-        let quic_config = client_config.quic_config;
-        let socket =
-            Self::bind_socket(client_config.bind_address, client_config.dual_stack_config)?;
-        let runtime = Arc::new(TokioRuntime);
+        Self::client_impl(client_config, Arc::new(TokioRuntime))
+    }
+
+    /// Constructs a *client* endpoint driven by a custom QUIC runtime.
+    ///
+    /// The `runtime` is in charge of I/O operations and timers of the underlying QUIC
+    /// endpoint. This can be used, for instance, to control time in tests.
+    ///
+    /// [`Endpoint::client`] is equivalent to this method with [`quinn::TokioRuntime`].
+    #[cfg(feature = "quinn")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+    pub fn client_with_runtime(
+        client_config: ClientConfig,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self> {
+        Self::client_impl(client_config, runtime)
+    }
+
+    /// Constructs a *client* endpoint over a user-supplied UDP transport.
+    ///
+    /// Instead of binding an OS UDP socket, the endpoint sends and receives QUIC packets
+    /// through `socket`. The bind address of `client_config` is ignored.
+    ///
+    /// See [`Endpoint::server_with_socket`] for the contract `socket` must fulfill.
+    ///
+    /// # Proxies
+    ///
+    /// This is the hook to reach servers through a proxy. WebTransport runs over QUIC, hence
+    /// over UDP: a classic HTTP `CONNECT` proxy only tunnels TCP streams, so it *cannot*
+    /// carry a WebTransport connection, and there is no built-in support for it.
+    ///
+    /// Proxies supporting *CONNECT-UDP* ([RFC 9298](https://www.rfc-editor.org/rfc/rfc9298))
+    /// can be used instead: the supplied `socket` establishes the tunnel with the proxy, then
+    /// encapsulates each outgoing datagram towards the proxy and decapsulates the incoming ones,
+    /// reporting the *target* server address as peer address. The URL passed to
+    /// [`connect`](Self::connect) still refers to the target server, so TLS validation
+    /// applies end-to-end.
+    #[cfg(feature = "quinn")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+    pub fn client_with_socket<S>(
+        client_config: ClientConfig,
+        socket: S,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self>
+    where
+        S: quinn::AsyncUdpSocket,
+    {
+        let endpoint = quinn::Endpoint::new_with_abstract_socket(
+            client_config.endpoint_config.clone(),
+            None,
+            socket,
+            runtime.clone(),
+        )?;
+
+        Ok(Self::client_from_quic(endpoint, runtime, client_config))
+    }
+
+    fn client_impl(
+        client_config: ClientConfig,
+        runtime: Arc<dyn quinn::Runtime>,
+    ) -> std::io::Result<Self> {
+        let socket = match &client_config.bind_port_range {
+            Some(ports) => Self::bind_socket_in_range(
+                client_config.bind_address,
+                ports.clone(),
+                client_config.dual_stack_config,
+                client_config.bind_device.as_deref(),
+            )?,
+            None => Self::bind_socket(
+                client_config.bind_address,
+                client_config.dual_stack_config,
+                client_config.bind_device.as_deref(),
+            )?,
+        };
 
-        let mut endpoint = quinn::Endpoint::new(
-            quinn::EndpointConfig::default(),
+        let endpoint = quinn::Endpoint::new(
+            client_config.endpoint_config.clone(),
             None,
             socket.into(),
-            runtime,
+            runtime.clone(),
         )?;
 
-        endpoint.set_default_client_config(quic_config);
+        Ok(Self::client_from_quic(endpoint, runtime, client_config))
+    }
+
+    fn client_from_quic(
+        mut endpoint: quinn::Endpoint,
+        runtime: Arc<dyn quinn::Runtime>,
+        client_config: ClientConfig,
+    ) -> Self {
+        endpoint.set_default_client_config(client_config.quic_config);
 
-        Ok(Self {
+        Self {
             endpoint,
+            runtime,
+            closed: Arc::default(),
             side: endpoint_side::Client {
                 dns_resolver: Mutex::new(client_config.dns_resolver),
+                dns_resolve_timeout: client_config.dns_resolve_timeout,
+                address_family: client_config.address_family,
+                handshake_timeout: client_config.handshake_timeout,
+                session_setup_timeout: client_config.session_setup_timeout,
+                max_field_section_size: client_config.max_field_section_size,
+                strict_hostname_verification: client_config.strict_hostname_verification,
+                #[cfg(feature = "dangerous-configuration")]
+                rejected_names: client_config.rejected_names,
+                receive_window: client_config.receive_window,
+                datagram_receive_buffer_size: client_config.datagram_receive_buffer_size,
             },
-        })
+        }
+    }
+
+    /// Checks whether the QUIC (UDP) path to a server is usable.
+    ///
+    /// A QUIC handshake is attempted towards the server identified by `url`, bounded by
+    /// `timeout`. The probe does not open a WebTransport session: once the handshake
+    /// completes, the connection is immediately closed.
+    ///
+    /// This is a lightweight reachability check, so that applications can fall back to
+    /// another transport (e.g., WebSocket) when UDP traffic is blocked. See [`Probe`] for
+    /// the outcome.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use wtransport::endpoint::endpoint_side::Client;
+    /// # async fn example(endpoint: wtransport::Endpoint<Client>) {
+    /// let probe = endpoint
+    ///     .probe("https://example.com:4433", Duration::from_secs(2))
+    ///     .await;
+    ///
+    /// if !probe.quic_reachable {
+    ///     // fall back to another transport
+    /// }
+    /// # }
+    /// ```
+    pub async fn probe(&self, url: &str, timeout: Duration) -> Probe {
+        let start = Instant::now();
+
+        let result = async {
+            let url = validate_url(url)?;
+            let (socket_address, server_name) = self.resolve(&url).await?;
+
+            let quic_connecting = self
+                .endpoint
+                .connect(socket_address, &server_name)
+                .expect("QUIC connection parameters must be validated");
+
+            with_handshake_timeout(quic_connecting, &*self.runtime, Some(timeout))
+                .await
+                .map_err(ConnectingError::ConnectionError)
+        }
+        .await;
+
+        match result {
+            Ok(quic_connection) => {
+                let rtt = quic_connection.rtt();
+                quic_connection.close(quinn::VarInt::from_u32(0), b"probe");
+
+                Probe {
+                    quic_reachable: true,
+                    handshake_completed: true,
+                    rtt: Some(rtt),
+                    elapsed: start.elapsed(),
+                    error: None,
+                }
+            }
+            Err(error) => {
+                // Any answer from the server (even a rejection) proves the path works
+                let quic_reachable = matches!(
+                    &error,
+                    ConnectingError::ConnectionError(
+                        ConnectionError::QuicProto(_)
+                            | ConnectionError::ConnectionClosed(_)
+                            | ConnectionError::ApplicationClosed(_),
+                    )
+                );
+
+                debug!("Probe failed: {error}");
+
+                Probe {
+                    quic_reachable,
+                    handshake_completed: false,
+                    rtt: None,
+                    elapsed: start.elapsed(),
+                    error: Some(error),
+                }
+            }
+        }
+    }
+
+    /// Resolves the server address and name of `url`.
+    async fn resolve(&self, url: &Url) -> Result<(SocketAddr, String), ConnectingError> {
+        let host = url.host().expect("URL host has been validated");
+        let port = url.port().unwrap_or(443);
+
+        let resolved = match host {
+            Host::Domain(domain) => {
+                let resolve = async {
+                    self.side
+                        .dns_resolver
+                        .lock()
+                        .await
+                        .resolve_all(&format!("{domain}:{port}"))
+                        .await
+                };
+
+                let addresses =
+                    with_timeout(&*self.runtime, self.side.dns_resolve_timeout, resolve)
+                        .await
+                        .ok_or_else(|| {
+                            debug!("DNS resolution timed out");
+                            ConnectingError::DnsLookup(std::io::Error::new(
+                                std::io::ErrorKind::TimedOut,
+                                "DNS resolution timed out",
+                            ))
+                        })?
+                        .map_err(ConnectingError::DnsLookup)?;
+
+                let ipv4_socket = matches!(self.endpoint.local_addr(), Ok(SocketAddr::V4(_)));
+
+                let socket_address = self
+                    .side
+                    .address_family
+                    .select(&addresses, ipv4_socket)
+                    .ok_or(ConnectingError::DnsNotFound)?;
+
+                (socket_address, domain.to_string())
+            }
+            Host::Ipv4(address) => {
+                let socket_address = SocketAddr::V4(SocketAddrV4::new(address, port));
+                (socket_address, address.to_string())
+            }
+            Host::Ipv6(address) => {
+                let socket_address = SocketAddr::V6(SocketAddrV6::new(address, port, 0, 0));
+                (socket_address, address.to_string())
+            }
+        };
+
+        Ok(resolved)
     }
 
     /// Establishes a WebTransport connection to a specified URL.
@@ -241,6 +840,33 @@ impl Endpoint<endpoint_side::Client> {
     ///               configured with
     ///               [`ClientConfigBuilder::dns_resolver`](crate::config::ClientConfigBuilder::dns_resolver).
     ///
+    /// When the URL specifies an IP address, the server certificate is validated against
+    /// its IP address subject alternative names.
+    ///
+    /// # 0-RTT
+    ///
+    /// Early data (0-RTT) is not supported: the connection always completes a full QUIC
+    /// handshake before the session is requested, and a [`Connection`] is only returned once
+    /// the server has accepted the session. Consequently, no application data (streams or
+    /// datagrams) is ever sent as replayable early data.
+    ///
+    /// This also guarantees that the strict hostname verification (see
+    /// [`ClientConfigBuilder::strict_hostname_verification`](crate::config::ClientConfigBuilder::strict_hostname_verification)),
+    /// performed once the handshake completes, happens before any application data is sent.
+    /// Servers built with [`ServerConfig`](crate::ServerConfig) reject 0-RTT as well.
+    ///
+    /// # Cancel safety
+    ///
+    /// Dropping the returned future aborts the connection attempt at any stage:
+    ///   - a pending DNS resolution is abandoned (with [`TokioDnsResolver`], the lookup itself
+    ///     may complete in the background, but its result is discarded);
+    ///   - a started QUIC handshake, or a session request waiting for the server response, is
+    ///     closed and the internal tasks serving it terminate.
+    ///
+    /// No half-open connection is left on the endpoint.
+    ///
+    /// [`TokioDnsResolver`]: crate::config::TokioDnsResolver
+    ///
     /// # Examples
     ///
     /// Connect using a URL with a hostname (DNS resolution is performed):
@@ -287,54 +913,119 @@ impl Endpoint<endpoint_side::Client> {
     {
         let options = options.into_options();
 
-        let url = Url::parse(&options.url)
-            .map_err(|parse_error| ConnectingError::InvalidUrl(parse_error.to_string()))?;
+        let url = validate_url(&options.url)?;
 
-        if url.scheme() != "https" {
-            return Err(ConnectingError::InvalidUrl(
-                "WebTransport URL scheme must be 'https'".to_string(),
-            ));
+        if let Some(authority) = &options.authority {
+            if !is_valid_authority(authority) {
+                return Err(ConnectingError::InvalidPseudoHeader {
+                    name: ":authority",
+                    value: authority.clone(),
+                });
+            }
         }
 
-        let host = url.host().expect("https scheme must have an host");
-        let port = url.port().unwrap_or(443);
+        if let Some(scheme) = &options.scheme {
+            if !is_valid_scheme(scheme) {
+                return Err(ConnectingError::InvalidPseudoHeader {
+                    name: ":scheme",
+                    value: scheme.clone(),
+                });
+            }
+        }
 
-        let (socket_address, server_name) = match host {
-            Host::Domain(domain) => {
-                let socket_address = self
-                    .side
-                    .dns_resolver
-                    .lock()
-                    .await
-                    .resolve(&format!("{domain}:{port}"))
-                    .await
-                    .map_err(ConnectingError::DnsLookup)?
-                    .ok_or(ConnectingError::DnsNotFound)?;
+        let (socket_address, server_name) = self.resolve(&url).await?;
 
-                (socket_address, domain.to_string())
-            }
-            Host::Ipv4(address) => {
-                let socket_address = SocketAddr::V4(SocketAddrV4::new(address, port));
-                (socket_address, address.to_string())
-            }
-            Host::Ipv6(address) => {
-                let socket_address = SocketAddr::V6(SocketAddrV6::new(address, port, 0, 0));
-                (socket_address, address.to_string())
+        if !self.is_open() {
+            return Err(ConnectingError::EndpointClosed);
+        }
+
+        debug!(
+            %url,
+            remote_address = %socket_address,
+            server_name,
+            local_address = ?self.endpoint.local_addr().ok(),
+            "Connecting"
+        );
+
+        let quic_connecting = match self.endpoint.connect(socket_address, &server_name) {
+            Ok(quic_connecting) => quic_connecting,
+            Err(quinn::ConnectError::EndpointStopping) => {
+                return Err(ConnectingError::EndpointClosed)
             }
+            Err(error) => panic!("QUIC connection parameters must be validated: {error}"),
         };
 
-        let quic_connection = self
-            .endpoint
-            .connect(socket_address, &server_name)
-            .expect("QUIC connection parameters must be validated")
-            .await
-            .map_err(|connection_error| {
-                ConnectingError::ConnectionError(connection_error.into())
-            })?;
+        let quic_connection =
+            with_handshake_timeout(quic_connecting, &*self.runtime, self.side.handshake_timeout)
+                .await
+                .map_err(|error| {
+                    if self.side.strict_hostname_verification && error.is_hostname_mismatch() {
+                        self.hostname_mismatch(&server_name)
+                    } else {
+                        ConnectingError::ConnectionError(error)
+                    }
+                })?;
+
+        let session_setup = self.setup_session(quic_connection, &url, options);
+
+        match with_timeout(
+            &*self.runtime,
+            self.side.session_setup_timeout,
+            session_setup,
+        )
+        .await
+        {
+            Some(result) => result,
+            None => {
+                debug!("WebTransport session setup timed out");
+                Err(ConnectingError::ConnectionError(
+                    ConnectionError::SessionSetupTimedOut,
+                ))
+            }
+        }
+    }
+
+    /// Reports the server certificate is not valid for `server_name`, with the names it was
+    /// issued for (if recorded by the verifier).
+    fn hostname_mismatch(&self, server_name: &str) -> ConnectingError {
+        debug!("Server certificate is not valid for '{server_name}'");
+
+        #[cfg(feature = "dangerous-configuration")]
+        let found = self
+            .side
+            .rejected_names
+            .lock()
+            .expect("Mutex is not poisoned")
+            .remove(server_name)
+            .unwrap_or_default();
+
+        #[cfg(not(feature = "dangerous-configuration"))]
+        let found = Vec::new();
+
+        ConnectingError::HostnameMismatch {
+            expected: server_name.to_string(),
+            found,
+        }
+    }
 
-        let driver = Driver::init(quic_connection.clone());
+    /// Establishes the WebTransport session over a QUIC connection (i.e., HTTP/3 settings
+    /// exchange and `CONNECT` request).
+    async fn setup_session(
+        &self,
+        quic_connection: quinn::Connection,
+        url: &Url,
+        options: ConnectOptions,
+    ) -> Result<Connection, ConnectingError> {
+        let driver = Driver::init(
+            quic_connection.clone(),
+            self.runtime.clone(),
+            self.side.max_field_section_size,
+            false,
+            None,
+            self.side.datagram_receive_buffer_size,
+        );
 
-        let _settings = driver.accept_settings().await.map_err(|driver_error| {
+        let settings = driver.accept_settings().await.map_err(|driver_error| {
             ConnectingError::ConnectionError(ConnectionError::with_driver_error(
                 driver_error,
                 &quic_connection,
@@ -346,12 +1037,28 @@ impl Endpoint<endpoint_side::Client> {
         let mut session_request_proto =
             SessionRequestProto::new(url.as_ref()).expect("Url has been already validate");
 
+        if let Some(authority) = options.authority {
+            session_request_proto.set_authority(authority);
+        }
+
+        if let Some(scheme) = options.scheme {
+            session_request_proto.set_scheme(scheme);
+        }
+
         for (k, v) in options.additional_headers {
             session_request_proto
                 .insert(k.clone(), v)
                 .map_err(|ReservedHeader| ConnectingError::ReservedHeader(k))?;
         }
 
+        if let Some(max_field_section_size) = settings.get(SettingId::MaxFieldSectionSize) {
+            if session_request_proto.headers().field_section_size()
+                > max_field_section_size.into_inner()
+            {
+                return Err(ConnectingError::HeadersTooLarge);
+            }
+        }
+
         let mut stream_session = match driver.open_session(session_request_proto).await {
             Ok(stream_session) => stream_session,
             Err(driver_error) => {
@@ -413,6 +1120,12 @@ impl Endpoint<endpoint_side::Client> {
             }
         };
 
+        if let Some(max_field_section_size) = self.side.max_field_section_size {
+            if headers.field_section_size() > max_field_section_size.into_inner() {
+                return Err(ConnectingError::HeadersTooLarge);
+            }
+        }
+
         let session_response = match SessionResponseProto::try_from(headers) {
             Ok(session_response) => session_response,
             Err(_) => {
@@ -432,11 +1145,94 @@ impl Endpoint<endpoint_side::Client> {
                     ))
                 }
             }
+        } else if session_response.code() == StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE {
+            return Err(ConnectingError::HeadersTooLarge);
         } else {
             return Err(ConnectingError::SessionRejected);
         }
 
-        Ok(Connection::new(quic_connection, driver, session_id))
+        Ok(Connection::new(
+            quic_connection,
+            driver,
+            session_id,
+            self.side.receive_window,
+        ))
+    }
+
+    /// Connects to multiple candidate servers concurrently, returning the first established
+    /// connection.
+    ///
+    /// Each candidate is connected as with [`connect`](Self::connect). As soon as one
+    /// succeeds, the other attempts are cancelled (i.e., their connections aborted).
+    /// This is useful for application-level failover among equivalent servers.
+    ///
+    /// If all the attempts fail, the error of each candidate is returned, in the same
+    /// order as `candidates`. An empty set of candidates fails immediately (with no errors).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use wtransport::ClientConfig;
+    /// # use wtransport::Endpoint;
+    /// # async fn run() -> Result<()> {
+    /// let endpoint = Endpoint::client(ClientConfig::default())?;
+    ///
+    /// match endpoint
+    ///     .connect_race([
+    ///         "https://eu.example.com",
+    ///         "https://us.example.com",
+    ///     ])
+    ///     .await
+    /// {
+    ///     Ok(connection) => println!("Connected to {}", connection.remote_address()),
+    ///     Err(errors) => {
+    ///         for error in errors {
+    ///             println!("Attempt failed: {error}");
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_race<I, O>(
+        &self,
+        candidates: I,
+    ) -> Result<Connection, Vec<ConnectingError>>
+    where
+        I: IntoIterator<Item = O>,
+        O: IntoConnectOptions,
+    {
+        let mut attempts = candidates
+            .into_iter()
+            .map(|options| Some(Box::pin(self.connect(options))))
+            .collect::<Vec<_>>();
+
+        let mut errors = attempts.iter().map(|_| None).collect::<Vec<_>>();
+
+        std::future::poll_fn(|cx| {
+            for (attempt, error) in attempts.iter_mut().zip(errors.iter_mut()) {
+                let Some(future) = attempt else {
+                    continue;
+                };
+
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(Ok(connection)) => return Poll::Ready(Ok(connection)),
+                    Poll::Ready(Err(connecting_error)) => {
+                        *attempt = None;
+                        *error = Some(connecting_error);
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            if attempts.iter().all(Option::is_none) {
+                Poll::Ready(Err(errors.drain(..).flatten().collect()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
     }
 }
 
@@ -461,6 +1257,8 @@ impl Endpoint<endpoint_side::Client> {
 pub struct ConnectOptions {
     url: String,
     additional_headers: HashMap<String, String>,
+    authority: Option<String>,
+    scheme: Option<String>,
 }
 
 impl ConnectOptions {
@@ -481,11 +1279,97 @@ impl ConnectOptions {
         ConnectRequestBuilder {
             url: url.to_string(),
             additional_headers: Default::default(),
+            authority: None,
+            scheme: None,
         }
     }
 }
 
+/// Outcome of [`Endpoint::probe`].
+#[derive(Debug)]
+pub struct Probe {
+    /// Whether the server answered over QUIC, i.e., the UDP path works in both directions.
+    ///
+    /// It is `true` even if the handshake failed afterwards (e.g., the server certificate
+    /// is not trusted), as the server could be reached.
+    pub quic_reachable: bool,
+
+    /// Whether the QUIC handshake completed, server certificate validation included.
+    pub handshake_completed: bool,
+
+    /// Round-trip time estimated during the handshake, if completed.
+    pub rtt: Option<Duration>,
+
+    /// Time spent by the probe, DNS resolution included.
+    pub elapsed: Duration,
+
+    /// Reason of the failure, if the handshake did not complete.
+    pub error: Option<ConnectingError>,
+}
+
+/// Parses and validates a WebTransport URL, before any network activity.
+fn validate_url(url: &str) -> Result<Url, ConnectingError> {
+    let url = Url::parse(url)
+        .map_err(|parse_error| ConnectingError::InvalidUrl(parse_error.to_string()))?;
+
+    if url.scheme() != "https" {
+        return Err(ConnectingError::InvalidUrl(format!(
+            "WebTransport URL scheme must be 'https' (found '{}')",
+            url.scheme()
+        )));
+    }
+
+    if matches!(url.host(), None | Some(Host::Domain(""))) {
+        return Err(ConnectingError::InvalidUrl(
+            "WebTransport URL must have a host".to_string(),
+        ));
+    }
+
+    if url.fragment().is_some() {
+        return Err(ConnectingError::InvalidUrl(
+            "WebTransport URL must not have a fragment".to_string(),
+        ));
+    }
+
+    Ok(url)
+}
+
+/// Checks whether `authority` is a valid `:authority` pseudo-header value (`host[:port]`).
+fn is_valid_authority(authority: &str) -> bool {
+    !authority.is_empty()
+        && authority
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !matches!(c, '@' | '/' | '?' | '#'))
+}
+
+/// Checks whether `scheme` is a valid URI scheme (RFC 3986, Section 3.1).
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
 /// A trait for converting types into `ConnectOptions`.
+///
+/// Besides [`ConnectOptions`] (and its builder), it is implemented for any type
+/// representing the URL as a string (i.e., implementing [`ToString`]), such as `&str`,
+/// [`String`], [`url::Url`] or `http::Uri`. Hence, these can be passed directly to
+/// [`Endpoint::connect`]:
+///
+/// ```no_run
+/// # use anyhow::Result;
+/// # use wtransport::endpoint::endpoint_side::Client;
+/// # async fn example(endpoint: wtransport::Endpoint<Client>) -> Result<()> {
+/// let url = url::Url::parse("https://example.com:4433/webtransport")?;
+/// let connection = endpoint.connect(url).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The URL is validated by [`Endpoint::connect`], before any network activity: an invalid
+/// URL, a scheme other than `https`, a missing host or a fragment (`#...`) result in
+/// [`ConnectingError::InvalidUrl`].
 pub trait IntoConnectOptions {
     /// Perform value-to-value conversion into [`ConnectOptions`].
     fn into_options(self) -> ConnectOptions;
@@ -497,6 +1381,8 @@ pub trait IntoConnectOptions {
 pub struct ConnectRequestBuilder {
     url: String,
     additional_headers: HashMap<String, String>,
+    authority: Option<String>,
+    scheme: Option<String>,
 }
 
 impl ConnectRequestBuilder {
@@ -521,11 +1407,48 @@ impl ConnectRequestBuilder {
         self
     }
 
+    /// Overrides the `:authority` pseudo-header of the request.
+    ///
+    /// By default, it is the authority (host and port) of the URL. This can be used, for
+    /// instance, when connecting through an L7 proxy routing requests by `:authority`,
+    /// independently of the network destination (which is still determined by the URL).
+    ///
+    /// The value must be a non-empty authority (`host[:port]`), without user information:
+    /// otherwise, [`Endpoint::connect`] fails with
+    /// [`ConnectingError::InvalidPseudoHeader`].
+    pub fn authority<A>(mut self, authority: A) -> Self
+    where
+        A: ToString,
+    {
+        self.authority = Some(authority.to_string());
+        self
+    }
+
+    /// Overrides the `:scheme` pseudo-header of the request.
+    ///
+    /// By default, it is `https`, as required by WebTransport. Note that a compliant
+    /// WebTransport server rejects any other scheme: this is only meant for intermediaries
+    /// expecting a different value.
+    ///
+    /// The value must be a valid URI scheme (e.g., `https`): otherwise,
+    /// [`Endpoint::connect`] fails with [`ConnectingError::InvalidPseudoHeader`].
+    ///
+    /// **Note**: the mandatory `:method` and `:protocol` pseudo-headers cannot be overridden.
+    pub fn scheme<S>(mut self, scheme: S) -> Self
+    where
+        S: ToString,
+    {
+        self.scheme = Some(scheme.to_string());
+        self
+    }
+
     /// Constructs the [`ConnectOptions`] from the builder configuration.
     pub fn build(self) -> ConnectOptions {
         ConnectOptions {
             url: self.url,
             additional_headers: self.additional_headers,
+            authority: self.authority,
+            scheme: self.scheme,
         }
     }
 }
@@ -557,17 +1480,111 @@ type DynFutureIncomingSession =
 /// [`Future`] for an in-progress incoming connection attempt.
 ///
 /// Created by [`Endpoint::accept`].
-pub struct IncomingSession(Pin<Box<DynFutureIncomingSession>>);
+///
+/// The peer's address is available immediately (see [`remote_address`](Self::remote_address)),
+/// before any resource is spent on the handshake. Dropping the [`IncomingSession`] without
+/// awaiting it refuses the connection.
+pub struct IncomingSession(Pin<Box<DynFutureIncomingSession>>, SocketAddr);
 
 impl IncomingSession {
-    fn new(quic_connecting: quinn::Connecting) -> Self {
-        Self(Box::pin(Self::accept(quic_connecting)))
+    fn new(
+        quic_connecting: quinn::Connecting,
+        runtime: Arc<dyn quinn::Runtime>,
+        incoming_config: IncomingConfig,
+    ) -> Self {
+        let remote_address = normalize_addr(quic_connecting.remote_address());
+
+        Self(
+            Box::pin(Self::accept(quic_connecting, runtime, incoming_config)),
+            remote_address,
+        )
     }
 
-    async fn accept(quic_connecting: quinn::Connecting) -> Result<SessionRequest, ConnectionError> {
-        let quic_connection = quic_connecting.await?;
+    /// Returns the peer's UDP address.
+    ///
+    /// IPv4-mapped IPv6 addresses are normalized (see [`normalize_addr`]).
+    ///
+    /// This can be used, for instance, to apply per-address rate limits before awaiting
+    /// the handshake.
+    #[inline(always)]
+    pub fn remote_address(&self) -> SocketAddr {
+        self.1
+    }
 
-        let driver = Driver::init(quic_connection.clone());
+    async fn accept(
+        quic_connecting: quinn::Connecting,
+        runtime: Arc<dyn quinn::Runtime>,
+        incoming_config: IncomingConfig,
+    ) -> Result<SessionRequest, ConnectionError> {
+        let remote_address = normalize_addr(quic_connecting.remote_address());
+
+        let result = Self::accept_impl(quic_connecting, runtime, incoming_config).await;
+
+        if let Err(error) = &result {
+            let category = match error {
+                ConnectionError::HandshakeTimedOut => "handshake-timeout",
+                ConnectionError::SessionSetupTimedOut => "session-setup-timeout",
+                ConnectionError::TimedOut => "timeout",
+                ConnectionError::ConnectionClosed(_) | ConnectionError::ApplicationClosed(_) => {
+                    "closed-by-peer"
+                }
+                ConnectionError::LocallyClosed => "locally-closed",
+                ConnectionError::LocalH3Error(_)
+                | ConnectionError::HeadersTooLarge
+                | ConnectionError::OriginNotAllowed => "webtransport",
+                ConnectionError::QuicProto(_) if error.tls_alert().is_some() => "tls",
+                ConnectionError::QuicProto(_) => "quic",
+            };
+
+            warn!(
+                %remote_address,
+                category,
+                tls_alert = error.tls_alert(),
+                "Incoming session failed: {error}"
+            );
+        }
+
+        result
+    }
+
+    async fn accept_impl(
+        quic_connecting: quinn::Connecting,
+        runtime: Arc<dyn quinn::Runtime>,
+        incoming_config: IncomingConfig,
+    ) -> Result<SessionRequest, ConnectionError> {
+        let quic_connection = with_handshake_timeout(
+            quic_connecting,
+            &*runtime,
+            incoming_config.handshake_timeout,
+        )
+        .await?;
+
+        let session_setup_timeout = incoming_config.session_setup_timeout;
+        let session_setup = Self::setup_session(quic_connection, runtime.clone(), incoming_config);
+
+        match with_timeout(&*runtime, session_setup_timeout, session_setup).await {
+            Some(result) => result,
+            None => {
+                debug!("WebTransport session setup timed out");
+                Err(ConnectionError::SessionSetupTimedOut)
+            }
+        }
+    }
+
+    /// Waits for the WebTransport session request over an established QUIC connection.
+    async fn setup_session(
+        quic_connection: quinn::Connection,
+        runtime: Arc<dyn quinn::Runtime>,
+        incoming_config: IncomingConfig,
+    ) -> Result<SessionRequest, ConnectionError> {
+        let driver = Driver::init(
+            quic_connection.clone(),
+            runtime,
+            incoming_config.max_field_section_size,
+            true,
+            incoming_config.migration_control(),
+            incoming_config.datagram_receive_buffer_size,
+        );
 
         let _settings = driver.accept_settings().await.map_err(|driver_error| {
             ConnectionError::with_driver_error(driver_error, &quic_connection)
@@ -575,11 +1592,49 @@ impl IncomingSession {
 
         // TODO(biagio): validate settings
 
-        let stream_session = driver.accept_session().await.map_err(|driver_error| {
+        let mut stream_session = driver.accept_session().await.map_err(|driver_error| {
             ConnectionError::with_driver_error(driver_error, &quic_connection)
         })?;
 
-        Ok(SessionRequest::new(quic_connection, driver, stream_session))
+        if let Some(reason) =
+            incoming_config.check_request_headers(stream_session.request().headers())
+        {
+            debug!("Session request rejected: {reason}");
+
+            let response = SessionResponseProto::request_header_fields_too_large();
+            let _ = stream_session
+                .write_frame(response.headers().generate_frame())
+                .await;
+            stream_session.finish().await;
+            quic_connection.close(varint_w2q(ErrorCode::NoError.to_code()), b"");
+
+            return Err(ConnectionError::HeadersTooLarge);
+        }
+
+        if !incoming_config.is_origin_allowed(stream_session.request().origin()) {
+            debug!(
+                "Session request rejected: origin {:?} not allowed",
+                stream_session.request().origin()
+            );
+
+            let response = SessionResponseProto::forbidden();
+            let _ = stream_session
+                .write_frame(response.headers().generate_frame())
+                .await;
+            stream_session.finish().await;
+            quic_connection.close(varint_w2q(ErrorCode::NoError.to_code()), b"");
+
+            return Err(ConnectionError::OriginNotAllowed);
+        }
+
+        Ok(SessionRequest::new(
+            quic_connection,
+            driver,
+            stream_session,
+            incoming_config.receive_window,
+            incoming_config.max_connection_lifetime,
+            incoming_config.connection_callback,
+        ))
     }
 }
 
@@ -591,6 +1646,199 @@ impl Future for IncomingSession {
     }
 }
 
+/// Callback invoked on every session established by a server endpoint.
+type ConnectionCallback = Arc<dyn Fn(&Connection) + Send + Sync>;
+
+/// Server configuration applied to each incoming session.
+#[derive(Clone)]
+struct IncomingConfig {
+    handshake_timeout: Option<Duration>,
+    max_field_section_size: Option<VarInt>,
+    max_request_headers: Option<usize>,
+    max_request_header_bytes: Option<usize>,
+    allowed_origins: Option<Arc<[String]>>,
+    max_connection_lifetime: Option<Duration>,
+    session_setup_timeout: Option<Duration>,
+    receive_window: VarInt,
+    datagram_receive_buffer_size: usize,
+    migration_policy: Option<MigrationPolicy>,
+    migration_rate_limit: Option<(u32, Duration)>,
+    connection_callback: Option<ConnectionCallback>,
+    peer_addresses: Option<Arc<PeerAddresses>>,
+}
+
+impl IncomingConfig {
+    fn new(server_config: &ServerConfig) -> Self {
+        Self {
+            handshake_timeout: server_config.handshake_timeout,
+            max_field_section_size: server_config.max_field_section_size,
+            max_request_headers: server_config.max_request_headers,
+            max_request_header_bytes: server_config.max_request_header_bytes,
+            allowed_origins: server_config.allowed_origins.clone(),
+            max_connection_lifetime: server_config.max_connection_lifetime,
+            session_setup_timeout: server_config.session_setup_timeout,
+            receive_window: server_config.receive_window,
+            datagram_receive_buffer_size: server_config.datagram_receive_buffer_size,
+            migration_policy: server_config.migration_policy.clone(),
+            migration_rate_limit: server_config.migration_rate_limit,
+            connection_callback: None,
+            peer_addresses: None,
+        }
+    }
+
+    fn migration_control(&self) -> Option<MigrationControl> {
+        if self.migration_policy.is_none() && self.migration_rate_limit.is_none() {
+            return None;
+        }
+
+        self.peer_addresses
+            .clone()
+            .map(|peer_addresses| MigrationControl {
+                policy: self.migration_policy.clone(),
+                rate_limit: self.migration_rate_limit,
+                peer_addresses,
+            })
+    }
+
+    /// Checks the session request headers against the configured limits.
+    ///
+    /// Returns the reason of the violation, if any.
+    fn check_request_headers(&self, headers: &Headers) -> Option<String> {
+        if let Some(max) = self.max_field_section_size {
+            let size = headers.field_section_size();
+            if size > max.into_inner() {
+                return Some(format!("field section size {size} exceeds {max}"));
+            }
+        }
+
+        if let Some(max) = self.max_request_headers {
+            let count = headers.as_ref().len();
+            if count > max {
+                return Some(format!("{count} header fields exceed {max}"));
+            }
+        }
+
+        if let Some(max) = self.max_request_header_bytes {
+            let bytes = headers
+                .as_ref()
+                .iter()
+                .map(|(name, value)| name.len() + value.len())
+                .sum::<usize>();
+            if bytes > max {
+                return Some(format!("header fields size {bytes} exceeds {max} bytes"));
+            }
+        }
+
+        None
+    }
+
+    /// Checks the session request origin against the allowed origins.
+    fn is_origin_allowed(&self, origin: Option<&str>) -> bool {
+        let Some(allowed_origins) = &self.allowed_origins else {
+            return true;
+        };
+
+        allowed_origins.iter().any(|allowed| {
+            allowed == "*" || origin.is_some_and(|origin| origin_matches(allowed, origin))
+        })
+    }
+}
+
+/// Compares two serialized origins (`scheme://host[:port]`).
+///
+/// Scheme and host are case-insensitive, and a trailing slash is ignored.
+fn origin_matches(allowed: &str, origin: &str) -> bool {
+    allowed
+        .trim_end_matches('/')
+        .eq_ignore_ascii_case(origin.trim_end_matches('/'))
+}
+
+fn close_endpoint(
+    endpoint: &quinn::Endpoint,
+    closed: &AtomicBool,
+    error_code: VarInt,
+    reason: &[u8],
+) {
+    if closed.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    debug!("Closing endpoint");
+    endpoint.set_server_config(None);
+    endpoint.close(varint_w2q(error_code), reason);
+}
+
+/// Converts an IPv4-mapped IPv6 address (e.g., `[::ffff:1.2.3.4]:443`) into the
+/// corresponding plain IPv4 address (e.g., `1.2.3.4:443`).
+///
+/// Endpoints bound to a dual-stack IPv6 socket (see [`IpBindConfig::InAddrAnyDual`])
+/// see IPv4 peers with their IPv4-mapped form. All the peer addresses returned by this
+/// crate (e.g., [`Connection::remote_address`]) are already normalized, so they can be
+/// compared against plain IPv4 addresses (allowlists, rate limiters, etc.).
+///
+/// Other addresses are returned unchanged.
+///
+/// [`IpBindConfig::InAddrAnyDual`]: crate::config::IpBindConfig::InAddrAnyDual
+pub fn normalize_addr(address: SocketAddr) -> SocketAddr {
+    match address {
+        SocketAddr::V6(address) => match address.ip().to_ipv4_mapped() {
+            Some(ip) => SocketAddr::V4(SocketAddrV4::new(ip, address.port())),
+            None => SocketAddr::V6(address),
+        },
+        address => address,
+    }
+}
+
+/// Awaits the QUIC handshake, bounded by `handshake_timeout` (if any).
+///
+/// On timeout, the pending connection is dropped (and so implicitly closed).
+async fn with_handshake_timeout(
+    quic_connecting: quinn::Connecting,
+    runtime: &dyn quinn::Runtime,
+    handshake_timeout: Option<Duration>,
+) -> Result<quinn::Connection, ConnectionError> {
+    match with_timeout(runtime, handshake_timeout, quic_connecting).await {
+        Some(result) => Ok(result?),
+        None => {
+            debug!("QUIC handshake timed out");
+            Err(ConnectionError::HandshakeTimedOut)
+        }
+    }
+}
+
+/// Awaits `future`, bounded by `timeout` (if any).
+///
+/// The timer is provided by `runtime`. Returns `None` on timeout.
+async fn with_timeout<F>(
+    runtime: &dyn quinn::Runtime,
+    timeout: Option<Duration>,
+    future: F,
+) -> Option<F::Output>
+where
+    F: Future,
+{
+    match timeout {
+        Some(timeout) => {
+            // Mutex only makes the timer `Sync` (as required by `IncomingSession`).
+            let mut timer = std::sync::Mutex::new(runtime.new_timer(Instant::now() + timeout));
+
+            let timer = std::future::poll_fn(|cx| {
+                timer
+                    .get_mut()
+                    .expect("Mutex is not poisoned")
+                    .as_mut()
+                    .poll(cx)
+            });
+
+            tokio::select! {
+                output = future => Some(output),
+                () = timer => None,
+            }
+        }
+        None => Some(future.await),
+    }
+}
+
 /// A incoming client session request.
 ///
 /// Server should use methods [`accept`](Self::accept), [`forbidden`](Self::forbidden),
@@ -599,6 +1847,9 @@ pub struct SessionRequest {
     quic_connection: quinn::Connection,
     driver: Driver,
     stream_session: StreamSession,
+    receive_window: VarInt,
+    max_connection_lifetime: Option<Duration>,
+    connection_callback: Option<ConnectionCallback>,
 }
 
 impl SessionRequest {
@@ -606,11 +1857,17 @@ impl SessionRequest {
         quic_connection: quinn::Connection,
         driver: Driver,
         stream_session: StreamSession,
+        receive_window: VarInt,
+        max_connection_lifetime: Option<Duration>,
+        connection_callback: Option<ConnectionCallback>,
     ) -> Self {
         Self {
             quic_connection,
             driver,
             stream_session,
+            receive_window,
+            max_connection_lifetime,
+            connection_callback,
         }
     }
 
@@ -639,6 +1896,21 @@ impl SessionRequest {
         self.stream_session.request().headers().as_ref()
     }
 
+    /// Returns the peer's UDP address.
+    ///
+    /// IPv4-mapped IPv6 addresses are normalized (see [`normalize_addr`]).
+    pub fn remote_address(&self) -> SocketAddr {
+        normalize_addr(self.quic_connection.remote_address())
+    }
+
+    /// A stable identifier for the underlying QUIC connection.
+    ///
+    /// It is the same value returned by [`Connection::stable_id`] once the request
+    /// is accepted.
+    pub fn stable_id(&self) -> usize {
+        self.quic_connection.stable_id()
+    }
+
     /// Accepts the client request and it establishes the WebTransport session.
     pub async fn accept(mut self) -> Result<Connection, ConnectionError> {
         let user_agent = self.user_agent().unwrap_or_default();
@@ -661,11 +1933,22 @@ impl SessionRequest {
                 ConnectionError::with_driver_error(driver_error, &self.quic_connection)
             })?;
 
-        Ok(Connection::new(
+        let connection = Connection::new(
             self.quic_connection,
             self.driver,
             session_id,
-        ))
+            self.receive_window,
+        );
+
+        if let Some(lifetime) = self.max_connection_lifetime {
+            connection.close_at(connection.established_at() + lifetime);
+        }
+
+        if let Some(callback) = &self.connection_callback {
+            callback(&connection);
+        }
+
+        Ok(connection)
     }
 
     /// Rejects the client request by replying with `403` status code.
@@ -712,3 +1995,847 @@ impl SessionRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IpBindConfig;
+    #[cfg(feature = "self-signed")]
+    use crate::test_util::config_pair_with;
+    #[cfg(feature = "self-signed")]
+    use crate::test_util::connected_pair_with;
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn close_on() {
+        use tokio::time::timeout;
+
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+
+        let (signal_tx, signal_rx) = tokio::sync::oneshot::channel::<()>();
+        server.close_on(
+            async {
+                let _ = signal_rx.await;
+            },
+            VarInt::from_u32(0),
+            b"shutdown",
+        );
+
+        signal_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let client = Endpoint::client(client_config).unwrap();
+
+        // Closed endpoint does not accept incoming connections.
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+        assert!(!matches!(
+            timeout(Duration::from_millis(500), client.connect(url)).await,
+            Ok(Ok(_))
+        ));
+
+        timeout(Duration::from_secs(5), server.wait_idle())
+            .await
+            .unwrap();
+        assert!(timeout(Duration::from_millis(50), server.accept())
+            .await
+            .is_err());
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn connect_ip_san() {
+        let certificate = crate::Certificate::self_signed(["127.0.0.1"]);
+
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store
+            .add(&rustls::Certificate(certificate.certificates[0].clone()))
+            .unwrap();
+
+        let server = Endpoint::server(
+            ServerConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4, 0)
+                .with_certificate(certificate)
+                .build(),
+        )
+        .unwrap();
+
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_root_certificates(root_store)
+                .strict_hostname_verification(true)
+                .build(),
+        )
+        .unwrap();
+
+        let url = format!("https://127.0.0.1:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn hostname_mismatch() {
+        let (server_config, client_config) = config_pair_with(
+            |server| server,
+            |client| client.strict_hostname_verification(true),
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://127.0.0.1:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, _) = tokio::join!(client.connect(url), async {
+            let _ = server.accept().await.await;
+        });
+
+        match client_connection {
+            Err(ConnectingError::HostnameMismatch { expected, found }) => {
+                assert_eq!(expected, "127.0.0.1");
+
+                #[cfg(feature = "dangerous-configuration")]
+                assert_eq!(found, ["localhost"]);
+
+                #[cfg(not(feature = "dangerous-configuration"))]
+                assert!(found.is_empty());
+            }
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[cfg(all(feature = "self-signed", feature = "dangerous-configuration"))]
+    #[tokio::test]
+    async fn hostname_verifier() {
+        let certificate = crate::Certificate::self_signed(["localhost"]);
+
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store
+            .add(&rustls::Certificate(certificate.certificates[0].clone()))
+            .unwrap();
+
+        let server = Endpoint::server(
+            ServerConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4, 0)
+                .with_certificate(certificate)
+                .build(),
+        )
+        .unwrap();
+
+        let port = server.local_addr().unwrap().port();
+
+        // Accepts a name the certificate was not issued for
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_hostname_verifier(root_store.clone(), |server_name, _| {
+                    matches!(server_name, rustls::ServerName::IpAddress(ip) if ip.is_loopback())
+                })
+                .build(),
+        )
+        .unwrap();
+
+        let (client_connection, server_connection) =
+            tokio::join!(client.connect(format!("https://127.0.0.1:{port}")), async {
+                server.accept().await.await.unwrap().accept().await
+            });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+
+        // Rejects a name the certificate was issued for
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_hostname_verifier(root_store, |_, _| false)
+                .build(),
+        )
+        .unwrap();
+
+        let (client_connection, _) =
+            tokio::join!(client.connect(format!("https://localhost:{port}")), async {
+                server.accept().await.await
+            });
+
+        assert!(client_connection.is_err());
+    }
+
+    #[cfg(all(feature = "self-signed", feature = "dangerous-configuration"))]
+    #[tokio::test]
+    async fn pinned_certificate() {
+        let certificate = crate::Certificate::self_signed(["localhost"]);
+        let pinned = rustls::Certificate(certificate.certificates[0].clone());
+        let other = rustls::Certificate(
+            crate::Certificate::self_signed(["localhost"]).certificates[0].clone(),
+        );
+
+        let server = Endpoint::server(
+            ServerConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4, 0)
+                .with_certificate(certificate)
+                .build(),
+        )
+        .unwrap();
+
+        let port = server.local_addr().unwrap().port();
+
+        // The server name is not checked against the pinned certificate
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_pinned_certificate(pinned)
+                .build(),
+        )
+        .unwrap();
+
+        let (client_connection, server_connection) =
+            tokio::join!(client.connect(format!("https://127.0.0.1:{port}")), async {
+                server.accept().await.await.unwrap().accept().await
+            });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_pinned_certificate(other)
+                .build(),
+        )
+        .unwrap();
+
+        let (client_connection, _) =
+            tokio::join!(client.connect(format!("https://localhost:{port}")), async {
+                server.accept().await.await
+            });
+
+        assert!(client_connection.is_err());
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn probe() {
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+        let probe = client.probe(&url, Duration::from_secs(5)).await;
+        assert!(probe.quic_reachable);
+        assert!(probe.handshake_completed);
+        assert!(probe.rtt.is_some());
+        assert!(probe.error.is_none());
+
+        // A socket never answering
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://localhost:{}", silent.local_addr().unwrap().port());
+        let probe = client.probe(&url, Duration::from_millis(200)).await;
+        assert!(!probe.quic_reachable);
+        assert!(!probe.handshake_completed);
+        assert!(matches!(
+            probe.error,
+            Some(ConnectingError::ConnectionError(
+                ConnectionError::HandshakeTimedOut
+            ))
+        ));
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn request_header_limits() {
+        let (server_config, client_config) = config_pair_with(
+            |server| {
+                server
+                    .max_request_headers(16)
+                    .max_request_header_bytes(1024)
+            },
+            |client| client,
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let too_many = (0..16).fold(ConnectOptions::builder(&url), |builder, index| {
+            builder.add_header(format!("x-header-{index}"), "value")
+        });
+        let too_large = ConnectOptions::builder(&url).add_header("x-header", "a".repeat(1024));
+        let within_limits = ConnectOptions::builder(&url).add_header("x-header", "value");
+
+        for options in [too_many, too_large] {
+            let (client_connection, server_connection) =
+                tokio::join!(client.connect(options), async {
+                    server.accept().await.await
+                });
+
+            assert!(matches!(
+                client_connection,
+                Err(ConnectingError::HeadersTooLarge)
+            ));
+            assert!(matches!(
+                server_connection,
+                Err(ConnectionError::HeadersTooLarge)
+            ));
+        }
+
+        let (client_connection, server_connection) =
+            tokio::join!(client.connect(within_limits), async {
+                server.accept().await.await.unwrap().accept().await
+            });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn allowed_origins() {
+        let (server_config, client_config) = config_pair_with(
+            |server| server.allowed_origins(["https://example.com"]),
+            |client| client,
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let missing = ConnectOptions::builder(&url);
+        let forbidden = ConnectOptions::builder(&url).add_header("origin", "https://evil.example");
+        let allowed = ConnectOptions::builder(&url).add_header("origin", "https://EXAMPLE.com");
+
+        for options in [missing, forbidden] {
+            let (client_connection, server_connection) =
+                tokio::join!(client.connect(options), async {
+                    server.accept().await.await
+                });
+
+            assert!(matches!(
+                client_connection,
+                Err(ConnectingError::SessionRejected)
+            ));
+            assert!(matches!(
+                server_connection,
+                Err(ConnectionError::OriginNotAllowed)
+            ));
+        }
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(allowed), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn max_concurrent_connections() {
+        let (server_config, client_config) = config_pair_with(
+            |server| server.max_concurrent_connections(1),
+            |client| client,
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) =
+            tokio::join!(client.connect(url.as_str()), async {
+                server.accept().await.await.unwrap().accept().await
+            });
+
+        let _client_connection = client_connection.unwrap();
+        let _server_connection = server_connection.unwrap();
+
+        // Refused by the QUIC stack: it never reaches `accept`
+        assert!(matches!(
+            client.connect(url).await,
+            Err(ConnectingError::ConnectionError(
+                ConnectionError::ConnectionClosed(_)
+            ))
+        ));
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn max_connection_lifetime() {
+        use crate::connection::LIFETIME_EXCEEDED_ERROR_CODE;
+        use tokio::time::timeout;
+
+        let (_server, server_connection, client_connection) = connected_pair_with(
+            |server| server.max_connection_lifetime(Some(Duration::from_millis(200))),
+            |client| client,
+        )
+        .await;
+
+        let error = timeout(Duration::from_secs(5), client_connection.accept_uni())
+            .await
+            .expect("Connection closed by the server")
+            .unwrap_err();
+
+        let ConnectionError::ApplicationClosed(close) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(close.code(), LIFETIME_EXCEEDED_ERROR_CODE);
+        assert!(server_connection.uptime() >= Duration::from_millis(200));
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn on_connection() {
+        use std::sync::atomic::AtomicUsize;
+
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+
+        let sessions = Arc::new(AtomicUsize::new(0));
+        server.on_connection({
+            let sessions = sessions.clone();
+            move |connection| {
+                assert!(connection.remote_address().ip().is_loopback());
+                sessions.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+            let request = server.accept().await.await.unwrap();
+            assert_eq!(sessions.load(Ordering::Relaxed), 0);
+            request.accept().await
+        });
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+        assert_eq!(sessions.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn bind_port_range() {
+        let occupied = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = occupied.local_addr().unwrap().port();
+
+        let config = || {
+            ClientConfig::builder()
+                .with_bind_port_range(IpBindConfig::LocalV4, port..=port)
+                .with_root_certificates(rustls::RootCertStore::empty())
+                .build()
+        };
+
+        let error = Endpoint::client(config()).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+
+        drop(occupied);
+
+        let client = Endpoint::client(config()).unwrap();
+        assert_eq!(client.local_addr().unwrap().port(), port);
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn session_setup_timeout() {
+        let (server_config, client_config) = config_pair_with(
+            |server| server.session_setup_timeout(Some(Duration::from_millis(200))),
+            |client| client.session_setup_timeout(Some(Duration::from_millis(200))),
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let server_address = server.local_addr().unwrap();
+
+        // The client completes the QUIC handshake, but never sends its session request.
+        let (quic_connection, server_connection) = tokio::join!(
+            async {
+                client
+                    .endpoint
+                    .connect(server_address, "localhost")
+                    .unwrap()
+                    .await
+            },
+            async { server.accept().await.await }
+        );
+
+        let _quic_connection = quic_connection.unwrap();
+        assert!(matches!(
+            server_connection,
+            Err(ConnectionError::SessionSetupTimedOut)
+        ));
+
+        // The server completes the QUIC handshake, but never answers the session request.
+        let url = format!("https://localhost:{}", server_address.port());
+        let (client_connection, quic_connection) = tokio::join!(client.connect(url), async {
+            server.endpoint.accept().await.unwrap().await
+        });
+
+        let _quic_connection = quic_connection.unwrap();
+        assert!(matches!(
+            client_connection,
+            Err(ConnectingError::ConnectionError(
+                ConnectionError::SessionSetupTimedOut
+            ))
+        ));
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn connect_cancellation() {
+        use tokio::time::timeout;
+
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        // Cancelled during the QUIC handshake (the peer never answers)
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://localhost:{}", silent.local_addr().unwrap().port());
+        assert!(timeout(Duration::from_millis(100), client.connect(url))
+            .await
+            .is_err());
+
+        timeout(Duration::from_secs(10), client.endpoint.wait_idle())
+            .await
+            .unwrap();
+
+        // Cancelled while waiting for the session response
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+        let (connect, session_request) = tokio::join!(
+            timeout(Duration::from_millis(500), client.connect(url)),
+            async { server.accept().await.await.unwrap() }
+        );
+        assert!(connect.is_err());
+
+        timeout(Duration::from_secs(10), client.endpoint.wait_idle())
+            .await
+            .unwrap();
+
+        drop(session_request);
+    }
+
+    #[cfg(all(feature = "self-signed", feature = "quinn"))]
+    #[tokio::test]
+    async fn set_default_transport_config() {
+        use tokio::time::timeout;
+
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        // Only the session stream is allowed
+        let mut transport_config = quinn::TransportConfig::default();
+        transport_config.max_concurrent_bidi_streams(quinn::VarInt::from_u32(1));
+        server.set_default_transport_config(transport_config);
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+        let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+        let client_connection = client_connection.unwrap();
+        let _server_connection = server_connection.unwrap();
+
+        let opening = async { client_connection.open_bi().await.unwrap().await };
+        assert!(timeout(Duration::from_millis(200), opening).await.is_err());
+        assert!(client_connection.open_uni().await.unwrap().await.is_ok());
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn set_max_concurrent_bidi_streams() {
+        use tokio::time::timeout;
+
+        let (_server, server_connection, client_connection) =
+            connected_pair_with(|server| server.initial_max_bidi_streams(1), |client| client).await;
+
+        let _bi_stream = client_connection.open_bi().await.unwrap().await.unwrap();
+
+        let opening_bi = async { client_connection.open_bi().await.unwrap().await };
+        assert!(timeout(Duration::from_millis(200), opening_bi)
+            .await
+            .is_err());
+
+        server_connection.set_max_concurrent_bidi_streams(2);
+
+        // The credit is sent once a packet is received from the peer
+        client_connection.send_datagram(b"hello").unwrap();
+
+        let opening_bi = async { client_connection.open_bi().await.unwrap().await };
+        assert!(timeout(Duration::from_secs(1), opening_bi)
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn close() {
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        // The endpoint outlives its connections
+        for _ in 0..2 {
+            let (client_connection, server_connection) =
+                tokio::join!(client.connect(&url), async {
+                    server.accept().await.await.unwrap().accept().await
+                });
+
+            client_connection
+                .unwrap()
+                .close(VarInt::from_u32(0), b"done");
+            server_connection.unwrap().closed().await;
+        }
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(&url), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+        let client_connection = client_connection.unwrap();
+        let _server_connection = server_connection.unwrap();
+
+        assert!(client.is_open());
+        client.close(VarInt::from_u32(0), b"shutdown");
+        assert!(!client.is_open());
+
+        assert!(matches!(
+            client_connection.accept_uni().await,
+            Err(ConnectionError::LocallyClosed)
+        ));
+        assert!(matches!(
+            client.connect(&url).await,
+            Err(ConnectingError::EndpointClosed)
+        ));
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn connect_race() {
+        let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) = tokio::join!(
+            client.connect_race(["http://localhost".to_string(), url]),
+            async { server.accept().await.await.unwrap().accept().await }
+        );
+
+        client_connection.unwrap();
+        server_connection.unwrap();
+
+        let errors = client
+            .connect_race(["http://localhost", "https://"])
+            .await
+            .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, ConnectingError::InvalidUrl(_))));
+
+        assert!(client
+            .connect_race(Vec::<String>::new())
+            .await
+            .unwrap_err()
+            .is_empty());
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn migration_policy() {
+        use crate::connection::MIGRATION_DENIED_ERROR_CODE;
+        use std::sync::atomic::AtomicUsize;
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let (server_config, client_config) = config_pair_with(
+            |server| {
+                server.migration_policy({
+                    let attempts = attempts.clone();
+                    move |old, new| {
+                        assert_ne!(old, new);
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        false
+                    }
+                })
+            },
+            |client| client,
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+
+        let client_connection = client_connection.unwrap();
+        let _server_connection = server_connection.unwrap();
+
+        client
+            .endpoint
+            .rebind(std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+            .unwrap();
+        client_connection.send_datagram(b"payload").unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), client_connection.accept_uni())
+            .await
+            .expect("Connection closed by the server")
+            .unwrap_err();
+
+        let ConnectionError::ApplicationClosed(close) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(close.code(), MIGRATION_DENIED_ERROR_CODE);
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[tokio::test]
+    async fn migration_rate_limit() {
+        use crate::connection::MIGRATION_DENIED_ERROR_CODE;
+
+        let (server_config, client_config) = config_pair_with(
+            |server| server.migration_rate_limit(1, Duration::from_secs(60)),
+            |client| client,
+        );
+        let server = Endpoint::server(server_config).unwrap();
+        let client = Endpoint::client(client_config).unwrap();
+
+        let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+        let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+            server.accept().await.await.unwrap().accept().await
+        });
+
+        let client_connection = client_connection.unwrap();
+        let server_connection = server_connection.unwrap();
+
+        // First migration is within the limit.
+        client
+            .endpoint
+            .rebind(std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+            .unwrap();
+        client_connection.send_datagram(b"first").unwrap();
+        assert_eq!(
+            &*server_connection.receive_datagram().await.unwrap(),
+            b"first"
+        );
+
+        client
+            .endpoint
+            .rebind(std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+            .unwrap();
+        client_connection.send_datagram(b"second").unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), client_connection.accept_uni())
+            .await
+            .expect("Connection closed by the server")
+            .unwrap_err();
+
+        let ConnectionError::ApplicationClosed(close) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(close.code(), MIGRATION_DENIED_ERROR_CODE);
+    }
+
+    #[test]
+    fn pseudo_headers() {
+        assert!(is_valid_authority("example.com"));
+        assert!(is_valid_authority("example.com:4433"));
+        assert!(is_valid_authority("[::1]:4433"));
+        assert!(!is_valid_authority(""));
+        assert!(!is_valid_authority("user@example.com"));
+        assert!(!is_valid_authority("example.com/path"));
+        assert!(!is_valid_authority("example .com"));
+
+        assert!(is_valid_scheme("https"));
+        assert!(is_valid_scheme("coap+tcp"));
+        assert!(!is_valid_scheme(""));
+        assert!(!is_valid_scheme("1https"));
+        assert!(!is_valid_scheme("https:"));
+    }
+
+    #[test]
+    fn into_options() {
+        let expected = "https://example.com:4433/webtransport";
+
+        assert_eq!(expected.into_options().url, expected);
+        assert_eq!(Url::parse(expected).unwrap().into_options().url, expected);
+        assert_eq!(
+            expected.parse::<hyper::Uri>().unwrap().into_options().url,
+            expected
+        );
+    }
+
+    #[test]
+    fn validate() {
+        for url in [
+            "https://example.com",
+            "https://example.com:4433/webtransport?query=1",
+            "https://127.0.0.1:4433",
+            "https://[::1]:4433/",
+        ] {
+            assert!(validate_url(url).is_ok(), "{url}");
+        }
+
+        for url in [
+            "http://example.com",
+            "ws://example.com",
+            "wss://example.com",
+            "example.com:4433",
+            "https://",
+            "https://example.com/webtransport#fragment",
+        ] {
+            assert!(
+                matches!(validate_url(url), Err(ConnectingError::InvalidUrl(_))),
+                "{url}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn connect_invalid_scheme() {
+        let client = Endpoint::client(
+            ClientConfig::builder()
+                .with_bind_config(IpBindConfig::LocalV4)
+                .with_custom_tls(
+                    rustls::ClientConfig::builder()
+                        .with_safe_defaults()
+                        .with_root_certificates(rustls::RootCertStore::empty())
+                        .with_no_client_auth(),
+                )
+                .build(),
+        )
+        .unwrap();
+
+        for url in [
+            "http://localhost:4433",
+            "wss://localhost:4433",
+            "localhost:4433",
+        ] {
+            assert!(matches!(
+                client.connect(url).await,
+                Err(ConnectingError::InvalidUrl(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        let mapped: SocketAddr = "[::ffff:1.2.3.4]:443".parse().unwrap();
+        assert_eq!(normalize_addr(mapped), "1.2.3.4:443".parse().unwrap());
+
+        for address in ["1.2.3.4:443", "[::1]:443", "[2001:db8::1]:443"] {
+            let address: SocketAddr = address.parse().unwrap();
+            assert_eq!(normalize_addr(address), address);
+        }
+    }
+}
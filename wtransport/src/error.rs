@@ -27,6 +27,25 @@ pub enum ConnectionError {
     #[error("connection timed out")]
     TimedOut,
 
+    /// The QUIC/TLS handshake did not complete within the configured handshake timeout.
+    #[error("connection handshake timed out")]
+    HandshakeTimedOut,
+
+    /// The WebTransport session was not established within the configured session setup
+    /// timeout, after the QUIC handshake.
+    #[error("session setup timed out")]
+    SessionSetupTimedOut,
+
+    /// The peer sent headers exceeding the configured maximum field section size, or the
+    /// configured limits on session request headers.
+    #[error("peer headers exceed the configured limits")]
+    HeadersTooLarge,
+
+    /// The session request was rejected, as its origin is not allowed (see
+    /// [`ServerConfigBuilder::allowed_origins`](crate::config::ServerConfigBuilder::allowed_origins)).
+    #[error("session request origin not allowed")]
+    OriginNotAllowed,
+
     /// The connection was closed because a QUIC protocol error.
     #[error("QUIC protocol error: {0}")]
     QuicProto(QuicProtoError),
@@ -50,6 +69,37 @@ impl ConnectionError {
             .into()
     }
 
+    /// Returns the TLS alert associated with the error, if the connection failed because
+    /// of a TLS error (either local or reported by the peer).
+    pub(crate) fn tls_alert(&self) -> Option<u8> {
+        let code = match self {
+            ConnectionError::ConnectionClosed(close) => u64::from(close.0.error_code),
+            ConnectionError::QuicProto(error) => error.code?.into_inner(),
+            _ => return None,
+        };
+
+        // QUIC CRYPTO_ERROR codes (RFC 9000, Section 20.1)
+        (0x0100..=0x01ff)
+            .contains(&code)
+            .then_some((code - 0x0100) as u8)
+    }
+
+    /// Returns whether the TLS handshake failed because the server certificate is not valid
+    /// for the server name.
+    ///
+    /// The TLS error is only reported with its alert and description: the latter is compared
+    /// with the one of [`rustls::CertificateError::NotValidForName`].
+    pub(crate) fn is_hostname_mismatch(&self) -> bool {
+        let ConnectionError::QuicProto(error) = self else {
+            return false;
+        };
+
+        self.tls_alert() == Some(rustls::AlertDescription::BadCertificate.get_u8())
+            && error.reason
+                == rustls::Error::InvalidCertificate(rustls::CertificateError::NotValidForName)
+                    .to_string()
+    }
+
     pub(crate) fn local_h3_error(error_code: ErrorCode) -> Self {
         ConnectionError::LocalH3Error(H3Error { code: error_code })
     }
@@ -63,8 +113,8 @@ pub enum ConnectingError {
     InvalidUrl(String),
 
     /// Failure during DNS resolution.
-    #[error("cannot resolve domain: {0}")]
-    DnsLookup(std::io::Error),
+    #[error("DNS lookup failed")]
+    DnsLookup(#[source] std::io::Error),
 
     /// Cannot find any DNS.
     #[error("cannot resolve domain")]
@@ -78,9 +128,49 @@ pub enum ConnectingError {
     #[error("server rejected WebTransport session request")]
     SessionRejected,
 
+    /// The endpoint has been closed (see [`Endpoint::close`](crate::Endpoint::close)).
+    #[error("endpoint closed")]
+    EndpointClosed,
+
     /// Cannot use reserved key for additional headers.
     #[error("additional header '{0}' is reserved")]
     ReservedHeader(String),
+
+    /// The value overriding a pseudo-header (`:authority` or `:scheme`) is not valid.
+    #[error("invalid value for pseudo-header '{name}': '{value}'")]
+    InvalidPseudoHeader {
+        /// The name of the pseudo-header.
+        name: &'static str,
+
+        /// The rejected value.
+        value: String,
+    },
+
+    /// Request or response headers exceed the maximum field section size.
+    ///
+    /// Either the request exceeds the limit advertised by the server, or the response
+    /// exceeds the limit configured on the client.
+    #[error("headers exceed the maximum field section size")]
+    HeadersTooLarge,
+
+    /// The server certificate is not valid for the host of the connect URL.
+    ///
+    /// Only returned when
+    /// [`strict_hostname_verification`](crate::config::ClientConfigBuilder::strict_hostname_verification)
+    /// is enabled.
+    #[error("server certificate is not valid for '{expected}' (found: {found:?})")]
+    HostnameMismatch {
+        /// The host of the connect URL.
+        expected: String,
+
+        /// The names listed in the server certificate.
+        ///
+        /// Only recorded with the `dangerous-configuration` feature, and with a verifier
+        /// configured from root certificates (e.g.,
+        /// [`with_native_certs`](crate::config::ClientConfigBuilder::with_native_certs)).
+        /// Empty otherwise.
+        found: Vec<String>,
+    },
 }
 
 impl ConnectingError {
@@ -152,8 +242,47 @@ pub enum SendDatagramError {
     /// The datagram is larger than the connection can currently accommodate.
     #[error("datagram payload too large")]
     TooLarge,
+
+    /// The datagram has been discarded as the send buffer is too full for its priority.
+    ///
+    /// See [`Connection::send_datagram_with_priority`](crate::Connection::send_datagram_with_priority).
+    #[error("datagram discarded as send buffer is full")]
+    BufferFull,
+}
+
+/// An error that arise from receiving a datagram into a caller-provided buffer.
+///
+/// See [`Connection::receive_datagram_into`](crate::Connection::receive_datagram_into).
+#[derive(thiserror::Error, Debug)]
+pub enum ReceiveDatagramError {
+    /// The connection failed.
+    #[error(transparent)]
+    Connection(ConnectionError),
+
+    /// The buffer is too small to hold the next datagram.
+    ///
+    /// The datagram is not consumed: it can be received again with a large enough buffer.
+    #[error("buffer too small for datagram ({needed} bytes needed)")]
+    BufferTooSmall {
+        /// The size of the datagram payload.
+        needed: usize,
+    },
 }
 
+/// An error that arise when exporting keying material.
+///
+/// See [`Connection::export_keying_material`](crate::Connection::export_keying_material).
+#[derive(thiserror::Error, Debug)]
+#[error("keying material cannot be exported: output too large")]
+pub struct ExportKeyingMaterialError;
+
+/// An error that arises when pausing incoming data.
+///
+/// See [`Connection::pause_incoming`](crate::Connection::pause_incoming).
+#[derive(thiserror::Error, Debug)]
+#[error("incoming data cannot be paused: receive window is unlimited")]
+pub struct PauseIncomingError;
+
 /// An error that arise when opening a new stream.
 #[derive(thiserror::Error, Debug)]
 pub enum StreamOpeningError {
@@ -166,6 +295,67 @@ pub enum StreamOpeningError {
     Refused,
 }
 
+/// An error that arise from sending a message (see [`message`](crate::message) module).
+#[cfg(feature = "message-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "message-stream")))]
+#[derive(thiserror::Error, Debug)]
+pub enum MessageSendError {
+    /// The message exceeds the maximum message size.
+    #[error("message too large")]
+    TooLarge,
+
+    /// A write error occurred.
+    #[error(transparent)]
+    Write(StreamWriteError),
+
+    /// The connection failed while opening the message stream.
+    #[error(transparent)]
+    Connection(ConnectionError),
+
+    /// The message stream could not be opened.
+    #[error(transparent)]
+    Opening(StreamOpeningError),
+}
+
+/// An error that arise from receiving a message (see [`message`](crate::message) module).
+#[cfg(feature = "message-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "message-stream")))]
+#[derive(thiserror::Error, Debug)]
+pub enum MessageRecvError {
+    /// The message exceeds the maximum message size.
+    ///
+    /// Contains the length announced by the peer, or the number of bytes received so far
+    /// if the length is not known in advance.
+    #[error("message too large ({0} bytes)")]
+    TooLarge(usize),
+
+    /// The stream finished in the middle of a message.
+    #[error("stream finished too early")]
+    FinishedEarly,
+
+    /// A read error occurred.
+    #[error(transparent)]
+    Read(StreamReadError),
+
+    /// The connection failed while accepting the message stream.
+    #[error(transparent)]
+    Connection(ConnectionError),
+}
+
+/// An error that arise from a request (see [`Rpc`](crate::message::Rpc)).
+#[cfg(feature = "message-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "message-stream")))]
+#[derive(thiserror::Error, Debug)]
+pub enum RpcError {
+    /// The request could not be sent.
+    #[error(transparent)]
+    Send(MessageSendError),
+
+    /// The response could not be received.
+    #[error(transparent)]
+    Recv(MessageRecvError),
+}
+
 /// Reason given by an application for closing the connection
 #[derive(Debug)]
 pub struct ApplicationClose {
@@ -173,6 +363,29 @@ pub struct ApplicationClose {
     reason: Box<[u8]>,
 }
 
+impl ApplicationClose {
+    /// Application-specific error code.
+    ///
+    /// For instance, [`LIFETIME_EXCEEDED_ERROR_CODE`](crate::connection::LIFETIME_EXCEEDED_ERROR_CODE)
+    /// when the server closed the connection because of its maximum lifetime.
+    pub fn code(&self) -> VarInt {
+        self.code
+    }
+
+    /// Human-readable reason for the close.
+    pub fn reason(&self) -> &[u8] {
+        &self.reason
+    }
+
+    /// The HTTP3 (or QPACK, WebTransport) error matching [`code`](Self::code), if any.
+    ///
+    /// It is set when the peer closed the connection because of a protocol violation.
+    /// Applications using codes of the same range for their own purposes can ignore it.
+    pub fn h3_error(&self) -> Option<ErrorCode> {
+        ErrorCode::from_code(self.code)
+    }
+}
+
 impl Display for ApplicationClose {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.reason.is_empty() {
@@ -203,6 +416,15 @@ pub struct H3Error {
     code: ErrorCode,
 }
 
+impl H3Error {
+    /// The HTTP3 error.
+    ///
+    /// Its numeric value is given by [`ErrorCode::to_code`].
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
 impl Display for H3Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.code.fmt(f)
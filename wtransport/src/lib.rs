@@ -109,6 +109,21 @@ pub mod tls;
 /// Datagrams module.
 pub mod datagram;
 
+/// Compression of stream data.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub mod compression;
+
+/// Message-oriented helpers on top of streams.
+#[cfg(feature = "message-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "message-stream")))]
+pub mod message;
+
+/// Helpers for testing applications built on top of this crate.
+#[cfg(any(feature = "test-util", all(test, feature = "self-signed")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod test_util;
+
 #[doc(inline)]
 pub use config::ClientConfig;
 
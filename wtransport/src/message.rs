@@ -0,0 +1,1071 @@
+use crate::error::ConnectionError;
+use crate::error::MessageRecvError;
+use crate::error::MessageSendError;
+use crate::error::RpcError;
+use crate::error::SendDatagramError;
+use crate::error::StreamReadExactError;
+use crate::error::StreamWriteError;
+use crate::Connection;
+use crate::RecvStream;
+use crate::SendStream;
+use bytes::Bytes;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+use tracing::debug;
+use wtransport_proto::bytes::BufferReader;
+use wtransport_proto::bytes::BufferWriter;
+use wtransport_proto::bytes::BytesReader;
+use wtransport_proto::bytes::BytesWriter;
+use wtransport_proto::varint::VarInt;
+
+/// Size (in bytes) of the length prefix of each message.
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Default maximum message size (64 KiB) of [`MessageStream`], [`ReliableMessenger`] and
+/// [`Rpc`] (requests and responses).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// A bidirectional stream exchanging length-prefixed messages.
+///
+/// Each message is framed as a 32-bit big-endian length, followed by the message payload.
+///
+/// Received messages whose advertised length exceeds the configured
+/// [maximum size](Self::with_max_message_size) are rejected *before* any buffer is
+/// allocated for them.
+///
+/// # Examples
+/// ```no_run
+/// # use anyhow::Result;
+/// # use wtransport::Connection;
+/// use wtransport::message::MessageStream;
+///
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let (send_stream, recv_stream) = connection.open_bi().await?.await?;
+/// let mut stream = MessageStream::new(send_stream, recv_stream);
+///
+/// stream.send_message(b"ping").await?;
+/// let reply = stream.recv_message().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MessageStream {
+    send_stream: SendStream,
+    recv_stream: RecvStream,
+    max_message_size: usize,
+}
+
+impl MessageStream {
+    /// Wraps a bidirectional stream.
+    ///
+    /// The maximum message size is [`DEFAULT_MAX_MESSAGE_SIZE`].
+    pub fn new(send_stream: SendStream, recv_stream: RecvStream) -> Self {
+        Self {
+            send_stream,
+            recv_stream,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Sets the maximum size (in bytes) of a message payload.
+    ///
+    /// The limit applies to both sent and received messages. It is capped to [`u32::MAX`].
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size.min(u32::MAX as usize);
+        self
+    }
+
+    /// Returns the maximum size (in bytes) of a message payload.
+    #[inline(always)]
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Sends a single message.
+    ///
+    /// Fails with [`MessageSendError::TooLarge`] if the message exceeds
+    /// the [maximum size](Self::max_message_size). In that case, nothing is written.
+    pub async fn send_message(&mut self, message: &[u8]) -> Result<(), MessageSendError> {
+        if message.len() > self.max_message_size {
+            return Err(MessageSendError::TooLarge);
+        }
+
+        let length = message.len() as u32;
+
+        self.send_stream
+            .write_all(&length.to_be_bytes())
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        self.send_stream
+            .write_all(message)
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        Ok(())
+    }
+
+    /// Receives a single message.
+    ///
+    /// Yields `None` if the peer finished the stream at a message boundary.
+    /// If the stream finishes in the middle of a message, it returns
+    /// [`MessageRecvError::FinishedEarly`].
+    pub async fn recv_message(&mut self) -> Result<Option<Vec<u8>>, MessageRecvError> {
+        let mut length = [0; LENGTH_PREFIX_SIZE];
+        let mut offset = 0;
+
+        while offset < LENGTH_PREFIX_SIZE {
+            match self
+                .recv_stream
+                .read(&mut length[offset..])
+                .await
+                .map_err(MessageRecvError::Read)?
+            {
+                Some(read) => offset += read,
+                None if offset == 0 => return Ok(None),
+                None => return Err(MessageRecvError::FinishedEarly),
+            }
+        }
+
+        let length = u32::from_be_bytes(length) as usize;
+
+        if length > self.max_message_size {
+            return Err(MessageRecvError::TooLarge(length));
+        }
+
+        let mut message = vec![0; length];
+
+        self.recv_stream
+            .read_exact(&mut message)
+            .await
+            .map_err(|error| match error {
+                StreamReadExactError::FinishedEarly => MessageRecvError::FinishedEarly,
+                StreamReadExactError::Read(error) => MessageRecvError::Read(error),
+            })?;
+
+        Ok(Some(message))
+    }
+
+    /// Finishes the sending side of the stream.
+    ///
+    /// See [`SendStream::finish`].
+    #[inline(always)]
+    pub async fn finish(&mut self) -> Result<(), StreamWriteError> {
+        self.send_stream.finish().await
+    }
+
+    /// Returns a reference to the underlying [`SendStream`].
+    #[inline(always)]
+    pub fn send_stream(&self) -> &SendStream {
+        &self.send_stream
+    }
+
+    /// Returns a reference to the underlying [`RecvStream`].
+    #[inline(always)]
+    pub fn recv_stream(&self) -> &RecvStream {
+        &self.recv_stream
+    }
+
+    /// Returns the underlying streams.
+    ///
+    /// **Note**: any data of a partially received message is lost.
+    #[inline(always)]
+    pub fn into_inner(self) -> (SendStream, RecvStream) {
+        (self.send_stream, self.recv_stream)
+    }
+}
+
+/// Reliable, ordered messages exchanged on a [`Connection`], one unidirectional stream
+/// per message.
+///
+/// Unlike datagrams, messages are delivered reliably; unlike a single stream, each message
+/// is independent: a lost packet only delays the message it belongs to. Message boundaries
+/// are given by the end of the stream, so no framing is added.
+///
+/// Note that messages might be received in a different order than the one they were sent,
+/// as they travel on different streams.
+///
+/// The messenger must be used on both sides of the connection, and it takes over
+/// unidirectional streams: [`Connection::accept_uni`] should not be used concurrently with
+/// [`recv`](Self::recv).
+///
+/// # Examples
+/// ```no_run
+/// # use anyhow::Result;
+/// # use wtransport::Connection;
+/// use wtransport::message::ReliableMessenger;
+///
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let messenger = ReliableMessenger::new(&connection);
+///
+/// messenger.send(b"Hello, wtransport!").await?;
+/// let message = messenger.recv().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReliableMessenger<'a> {
+    connection: &'a Connection,
+    max_message_size: usize,
+    send_permits: Semaphore,
+}
+
+impl<'a> ReliableMessenger<'a> {
+    /// Default maximum number of messages being sent concurrently.
+    pub const DEFAULT_MAX_CONCURRENT_SENDS: usize = 16;
+
+    /// Creates a messenger on `connection`.
+    ///
+    /// The maximum message size is [`DEFAULT_MAX_MESSAGE_SIZE`],
+    /// the concurrency limit is [`DEFAULT_MAX_CONCURRENT_SENDS`](Self::DEFAULT_MAX_CONCURRENT_SENDS).
+    pub fn new(connection: &'a Connection) -> Self {
+        Self {
+            connection,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            send_permits: Semaphore::new(Self::DEFAULT_MAX_CONCURRENT_SENDS),
+        }
+    }
+
+    /// Sets the maximum size (in bytes) of a message.
+    ///
+    /// The limit applies to both sent and received messages.
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Sets the maximum number of messages being sent concurrently.
+    ///
+    /// Once the limit is reached, [`send`](Self::send) waits for a previous message to be
+    /// acknowledged by the peer. The value is clamped to at least `1`.
+    pub fn with_max_concurrent_sends(mut self, max_concurrent_sends: usize) -> Self {
+        self.send_permits = Semaphore::new(max_concurrent_sends.clamp(1, Semaphore::MAX_PERMITS));
+        self
+    }
+
+    /// Returns the maximum size (in bytes) of a message.
+    #[inline(always)]
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Sends a single message on a new unidirectional stream.
+    ///
+    /// Completes when the peer has acknowledged the whole message.
+    ///
+    /// Fails with [`MessageSendError::TooLarge`] if the message exceeds
+    /// the [maximum size](Self::max_message_size). In that case, no stream is opened.
+    ///
+    /// # Cancel safety
+    ///
+    /// If the future is cancelled (or fails) after the stream has been opened, the stream
+    /// is reset: the peer never receives a partial message as a complete one.
+    pub async fn send(&self, message: &[u8]) -> Result<(), MessageSendError> {
+        if message.len() > self.max_message_size {
+            return Err(MessageSendError::TooLarge);
+        }
+
+        let _permit = self
+            .send_permits
+            .acquire()
+            .await
+            .expect("Semaphore is never closed");
+
+        let send_stream = self
+            .connection
+            .open_uni()
+            .await
+            .map_err(MessageSendError::Connection)?
+            .await
+            .map_err(MessageSendError::Opening)?;
+
+        let mut send_stream = ResetOnDrop::new(send_stream);
+
+        send_stream
+            .get_mut()
+            .write_all(message)
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        send_stream
+            .get_mut()
+            .finish()
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        send_stream.disarm();
+
+        Ok(())
+    }
+
+    /// Receives a single message, reading a whole unidirectional stream.
+    ///
+    /// Messages are read one at a time: a message delivered slowly delays the following ones.
+    ///
+    /// If the message exceeds the [maximum size](Self::max_message_size), the stream is
+    /// stopped and [`MessageRecvError::TooLarge`] is returned. Subsequent messages can still
+    /// be received.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is *not* cancel safe: cancelling it while a message is being read
+    /// discards that message.
+    pub async fn recv(&self) -> Result<Vec<u8>, MessageRecvError> {
+        let recv_stream = self
+            .connection
+            .accept_uni()
+            .await
+            .map_err(MessageRecvError::Connection)?;
+
+        read_to_end(recv_stream, self.max_message_size).await
+    }
+}
+
+/// Request/response exchanges on a [`Connection`], one bidirectional stream per request.
+///
+/// The client [requests](Self::request): it opens a stream, writes the whole request and
+/// finishes the stream, then reads the response until the end of the stream. The server
+/// [accepts](Self::accept_request) the request, and answers through the returned
+/// [`Responder`]. Message boundaries are given by the end of the stream, so no framing is
+/// added, and the pattern interoperates with any peer following it (e.g., a browser).
+///
+/// Requests are independent: many of them can be in flight at the same time, each on its
+/// own stream.
+///
+/// On the server, it takes over bidirectional streams: [`Connection::accept_bi`] should not
+/// be used concurrently with [`accept_request`](Self::accept_request).
+///
+/// # Examples
+/// ```no_run
+/// # use anyhow::Result;
+/// # use wtransport::Connection;
+/// use wtransport::message::Rpc;
+///
+/// # async fn client(connection: Connection) -> Result<()> {
+/// let response = Rpc::new(&connection).request(b"ping").await?;
+/// # Ok(())
+/// # }
+/// # async fn server(connection: Connection) -> Result<()> {
+/// let (request, responder) = Rpc::new(&connection).accept_request().await?;
+/// responder.respond(b"pong").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Rpc<'a> {
+    connection: &'a Connection,
+    max_message_size: usize,
+}
+
+impl<'a> Rpc<'a> {
+    /// Creates a request/response helper on `connection`.
+    ///
+    /// The maximum message size is [`DEFAULT_MAX_MESSAGE_SIZE`].
+    pub fn new(connection: &'a Connection) -> Self {
+        Self {
+            connection,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Sets the maximum size (in bytes) of requests and responses.
+    ///
+    /// The limit applies to both sent and received messages.
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Returns the maximum size (in bytes) of requests and responses.
+    #[inline(always)]
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Sends a request on a new bidirectional stream, and waits for the whole response.
+    ///
+    /// Fails with [`MessageSendError::TooLarge`] if the request exceeds the
+    /// [maximum size](Self::max_message_size) (no stream is opened), and with
+    /// [`MessageRecvError::TooLarge`] if the response does.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is *not* cancel safe: cancelling it abandons the request, and the
+    /// response (if any) is discarded. A request partially sent is reset, so the peer never
+    /// receives it as a complete one.
+    pub async fn request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
+        if request.len() > self.max_message_size {
+            return Err(RpcError::Send(MessageSendError::TooLarge));
+        }
+
+        let (send_stream, recv_stream) = self
+            .connection
+            .open_bi()
+            .await
+            .map_err(|error| RpcError::Send(MessageSendError::Connection(error)))?
+            .await
+            .map_err(|error| RpcError::Send(MessageSendError::Opening(error)))?;
+
+        let mut send_stream = ResetOnDrop::new(send_stream);
+
+        send_stream
+            .get_mut()
+            .write_all(request)
+            .await
+            .map_err(|error| RpcError::Send(MessageSendError::Write(error)))?;
+
+        send_stream
+            .get_mut()
+            .finish()
+            .await
+            .map_err(|error| RpcError::Send(MessageSendError::Write(error)))?;
+
+        send_stream.disarm();
+
+        read_to_end(recv_stream, self.max_message_size)
+            .await
+            .map_err(RpcError::Recv)
+    }
+
+    /// Accepts the next request, reading it whole.
+    ///
+    /// Requests are read one at a time: a request delivered slowly delays the following ones.
+    ///
+    /// If the request exceeds the [maximum size](Self::max_message_size), the stream is
+    /// reset and [`MessageRecvError::TooLarge`] is returned. Subsequent requests can still
+    /// be accepted.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is *not* cancel safe: cancelling it while a request is being read
+    /// discards that request.
+    pub async fn accept_request(&self) -> Result<(Vec<u8>, Responder), MessageRecvError> {
+        let (send_stream, recv_stream) = self
+            .connection
+            .accept_bi()
+            .await
+            .map_err(MessageRecvError::Connection)?;
+
+        let mut responder = Responder {
+            send_stream: Some(send_stream),
+            max_message_size: self.max_message_size,
+        };
+
+        match read_to_end(recv_stream, self.max_message_size).await {
+            Ok(request) => Ok((request, responder)),
+            Err(error) => {
+                responder.reset();
+                Err(error)
+            }
+        }
+    }
+}
+
+/// The handle to answer a request accepted with [`Rpc::accept_request`].
+///
+/// Dropping it without [responding](Self::respond) resets the stream, so the peer is
+/// notified that no response will come.
+#[derive(Debug)]
+pub struct Responder {
+    send_stream: Option<SendStream>,
+    max_message_size: usize,
+}
+
+impl Responder {
+    /// Sends the response, and finishes the stream.
+    ///
+    /// Fails with [`MessageSendError::TooLarge`] if the response exceeds the
+    /// [maximum size](Rpc::max_message_size). In that case, the stream is reset.
+    ///
+    /// # Cancel safety
+    ///
+    /// If the future is cancelled (or fails), the stream is reset: the peer never receives
+    /// a partial response as a complete one.
+    pub async fn respond(mut self, response: &[u8]) -> Result<(), MessageSendError> {
+        if response.len() > self.max_message_size {
+            self.reset();
+            return Err(MessageSendError::TooLarge);
+        }
+
+        let send_stream = self.send_stream.as_mut().expect("Response not sent yet");
+
+        send_stream
+            .write_all(response)
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        send_stream
+            .finish()
+            .await
+            .map_err(MessageSendError::Write)?;
+
+        // Finished: nothing left to reset on drop.
+        self.send_stream = None;
+
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        if let Some(send_stream) = self.send_stream.take() {
+            send_stream.reset(VarInt::from_u32(0));
+        }
+    }
+}
+
+impl Drop for Responder {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+/// Datagrams tagged with a sequence number, for loss and reordering observability.
+///
+/// Each sent datagram is prefixed with a sequence number (a variable-length integer,
+/// from 1 to 8 bytes), increasing by one at each [`send`](Self::send). On the receiving
+/// side, [`receive`](Self::receive) yields the payload along with its sequence number, and
+/// keeps track of the sequence numbers never received (see [`report`](Self::report)).
+///
+/// This is **not** a reliability layer: datagrams are neither retransmitted nor reordered,
+/// they are yielded as soon as they arrive. It only gives the application the information
+/// to build its own policy (e.g., requesting a keyframe after losses, or discarding stale
+/// updates).
+///
+/// Both peers must use the adapter, and all datagrams of the session must go through it:
+/// datagrams without a valid sequence number are discarded. Duplicated datagrams (whose
+/// sequence number was already received) are discarded as well.
+///
+/// # Examples
+/// ```no_run
+/// # use anyhow::Result;
+/// # use wtransport::Connection;
+/// use wtransport::message::SequencedDatagrams;
+///
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let datagrams = SequencedDatagrams::new(&connection);
+///
+/// datagrams.send(b"position update")?;
+///
+/// let datagram = datagrams.receive().await?;
+/// if datagram.gap() > 0 {
+///     println!("{} datagrams lost before #{}", datagram.gap(), datagram.sequence());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SequencedDatagrams<'a> {
+    connection: &'a Connection,
+    next_sequence: AtomicU64,
+    receive_state: Mutex<ReceiveState>,
+}
+
+impl<'a> SequencedDatagrams<'a> {
+    /// Creates the adapter on `connection`.
+    pub fn new(connection: &'a Connection) -> Self {
+        Self {
+            connection,
+            next_sequence: AtomicU64::new(0),
+            receive_state: Mutex::new(ReceiveState::default()),
+        }
+    }
+
+    /// Sends a datagram, returning its sequence number.
+    ///
+    /// The sequence number is consumed even if sending fails, which the peer sees as a loss.
+    pub fn send(&self, payload: &[u8]) -> Result<u64, SendDatagramError> {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        let datagram = encode_sequenced(sequence, payload);
+        self.connection.send_datagram(datagram)?;
+        Ok(sequence)
+    }
+
+    /// Receives the next datagram, along with its sequencing information.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn receive(&self) -> Result<SequencedDatagram, ConnectionError> {
+        loop {
+            let datagram = self.connection.receive_datagram().await?;
+            let payload = datagram.payload();
+
+            let mut reader = BufferReader::new(&payload);
+            let Some(sequence) = reader.get_varint() else {
+                debug!("Discarded datagram without sequence number");
+                self.lock_state().report.malformed += 1;
+                continue;
+            };
+            let sequence = sequence.into_inner();
+            let offset = reader.offset();
+
+            let mut state = self.lock_state();
+
+            let (gap, late) = match state.next_expected {
+                Some(next_expected) if sequence < next_expected => {
+                    if !state.take_missing(sequence) {
+                        debug!("Discarded duplicated datagram (sequence: {sequence})");
+                        state.report.duplicate += 1;
+                        continue;
+                    }
+
+                    state.report.late += 1;
+                    state.report.missing -= 1;
+                    (0, true)
+                }
+                next_expected => {
+                    let next_expected = next_expected.unwrap_or(0);
+                    let gap = sequence - next_expected;
+                    if gap > 0 {
+                        state.add_missing(next_expected, sequence);
+                    }
+
+                    state.report.missing += gap;
+                    state.next_expected = Some(sequence + 1);
+                    (gap, false)
+                }
+            };
+
+            state.report.received += 1;
+
+            return Ok(SequencedDatagram {
+                sequence,
+                payload: payload.slice(offset..),
+                gap,
+                late,
+            });
+        }
+    }
+
+    /// Returns the statistics of the datagrams received so far.
+    pub fn report(&self) -> SequenceReport {
+        self.lock_state().report
+    }
+
+    /// Returns the maximum payload size of a datagram, accounting for the largest sequence
+    /// number prefix.
+    ///
+    /// See [`Connection::max_datagram_size`].
+    pub fn max_payload_size(&self) -> Option<usize> {
+        self.connection
+            .max_datagram_size()
+            .map(|size| size.saturating_sub(VarInt::MAX_SIZE))
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ReceiveState> {
+        self.receive_state.lock().expect("Mutex is not poisoned")
+    }
+}
+
+/// A datagram received through [`SequencedDatagrams`].
+#[derive(Debug)]
+pub struct SequencedDatagram {
+    sequence: u64,
+    payload: Bytes,
+    gap: u64,
+    late: bool,
+}
+
+impl SequencedDatagram {
+    /// The sequence number assigned by the sender.
+    #[inline(always)]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// The datagram payload (without sequence number).
+    #[inline(always)]
+    pub fn payload(&self) -> &Bytes {
+        &self.payload
+    }
+
+    /// Consumes the datagram, returning its payload.
+    #[inline(always)]
+    pub fn into_payload(self) -> Bytes {
+        self.payload
+    }
+
+    /// The number of sequence numbers skipped right before this datagram, i.e., datagrams
+    /// lost or not arrived yet.
+    #[inline(always)]
+    pub fn gap(&self) -> u64 {
+        self.gap
+    }
+
+    /// Whether this datagram arrived after a datagram with a higher sequence number
+    /// (reordering). Its sequence number was part of a previous [gap](Self::gap).
+    #[inline(always)]
+    pub fn is_late(&self) -> bool {
+        self.late
+    }
+}
+
+/// Statistics of the datagrams received through [`SequencedDatagrams`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SequenceReport {
+    /// Number of datagrams delivered (malformed and duplicated ones excluded).
+    pub received: u64,
+
+    /// Number of sequence numbers skipped and not received (yet), i.e., datagrams lost so
+    /// far. Late datagrams are no longer counted.
+    pub missing: u64,
+
+    /// Number of datagrams received late (see [`SequencedDatagram::is_late`]).
+    pub late: u64,
+
+    /// Number of datagrams discarded as they lacked a valid sequence number.
+    pub malformed: u64,
+
+    /// Number of datagrams discarded as their sequence number was already received.
+    ///
+    /// Only the most recent gaps are tracked (see [`SequencedDatagram::gap`]): a datagram
+    /// arriving after many later gaps is counted here too, and it stays missing.
+    pub duplicate: u64,
+}
+
+/// Maximum number of gaps (ranges of missing sequence numbers) tracked by
+/// [`SequencedDatagrams`]: older ones are considered lost for good.
+const MAX_MISSING_RANGES: usize = 1024;
+
+#[derive(Debug, Default)]
+struct ReceiveState {
+    report: SequenceReport,
+    next_expected: Option<u64>,
+
+    /// Sequence numbers skipped and not received yet (start to end, exclusive).
+    missing_ranges: BTreeMap<u64, u64>,
+}
+
+impl ReceiveState {
+    fn add_missing(&mut self, start: u64, end: u64) {
+        self.missing_ranges.insert(start, end);
+
+        if self.missing_ranges.len() > MAX_MISSING_RANGES {
+            self.missing_ranges.pop_first();
+        }
+    }
+
+    /// Removes `sequence` from the missing ones, returning whether it was missing.
+    fn take_missing(&mut self, sequence: u64) -> bool {
+        let Some((&start, &end)) = self.missing_ranges.range(..=sequence).next_back() else {
+            return false;
+        };
+
+        if sequence >= end {
+            return false;
+        }
+
+        self.missing_ranges.remove(&start);
+        if start < sequence {
+            self.missing_ranges.insert(start, sequence);
+        }
+        if sequence + 1 < end {
+            self.missing_ranges.insert(sequence + 1, end);
+        }
+
+        true
+    }
+}
+
+/// Prefixes `payload` with `sequence`, encoded as a variable-length integer.
+fn encode_sequenced(sequence: u64, payload: &[u8]) -> Vec<u8> {
+    let sequence = VarInt::try_from_u64(sequence).expect("Sequence numbers cannot be exhausted");
+
+    let mut datagram = vec![0; sequence.size() + payload.len()];
+    BufferWriter::new(&mut datagram)
+        .put_varint(sequence)
+        .expect("Preallocated capacity");
+    datagram[sequence.size()..].copy_from_slice(payload);
+
+    datagram
+}
+
+/// Resets the wrapped stream on drop, unless [disarmed](Self::disarm).
+///
+/// A dropped [`SendStream`] is finished implicitly: without this guard, a message
+/// interrupted by a failure or a cancellation would reach the peer truncated, but complete
+/// in appearance.
+struct ResetOnDrop(Option<SendStream>);
+
+impl ResetOnDrop {
+    fn new(send_stream: SendStream) -> Self {
+        Self(Some(send_stream))
+    }
+
+    fn get_mut(&mut self) -> &mut SendStream {
+        self.0.as_mut().expect("Guard is armed")
+    }
+
+    /// To be called once the stream is finished.
+    fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ResetOnDrop {
+    fn drop(&mut self) {
+        if let Some(send_stream) = self.0.take() {
+            send_stream.reset(VarInt::from_u32(0));
+        }
+    }
+}
+
+/// Reads a whole stream, stopping it if it exceeds `max_message_size`.
+async fn read_to_end(
+    mut recv_stream: RecvStream,
+    max_message_size: usize,
+) -> Result<Vec<u8>, MessageRecvError> {
+    let mut message = Vec::new();
+
+    while let Some(chunk) = recv_stream
+        .read_chunk(usize::MAX, true)
+        .await
+        .map_err(MessageRecvError::Read)?
+    {
+        let length = message.len() + chunk.bytes().len();
+
+        if length > max_message_size {
+            recv_stream.stop(VarInt::from_u32(0));
+            return Err(MessageRecvError::TooLarge(length));
+        }
+
+        message.extend_from_slice(chunk.bytes());
+    }
+
+    Ok(message)
+}
+
+#[cfg(all(test, feature = "self-signed"))]
+mod tests {
+    use super::*;
+    use crate::error::StreamReadError;
+    use crate::test_util::connected_pair;
+
+    #[tokio::test]
+    async fn send_recv_messages() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let (send_stream, recv_stream) = client.open_bi().await.unwrap().await.unwrap();
+        let mut client_stream = MessageStream::new(send_stream, recv_stream);
+
+        client_stream.send_message(b"first").await.unwrap();
+        client_stream.send_message(b"").await.unwrap();
+        client_stream.send_message(b"third").await.unwrap();
+        client_stream.finish().await.unwrap();
+
+        let (send_stream, recv_stream) = server.accept_bi().await.unwrap();
+        let mut server_stream = MessageStream::new(send_stream, recv_stream);
+
+        assert_eq!(
+            server_stream.recv_message().await.unwrap().unwrap(),
+            b"first"
+        );
+        assert_eq!(server_stream.recv_message().await.unwrap().unwrap(), b"");
+        assert_eq!(
+            server_stream.recv_message().await.unwrap().unwrap(),
+            b"third"
+        );
+        assert!(server_stream.recv_message().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn max_message_size() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let (send_stream, recv_stream) = client.open_bi().await.unwrap().await.unwrap();
+        let mut client_stream = MessageStream::new(send_stream, recv_stream);
+
+        client_stream.send_message(&[0; 16]).await.unwrap();
+
+        let (send_stream, recv_stream) = server.accept_bi().await.unwrap();
+        let mut server_stream =
+            MessageStream::new(send_stream, recv_stream).with_max_message_size(8);
+
+        assert!(matches!(
+            server_stream.recv_message().await,
+            Err(MessageRecvError::TooLarge(16))
+        ));
+        assert!(matches!(
+            server_stream.send_message(&[0; 16]).await,
+            Err(MessageSendError::TooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reliable_messenger() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        // Serialize sends, so streams are received in order.
+        let client_messenger = ReliableMessenger::new(&client).with_max_concurrent_sends(1);
+        let server_messenger = ReliableMessenger::new(&server).with_max_message_size(8);
+
+        let ((), ()) = tokio::join!(
+            async {
+                client_messenger.send(b"first").await.unwrap();
+                // The peer might stop the stream before it is acknowledged.
+                let _ = client_messenger.send(&[0; 16]).await;
+                client_messenger.send(b"").await.unwrap();
+            },
+            async {
+                assert_eq!(server_messenger.recv().await.unwrap(), b"first");
+                assert!(matches!(
+                    server_messenger.recv().await,
+                    Err(MessageRecvError::TooLarge(_))
+                ));
+                assert!(server_messenger.recv().await.unwrap().is_empty());
+            }
+        );
+
+        assert!(matches!(
+            server_messenger.send(&[0; 16]).await,
+            Err(MessageSendError::TooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_send_resets() {
+        use std::time::Duration;
+
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let size = 8 * 1024 * 1024;
+        let client_messenger = ReliableMessenger::new(&client).with_max_message_size(size);
+        let server_messenger = ReliableMessenger::new(&server).with_max_message_size(size);
+
+        // Not read by the peer yet: flow control blocks the send, until cancelled.
+        let message = vec![0; size];
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(100), client_messenger.send(&message)).await;
+        assert!(cancelled.is_err());
+
+        assert!(matches!(
+            server_messenger.recv().await,
+            Err(MessageRecvError::Read(StreamReadError::Reset(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rpc() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let client_rpc = Rpc::new(&client);
+        let server_rpc = Rpc::new(&server).with_max_message_size(8);
+
+        let ((), ()) = tokio::join!(
+            async {
+                assert_eq!(client_rpc.request(b"ping").await.unwrap(), b"pong");
+                assert!(matches!(
+                    client_rpc.request(b"dropped").await,
+                    Err(RpcError::Recv(MessageRecvError::Read(_)))
+                ));
+            },
+            async {
+                let (request, responder) = server_rpc.accept_request().await.unwrap();
+                assert_eq!(request, b"ping");
+                responder.respond(b"pong").await.unwrap();
+
+                let (request, responder) = server_rpc.accept_request().await.unwrap();
+                assert_eq!(request, b"dropped");
+                drop(responder);
+            }
+        );
+
+        assert!(matches!(
+            client_rpc.request(&[0; 128 * 1024]).await,
+            Err(RpcError::Send(MessageSendError::TooLarge))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_response_resets() {
+        use std::time::Duration;
+
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let size = 8 * 1024 * 1024;
+        let server_rpc = Rpc::new(&server).with_max_message_size(size);
+
+        let (mut send_stream, mut recv_stream) = client.open_bi().await.unwrap().await.unwrap();
+        send_stream.write_all(b"request").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        let (request, responder) = server_rpc.accept_request().await.unwrap();
+        assert_eq!(request, b"request");
+
+        // Not read by the peer yet: flow control blocks the response, until cancelled.
+        let response = vec![0; size];
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(100), responder.respond(&response)).await;
+        assert!(cancelled.is_err());
+
+        let result = loop {
+            match recv_stream.read_chunk(usize::MAX, true).await {
+                Ok(Some(_)) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(StreamReadError::Reset(_))));
+    }
+
+    #[tokio::test]
+    async fn sequenced_datagrams() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let client_datagrams = SequencedDatagrams::new(&client);
+        let server_datagrams = SequencedDatagrams::new(&server);
+
+        assert_eq!(client_datagrams.send(b"first").unwrap(), 0);
+        let datagram = server_datagrams.receive().await.unwrap();
+        assert_eq!(datagram.sequence(), 0);
+        assert_eq!(datagram.payload().as_ref(), b"first");
+        assert_eq!(datagram.gap(), 0);
+        assert!(!datagram.is_late());
+
+        // Simulate the loss of two datagrams.
+        client_datagrams
+            .next_sequence
+            .fetch_add(2, Ordering::Relaxed);
+        assert_eq!(client_datagrams.send(b"fourth").unwrap(), 3);
+        let datagram = server_datagrams.receive().await.unwrap();
+        assert_eq!(datagram.sequence(), 3);
+        assert_eq!(datagram.gap(), 2);
+
+        // Not sequenced.
+        client.send_datagram([]).unwrap();
+
+        // One of the lost datagrams arrives late.
+        client
+            .send_datagram(encode_sequenced(1, b"second"))
+            .unwrap();
+        let datagram = server_datagrams.receive().await.unwrap();
+        assert_eq!(datagram.sequence(), 1);
+        assert_eq!(datagram.gap(), 0);
+        assert!(datagram.is_late());
+        assert_eq!(datagram.into_payload().as_ref(), b"second");
+
+        // Duplicates are discarded, whether received in order or late.
+        client
+            .send_datagram(encode_sequenced(3, b"fourth"))
+            .unwrap();
+        client
+            .send_datagram(encode_sequenced(1, b"second"))
+            .unwrap();
+        assert_eq!(client_datagrams.send(b"fifth").unwrap(), 4);
+        let datagram = server_datagrams.receive().await.unwrap();
+        assert_eq!(datagram.sequence(), 4);
+        assert_eq!(datagram.gap(), 0);
+
+        assert_eq!(
+            server_datagrams.report(),
+            SequenceReport {
+                received: 4,
+                missing: 1,
+                late: 1,
+                malformed: 1,
+                duplicate: 2,
+            }
+        );
+    }
+}
@@ -3,12 +3,18 @@ use crate::driver::streams::unilocal::StreamUniLocalQuic;
 use crate::driver::streams::ProtoWriteError;
 use crate::driver::streams::QuicRecvStream;
 use crate::driver::streams::QuicSendStream;
+use crate::driver::utils::noop_waker;
+use crate::driver::utils::BytesCounter;
+use crate::driver::utils::PendingSendStream;
+use crate::driver::utils::PendingSendStreams;
 use crate::error::StreamOpeningError;
 use crate::error::StreamReadError;
 use crate::error::StreamReadExactError;
 use crate::error::StreamWriteError;
+use bytes::Bytes;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 use tokio::io::ReadBuf;
@@ -18,13 +24,48 @@ use wtransport_proto::stream_header::StreamHeader;
 use wtransport_proto::varint::VarInt;
 
 /// A stream that can only be used to send data.
+///
+/// # Dropping
+///
+/// Dropping a stream that has not been [finished](Self::finish) or [reset](Self::reset)
+/// finishes it implicitly: data already written is still delivered (and retransmitted as
+/// needed), followed by the end-of-stream signal. Hence, the peer never waits forever on a
+/// stream that was written and then dropped.
+///
+/// As drop cannot await, the finish is only *scheduled*: unlike [`finish`](Self::finish),
+/// there is no way to know when (or if) the peer received all data. If the connection is
+/// closed or dropped right after the stream, pending data may be lost.
+/// For the same reason, a dropped stream is not awaited by
+/// [`Connection::close_gracefully`](crate::Connection::close_gracefully).
+///
+/// # Compression
+///
+/// Stream data is not compressed, neither by WebTransport nor by QUIC. The `compression`
+/// feature provides the `compression::CompressedSendStream` and
+/// `compression::CompressedRecvStream` adapters, negotiating the codec with a header byte
+/// at the start of the stream.
+///
+/// # Backpressure
+///
+/// The QUIC stack does not expose the amount of data buffered on a stream, nor the bytes in
+/// flight on the connection, so there are no such accessors. Instead, writes provide
+/// backpressure: [`write`](Self::write) and [`write_all`](Self::write_all) only complete when
+/// the data fits in the stream buffer, which is bounded by flow control and by the data not
+/// yet acknowledged by the peer. A producer that must not build up latency (e.g., a live
+/// encoder) can bound the time spent awaiting a write and drop data when it expires, and can
+/// monitor [`Connection::stats`](crate::Connection::stats) (round-trip time and congestion
+/// window) to adapt its rate.
 #[derive(Debug)]
-pub struct SendStream(QuicSendStream);
+pub struct SendStream(QuicSendStream, Arc<BytesCounter>, PendingSendStream);
 
 impl SendStream {
     #[inline(always)]
-    pub(crate) fn new(stream: QuicSendStream) -> Self {
-        Self(stream)
+    pub(crate) fn new(
+        stream: QuicSendStream,
+        bytes_counter: Arc<BytesCounter>,
+        pending: PendingSendStream,
+    ) -> Self {
+        Self(stream, bytes_counter, pending)
     }
 
     /// Writes bytes to the stream.
@@ -34,13 +75,33 @@ impl SendStream {
     /// indicating that only a prefix of `buf` was written.
     #[inline(always)]
     pub async fn write(&mut self, buf: &[u8]) -> Result<usize, StreamWriteError> {
-        self.0.write(buf).await
+        let written = self.0.write(buf).await?;
+        self.1.add_sent(written);
+        Ok(written)
     }
 
     /// Convenience method to write an entire buffer to the stream.
     #[inline(always)]
     pub async fn write_all(&mut self, buf: &[u8]) -> Result<(), StreamWriteError> {
-        self.0.write_all(buf).await
+        self.0.write_all(buf).await?;
+        self.1.add_sent(buf.len());
+        Ok(())
+    }
+
+    /// Ensures all data written so far is submitted to the QUIC layer.
+    ///
+    /// Writes are not buffered by this crate: as soon as [`write`](Self::write) completes,
+    /// the data is handed to the QUIC stack, which wakes the connection to transmit it
+    /// immediately, within the limits of congestion control and pacing. Hence this method
+    /// completes right away; it exists for protocols that want to mark a message boundary
+    /// explicitly (it is also the behavior of [`AsyncWrite::poll_flush`](tokio::io::AsyncWrite::poll_flush)).
+    ///
+    /// **Note**: flushing does not guarantee delivery, nor that the data has been sent yet.
+    /// Use [`finish`](Self::finish) to wait for the peer to acknowledge all data.
+    pub async fn flush(&mut self) -> Result<(), StreamWriteError> {
+        std::future::poll_fn(|cx| tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx))
+            .await
+            .map_err(|_| StreamWriteError::QuicProto)
     }
 
     /// Shut down the stream gracefully.
@@ -49,7 +110,50 @@ impl SendStream {
     /// acknowledged all sent data, retransmitting data as needed.
     #[inline(always)]
     pub async fn finish(&mut self) -> Result<(), StreamWriteError> {
-        self.0.finish().await
+        let result = self.0.finish().await;
+        self.2.release();
+        result
+    }
+
+    /// Shuts down the stream gracefully, without waiting for the peer acknowledgement.
+    ///
+    /// No new data may be written after calling this method. The end of the stream is sent
+    /// after all data written so far: use [`finished`](Self::finished) to later confirm that
+    /// the peer received everything.
+    pub fn start_finish(&mut self) -> Result<(), StreamWriteError> {
+        self.0.start_finish()
+    }
+
+    /// Waits until the peer has acknowledged all sent data and the end of the stream.
+    ///
+    /// If the stream was not finished yet (see [`start_finish`](Self::start_finish)), it is
+    /// finished first: then, this is equivalent to [`finish`](Self::finish).
+    ///
+    /// Returns [`StreamWriteError::Stopped`] if the peer stopped the stream before receiving
+    /// all data.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: the end of the stream is still delivered, and calling it
+    /// again resumes waiting for the acknowledgement.
+    pub async fn finished(&mut self) -> Result<(), StreamWriteError> {
+        self.finish().await
+    }
+
+    /// Waits for the peer to stop the stream (i.e., a `STOP_SENDING` frame).
+    ///
+    /// Resolves with the error code of the peer, or with `None` if the stream can no longer be
+    /// stopped: it was fully acknowledged after [finishing](Self::finish), it was
+    /// [reset](Self::reset), or the connection is lost.
+    ///
+    /// Unlike [`stopped`](Self::stopped), this does not consume the stream: once it
+    /// resolves, the stream can still be [reset](Self::reset), for instance.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn wait_stopped(&mut self) -> Option<VarInt> {
+        self.0.wait_stopped().await
     }
 
     /// Returns the [`StreamId`] associated.
@@ -65,6 +169,12 @@ impl SendStream {
     /// the priority of a stream with pending data may only take effect after that data has been
     /// transmitted. Using many different priority levels per connection may have a negative
     /// impact on performance.
+    ///
+    /// Streams with the same priority share the bandwidth in a round-robin fashion: the
+    /// scheduler sends one frame from each of them in turn, so they progress at roughly the
+    /// same rate. Weighted sharing among streams of the same priority is not supported by the
+    /// QUIC stack; a weighted share (e.g., 2:1 between two transfers) can be approximated by
+    /// splitting the heavier transfer over multiple streams.
     #[inline(always)]
     pub fn set_priority(&self, priority: i32) {
         self.0.set_priority(priority);
@@ -91,6 +201,7 @@ impl SendStream {
     /// If the stream is stopped the error code will be stored in [`StreamWriteError::Stopped`].
     #[inline(always)]
     pub async fn stopped(mut self) -> StreamWriteError {
+        self.2.release();
         self.0.stopped().await
     }
 
@@ -113,12 +224,12 @@ impl SendStream {
 
 /// A stream that can only be used to receive data.
 #[derive(Debug)]
-pub struct RecvStream(QuicRecvStream);
+pub struct RecvStream(QuicRecvStream, Arc<BytesCounter>);
 
 impl RecvStream {
     #[inline(always)]
-    pub(crate) fn new(stream: QuicRecvStream) -> Self {
-        Self(stream)
+    pub(crate) fn new(stream: QuicRecvStream, bytes_counter: Arc<BytesCounter>) -> Self {
+        Self(stream, bytes_counter)
     }
 
     /// Read data contiguously from the stream.
@@ -126,7 +237,9 @@ impl RecvStream {
     /// On success, returns the number of bytes read into `buf`.
     #[inline(always)]
     pub async fn read(&mut self, buf: &mut [u8]) -> Result<Option<usize>, StreamReadError> {
-        self.0.read(buf).await
+        let read = self.0.read(buf).await?;
+        self.1.add_received(read.unwrap_or_default());
+        Ok(read)
     }
 
     /// Reads an exact number of bytes contiguously from the stream.
@@ -134,7 +247,37 @@ impl RecvStream {
     /// If the stream terminates before the entire length has been read, it
     /// returns [`StreamReadExactError::FinishedEarly`].
     pub async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), StreamReadExactError> {
-        self.0.read_exact(buf).await
+        self.0.read_exact(buf).await?;
+        self.1.add_received(buf.len());
+        Ok(())
+    }
+
+    /// Reads the next segment of data from the stream, without copying it.
+    ///
+    /// Yields `None` if the stream was finished. Otherwise, yields a [`Chunk`] of at most
+    /// `max_length` bytes along with its offset in the stream.
+    ///
+    /// If `ordered` is `true`, the chunk's offset will be immediately after the last data
+    /// yielded by [`read`](Self::read) or `read_chunk`. If `ordered` is `false`, segments
+    /// may be received in any order, and the [`Chunk::offset`] can be used by the caller
+    /// to reassemble the original data. Unordered reads are less prone to head-of-line
+    /// blocking within a stream.
+    ///
+    /// **Note**: chunk boundaries do not correspond to peer writes, and hence cannot be used
+    /// as framing.
+    ///
+    /// As chunks are not copied, this is already as cheap as a buffered read (see
+    /// [`BufRecvStream`]).
+    #[inline(always)]
+    pub async fn read_chunk(
+        &mut self,
+        max_length: usize,
+        ordered: bool,
+    ) -> Result<Option<Chunk>, StreamReadError> {
+        let chunk = self.0.read_chunk(max_length, ordered).await?;
+        self.1
+            .add_received(chunk.as_ref().map_or(0, |chunk| chunk.bytes.len()));
+        Ok(chunk)
     }
 
     /// Stops accepting data on the stream.
@@ -144,6 +287,11 @@ impl RecvStream {
         let _ = self.0.stop(error_code);
     }
 
+    /// Wraps this stream in a [`BufRecvStream`] with a read buffer of `capacity` bytes.
+    pub fn with_read_buffer_capacity(self, capacity: usize) -> BufRecvStream {
+        BufRecvStream::with_capacity(capacity, self)
+    }
+
     /// Returns the [`StreamId`] associated.
     #[inline(always)]
     pub fn id(&self) -> StreamId {
@@ -167,6 +315,40 @@ impl RecvStream {
     }
 }
 
+/// A segment of data received on a [`RecvStream`].
+///
+/// See [`RecvStream::read_chunk`].
+#[derive(Debug)]
+pub struct Chunk {
+    offset: u64,
+    bytes: Bytes,
+}
+
+impl Chunk {
+    #[inline(always)]
+    pub(crate) fn new(offset: u64, bytes: Bytes) -> Self {
+        Self { offset, bytes }
+    }
+
+    /// Returns the offset of this chunk in the stream.
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the content of this chunk.
+    #[inline(always)]
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Consumes the chunk, returning its content.
+    #[inline(always)]
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
 impl tokio::io::AsyncWrite for SendStream {
     #[inline(always)]
     fn poll_write(
@@ -174,7 +356,13 @@ impl tokio::io::AsyncWrite for SendStream {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<std::io::Result<usize>> {
-        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+        let written = std::task::ready!(tokio::io::AsyncWrite::poll_write(
+            Pin::new(&mut self.0),
+            cx,
+            buf
+        ))?;
+        self.1.add_sent(written);
+        Poll::Ready(Ok(written))
     }
 
     #[inline(always)]
@@ -184,7 +372,12 @@ impl tokio::io::AsyncWrite for SendStream {
 
     #[inline(always)]
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
-        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+        let result = std::task::ready!(tokio::io::AsyncWrite::poll_shutdown(
+            Pin::new(&mut self.0),
+            cx
+        ));
+        self.2.release();
+        Poll::Ready(result)
     }
 
     #[inline(always)]
@@ -193,7 +386,13 @@ impl tokio::io::AsyncWrite for SendStream {
         cx: &mut Context<'_>,
         bufs: &[std::io::IoSlice<'_>],
     ) -> Poll<Result<usize, std::io::Error>> {
-        tokio::io::AsyncWrite::poll_write_vectored(Pin::new(&mut self.0), cx, bufs)
+        let written = std::task::ready!(tokio::io::AsyncWrite::poll_write_vectored(
+            Pin::new(&mut self.0),
+            cx,
+            bufs
+        ))?;
+        self.1.add_sent(written);
+        Poll::Ready(Ok(written))
     }
 
     #[inline(always)]
@@ -209,7 +408,379 @@ impl tokio::io::AsyncRead for RecvStream {
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        tokio::io::AsyncRead::poll_read(Pin::new(&mut self.0), cx, buf)
+        let filled = buf.filled().len();
+        std::task::ready!(tokio::io::AsyncRead::poll_read(
+            Pin::new(&mut self.0),
+            cx,
+            buf
+        ))?;
+        self.1.add_received(buf.filled().len() - filled);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A [`RecvStream`] with an internal read buffer.
+///
+/// Each read on a [`RecvStream`] locks the QUIC connection state. When the application reads
+/// many small frames (e.g., a length prefix followed by a small payload), this overhead
+/// adds up. A [`BufRecvStream`] instead reads up to its capacity at once, and serves the
+/// following small reads from its buffer.
+///
+/// It also implements [`AsyncBufRead`](tokio::io::AsyncBufRead), for line or delimiter
+/// based protocols.
+///
+/// # Read-ahead
+///
+/// The buffer is filled with whatever stream data is available, regardless of the message
+/// boundaries of the application: data following the current message stays in the buffer.
+/// Hence, once a stream is buffered, it must be read through the [`BufRecvStream`] only
+/// (or the buffered data taken back with [`into_parts`](Self::into_parts)).
+///
+/// The end of the stream is reported (i.e., [`read`](Self::read) yields `None`) only after
+/// all buffered data was read.
+///
+/// # Chunks
+///
+/// There is no buffered counterpart of [`RecvStream::read_chunk`]: chunks are never copied,
+/// hence they do not benefit from buffering. Reading chunks from
+/// [`get_mut`](Self::get_mut) is only consistent while [`buffer`](Self::buffer) is empty.
+///
+/// # Example
+///
+/// ```no_run
+/// # use wtransport::Connection;
+/// # use anyhow::Result;
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let mut stream = connection.accept_uni().await?.with_read_buffer_capacity(4096);
+///
+/// let mut length = [0; 2];
+/// stream.read_exact(&mut length).await?;
+///
+/// let mut frame = vec![0; u16::from_be_bytes(length) as usize];
+/// stream.read_exact(&mut frame).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BufRecvStream {
+    stream: RecvStream,
+    buffer: Box<[u8]>,
+    position: usize,
+    filled: usize,
+    pending_error: Option<StreamReadError>,
+}
+
+impl BufRecvStream {
+    /// Default capacity of the read buffer (in bytes).
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    /// Creates a buffered stream with [`DEFAULT_CAPACITY`](Self::DEFAULT_CAPACITY).
+    pub fn new(stream: RecvStream) -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, stream)
+    }
+
+    /// Creates a buffered stream with a read buffer of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, stream: RecvStream) -> Self {
+        assert!(capacity > 0, "Read buffer capacity must be positive");
+
+        Self {
+            stream,
+            buffer: vec![0; capacity].into_boxed_slice(),
+            position: 0,
+            filled: 0,
+            pending_error: None,
+        }
+    }
+
+    /// Reads data contiguously from the stream.
+    ///
+    /// On success, returns the number of bytes read into `buf`, or `None` once the stream
+    /// is finished and the buffer is empty.
+    ///
+    /// When the buffer is empty and `buf` is at least as large as its capacity, data is read
+    /// directly into `buf`.
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<Option<usize>, StreamReadError> {
+        if self.position == self.filled {
+            if let Some(error) = self.pending_error.take() {
+                return Err(error);
+            }
+
+            if buf.len() >= self.buffer.len() {
+                return self.stream.read(buf).await;
+            }
+        }
+
+        if self.position == self.filled {
+            match self.stream.read(&mut self.buffer).await? {
+                Some(read) => {
+                    self.position = 0;
+                    self.filled = read;
+                }
+                None => return Ok(None),
+            }
+        }
+
+        let buffered = &self.buffer[self.position..self.filled];
+        let length = buffered.len().min(buf.len());
+        buf[..length].copy_from_slice(&buffered[..length]);
+        self.position += length;
+
+        Ok(Some(length))
+    }
+
+    /// Reads an exact number of bytes contiguously from the stream.
+    ///
+    /// If the stream terminates before the entire length has been read, it
+    /// returns [`StreamReadExactError::FinishedEarly`].
+    pub async fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), StreamReadExactError> {
+        while !buf.is_empty() {
+            match self.read(buf).await.map_err(StreamReadExactError::Read)? {
+                Some(read) => buf = &mut buf[read..],
+                None => return Err(StreamReadExactError::FinishedEarly),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the data buffered but not read yet.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer[self.position..self.filled]
+    }
+
+    /// Returns the capacity of the read buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes that can be read without waiting.
+    ///
+    /// Data already received from the peer is moved into [`buffer`](Self::buffer), up to its
+    /// capacity, without blocking. Reading up to this amount then completes immediately.
+    ///
+    /// This is a lower bound: more data may arrive at any time, and data exceeding the
+    /// buffer capacity is not accounted for.
+    pub fn available_bytes(&mut self) -> usize {
+        if self.position > 0 {
+            self.buffer.copy_within(self.position..self.filled, 0);
+            self.filled -= self.position;
+            self.position = 0;
+        }
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        while self.pending_error.is_none() && self.filled < self.buffer.len() {
+            let read = std::pin::pin!(self.stream.read(&mut self.buffer[self.filled..]));
+            match read.poll(&mut cx) {
+                Poll::Ready(Ok(Some(read))) => self.filled += read,
+                Poll::Ready(Ok(None)) | Poll::Pending => break,
+                // Reported once buffered data is read.
+                Poll::Ready(Err(error)) => self.pending_error = Some(error),
+            }
+        }
+
+        self.filled
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &RecvStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// Reading from it directly skips the data in [`buffer`](Self::buffer).
+    pub fn get_mut(&mut self) -> &mut RecvStream {
+        &mut self.stream
+    }
+
+    /// Consumes the buffered stream, returning the underlying stream and the data buffered but
+    /// not read yet (which precedes any further data read from the stream).
+    ///
+    /// A read error already encountered by [`available_bytes`](Self::available_bytes) is
+    /// discarded.
+    pub fn into_parts(self) -> (RecvStream, Vec<u8>) {
+        let buffered = self.buffer().to_vec();
+        (self.stream, buffered)
+    }
+
+    /// Returns the [`StreamId`] associated.
+    #[inline(always)]
+    pub fn id(&self) -> StreamId {
+        self.stream.id()
+    }
+}
+
+impl tokio::io::AsyncRead for BufRecvStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.position == self.filled
+            && self.pending_error.is_none()
+            && buf.remaining() >= self.buffer.len()
+        {
+            return tokio::io::AsyncRead::poll_read(Pin::new(&mut self.stream), cx, buf);
+        }
+
+        let buffered =
+            std::task::ready!(tokio::io::AsyncBufRead::poll_fill_buf(self.as_mut(), cx))?;
+        let length = buffered.len().min(buf.remaining());
+        buf.put_slice(&buffered[..length]);
+        tokio::io::AsyncBufRead::consume(self, length);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl tokio::io::AsyncBufRead for BufRecvStream {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        if this.position == this.filled {
+            if let Some(error) = this.pending_error.take() {
+                return Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::Other, error)));
+            }
+
+            let mut read_buf = ReadBuf::new(&mut this.buffer);
+            std::task::ready!(tokio::io::AsyncRead::poll_read(
+                Pin::new(&mut this.stream),
+                cx,
+                &mut read_buf
+            ))?;
+            this.filled = read_buf.filled().len();
+            this.position = 0;
+        }
+
+        Poll::Ready(Ok(&this.buffer[this.position..this.filled]))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amount: usize) {
+        self.position = (self.position + amount).min(self.filled);
+    }
+}
+
+/// A bidirectional stream, combining its [`SendStream`] and [`RecvStream`] halves.
+///
+/// It implements both [`AsyncRead`](tokio::io::AsyncRead) and
+/// [`AsyncWrite`](tokio::io::AsyncWrite), so it can be used with libraries expecting a
+/// single duplex I/O object.
+///
+/// # Example
+///
+/// ```no_run
+/// # use wtransport::Connection;
+/// # use wtransport::stream::BiStream;
+/// # use anyhow::Result;
+/// # async fn run(connection: Connection) -> Result<()> {
+/// let stream = BiStream::from(connection.open_bi().await?.await?);
+///
+/// // ... use `stream` as a duplex ...
+///
+/// let (send_stream, recv_stream) = stream.into_split();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BiStream {
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl BiStream {
+    /// Combines the two halves of a bidirectional stream.
+    #[inline(always)]
+    pub fn join(send: SendStream, recv: RecvStream) -> Self {
+        Self { send, recv }
+    }
+
+    /// Splits the stream back into its two halves.
+    #[inline(always)]
+    pub fn into_split(self) -> (SendStream, RecvStream) {
+        (self.send, self.recv)
+    }
+
+    /// Returns a reference to the sending half.
+    #[inline(always)]
+    pub fn send_stream(&self) -> &SendStream {
+        &self.send
+    }
+
+    /// Returns a mutable reference to the sending half.
+    #[inline(always)]
+    pub fn send_stream_mut(&mut self) -> &mut SendStream {
+        &mut self.send
+    }
+
+    /// Returns a reference to the receiving half.
+    #[inline(always)]
+    pub fn recv_stream(&self) -> &RecvStream {
+        &self.recv
+    }
+
+    /// Returns a mutable reference to the receiving half.
+    #[inline(always)]
+    pub fn recv_stream_mut(&mut self) -> &mut RecvStream {
+        &mut self.recv
+    }
+}
+
+impl From<(SendStream, RecvStream)> for BiStream {
+    #[inline(always)]
+    fn from((send, recv): (SendStream, RecvStream)) -> Self {
+        Self::join(send, recv)
+    }
+}
+
+impl tokio::io::AsyncWrite for BiStream {
+    #[inline(always)]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.send), cx, buf)
+    }
+
+    #[inline(always)]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.send), cx)
+    }
+
+    #[inline(always)]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.send), cx)
+    }
+
+    #[inline(always)]
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        tokio::io::AsyncWrite::poll_write_vectored(Pin::new(&mut self.send), cx, bufs)
+    }
+
+    #[inline(always)]
+    fn is_write_vectored(&self) -> bool {
+        tokio::io::AsyncWrite::is_write_vectored(&self.send)
+    }
+}
+
+impl tokio::io::AsyncRead for BiStream {
+    #[inline(always)]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        tokio::io::AsyncRead::poll_read(Pin::new(&mut self.recv), cx, buf)
     }
 }
 
@@ -221,13 +792,24 @@ type DynFutureUniStream = dyn Future<Output = Result<SendStream, StreamOpeningEr
 pub struct OpeningUniStream(Pin<Box<DynFutureUniStream>>);
 
 impl OpeningUniStream {
-    pub(crate) fn new(session_id: SessionId, quic_stream: StreamUniLocalQuic) -> Self {
+    pub(crate) fn new(
+        session_id: SessionId,
+        quic_stream: StreamUniLocalQuic,
+        bytes_counter: Arc<BytesCounter>,
+        pending_send_streams: PendingSendStreams,
+    ) -> Self {
+        let pending = pending_send_streams.track();
+
         Self(Box::pin(async move {
             match quic_stream
                 .upgrade(StreamHeader::new_webtransport(session_id))
                 .await
             {
-                Ok(stream) => Ok(SendStream(stream.upgrade().into_stream())),
+                Ok(stream) => Ok(SendStream::new(
+                    stream.upgrade().into_stream(),
+                    bytes_counter,
+                    pending,
+                )),
                 Err(ProtoWriteError::NotConnected) => Err(StreamOpeningError::NotConnected),
                 Err(ProtoWriteError::Stopped) => Err(StreamOpeningError::Refused),
             }
@@ -252,12 +834,22 @@ type DynFutureBiStream =
 pub struct OpeningBiStream(Pin<Box<DynFutureBiStream>>);
 
 impl OpeningBiStream {
-    pub(crate) fn new(session_id: SessionId, quic_stream: StreamBiLocalQuic) -> Self {
+    pub(crate) fn new(
+        session_id: SessionId,
+        quic_stream: StreamBiLocalQuic,
+        bytes_counter: Arc<BytesCounter>,
+        pending_send_streams: PendingSendStreams,
+    ) -> Self {
+        let pending = pending_send_streams.track();
+
         Self(Box::pin(async move {
             match quic_stream.upgrade().upgrade(session_id).await {
                 Ok(stream) => {
                     let stream = stream.into_stream();
-                    Ok((SendStream::new(stream.0), RecvStream::new(stream.1)))
+                    Ok((
+                        SendStream::new(stream.0, bytes_counter.clone(), pending),
+                        RecvStream::new(stream.1, bytes_counter),
+                    ))
                 }
                 Err(ProtoWriteError::NotConnected) => Err(StreamOpeningError::NotConnected),
                 Err(ProtoWriteError::Stopped) => Err(StreamOpeningError::Refused),
@@ -274,3 +866,185 @@ impl Future for OpeningBiStream {
         Future::poll(self.0.as_mut(), cx)
     }
 }
+
+#[cfg(all(test, feature = "self-signed"))]
+mod tests {
+    use super::*;
+    use crate::test_util::connected_pair;
+
+    #[tokio::test]
+    async fn read_chunk_offset() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"uni").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        let mut recv_stream = server.accept_uni().await.unwrap();
+        let chunk = recv_stream.read_chunk(1, true).await.unwrap().unwrap();
+        assert_eq!(chunk.offset(), 0);
+        assert_eq!(chunk.bytes().as_ref(), b"u");
+        let chunk = recv_stream.read_chunk(16, true).await.unwrap().unwrap();
+        assert_eq!(chunk.offset(), 1);
+        assert_eq!(chunk.bytes().as_ref(), b"ni");
+
+        let (mut send_stream, _) = client.open_bi().await.unwrap().await.unwrap();
+        send_stream.write_all(b"bi").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        let (_, mut recv_stream) = server.accept_bi().await.unwrap();
+        let chunk = recv_stream.read_chunk(16, true).await.unwrap().unwrap();
+        assert_eq!(chunk.offset(), 0);
+        assert_eq!(chunk.bytes().as_ref(), b"bi");
+    }
+
+    #[tokio::test]
+    async fn drop_finishes() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"payload").await.unwrap();
+        drop(send_stream);
+
+        let mut recv_stream = server.accept_uni().await.unwrap();
+
+        let mut buffer = [0; 7];
+        recv_stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"payload");
+        assert!(recv_stream.read(&mut buffer).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn flush() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"ping").await.unwrap();
+        send_stream.flush().await.unwrap();
+
+        // The stream is neither finished nor dropped: data is sent anyway.
+        let mut recv_stream = server.accept_uni().await.unwrap();
+        let mut buffer = [0; 4];
+        recv_stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"ping");
+    }
+
+    #[tokio::test]
+    async fn buffered_read() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"\x02hi\x05hello").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        let mut recv_stream = server
+            .accept_uni()
+            .await
+            .unwrap()
+            .with_read_buffer_capacity(64);
+
+        let mut frames = Vec::new();
+        let mut length = [0; 1];
+        while recv_stream.read(&mut length).await.unwrap().is_some() {
+            let mut frame = vec![0; length[0] as usize];
+            recv_stream.read_exact(&mut frame).await.unwrap();
+            frames.push(frame);
+        }
+
+        assert_eq!(frames, [b"hi".to_vec(), b"hello".to_vec()]);
+        assert!(recv_stream.buffer().is_empty());
+        assert!(matches!(
+            recv_stream.read_exact(&mut length).await,
+            Err(StreamReadExactError::FinishedEarly)
+        ));
+    }
+
+    #[tokio::test]
+    async fn available_bytes() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"first").await.unwrap();
+
+        let mut recv_stream = server
+            .accept_uni()
+            .await
+            .unwrap()
+            .with_read_buffer_capacity(64);
+
+        let mut first = [0; 5];
+        recv_stream.read_exact(&mut first).await.unwrap();
+        assert_eq!(recv_stream.available_bytes(), 0);
+
+        send_stream.write_all(b"second").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        while recv_stream.available_bytes() < 6 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(recv_stream.buffer(), b"second");
+
+        let mut second = [0; 6];
+        recv_stream.read_exact(&mut second).await.unwrap();
+        assert_eq!(recv_stream.available_bytes(), 0);
+        assert!(recv_stream.read(&mut second).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn finished_and_stopped() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"done").await.unwrap();
+        send_stream.start_finish().unwrap();
+
+        let mut recv_stream = server.accept_uni().await.unwrap();
+        let mut buffer = [0; 4];
+        recv_stream.read_exact(&mut buffer).await.unwrap();
+        assert!(recv_stream.read(&mut buffer).await.unwrap().is_none());
+
+        send_stream.finished().await.unwrap();
+        assert_eq!(send_stream.wait_stopped().await, None);
+
+        let mut send_stream = client.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"more").await.unwrap();
+
+        let recv_stream = server.accept_uni().await.unwrap();
+        recv_stream.stop(VarInt::from_u32(7));
+
+        assert_eq!(send_stream.wait_stopped().await, Some(VarInt::from_u32(7)));
+    }
+
+    #[tokio::test]
+    async fn bi_stream() {
+        let (_endpoint, server, client) = connected_pair().await;
+
+        let mut stream = BiStream::from(client.open_bi().await.unwrap().await.unwrap());
+        let written = std::future::poll_fn(|cx| {
+            tokio::io::AsyncWrite::poll_write(Pin::new(&mut stream), cx, b"ping")
+        })
+        .await
+        .unwrap();
+        assert_eq!(written, 4);
+
+        let (mut send_stream, mut recv_stream) = server.accept_bi().await.unwrap();
+        let mut buffer = [0; 4];
+        recv_stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"ping");
+        send_stream.write_all(b"pong").await.unwrap();
+
+        let mut buffer = [0; 4];
+        let mut read_buf = ReadBuf::new(&mut buffer);
+        while read_buf.filled().len() < 4 {
+            std::future::poll_fn(|cx| {
+                tokio::io::AsyncRead::poll_read(Pin::new(&mut stream), cx, &mut read_buf)
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(read_buf.filled(), b"pong");
+
+        let (send_half, recv_half) = stream.into_split();
+        assert_eq!(send_half.id(), recv_half.id());
+    }
+}
@@ -0,0 +1,582 @@
+use crate::config::states::WantsTransportConfigClient;
+use crate::config::states::WantsTransportConfigServer;
+use crate::config::ClientConfigBuilder;
+use crate::config::IpBindConfig;
+use crate::config::ServerConfigBuilder;
+use crate::endpoint::endpoint_side::Server;
+use crate::Certificate;
+use crate::ClientConfig;
+use crate::Connection;
+use crate::Endpoint;
+use crate::ServerConfig;
+
+#[cfg(feature = "quinn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+pub use faulty::FaultySocket;
+
+#[cfg(feature = "quinn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+pub use faulty::PacketDirection;
+
+#[cfg(feature = "quinn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "quinn")))]
+pub use faulty::PacketFate;
+
+/// Sets up a WebTransport session between a server and a client, both bound on localhost.
+///
+/// The server uses a self-signed certificate, which the client trusts as its only root
+/// certificate. It returns the server endpoint (so that more sessions can be accepted),
+/// the server side of the connection and the client side of the connection.
+///
+/// This is intended for tests only: it must never be used for real traffic.
+///
+/// # Panics
+///
+/// Panics if any step of the setup fails (binding, handshake, session request).
+///
+/// # Examples
+/// ```
+/// # async fn run() {
+/// use wtransport::test_util::connected_pair;
+///
+/// let (_server, server_connection, client_connection) = connected_pair().await;
+///
+/// client_connection.send_datagram(b"ping").unwrap();
+/// let datagram = server_connection.receive_datagram().await.unwrap();
+/// assert_eq!(&*datagram, b"ping");
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+pub async fn connected_pair() -> (Endpoint<Server>, Connection, Connection) {
+    connected_pair_with(|server| server, |client| client).await
+}
+
+/// Like [`connected_pair`], with server and client options set by `server_config` and
+/// `client_config`.
+///
+/// Both functions receive their builder after the certificate setup, and return it with
+/// the options under test.
+///
+/// # Examples
+/// ```
+/// # async fn run() {
+/// use std::time::Duration;
+/// use wtransport::test_util::connected_pair_with;
+///
+/// let (_server, server_connection, _client_connection) = connected_pair_with(
+///     |server| server.keep_alive_interval(Some(Duration::from_secs(1))),
+///     |client| client,
+/// )
+/// .await;
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+pub async fn connected_pair_with<S, C>(
+    server_config: S,
+    client_config: C,
+) -> (Endpoint<Server>, Connection, Connection)
+where
+    S: FnOnce(
+        ServerConfigBuilder<WantsTransportConfigServer>,
+    ) -> ServerConfigBuilder<WantsTransportConfigServer>,
+    C: FnOnce(
+        ClientConfigBuilder<WantsTransportConfigClient>,
+    ) -> ClientConfigBuilder<WantsTransportConfigClient>,
+{
+    let (server_config, client_config) = config_pair_with(server_config, client_config);
+    connect(server_config, client_config).await
+}
+
+/// Builds a server and a client configuration, both bound on localhost, as set up by
+/// [`connected_pair_with`].
+///
+/// This allows creating the endpoints (e.g., with a custom socket) and connecting them
+/// separately.
+pub fn config_pair_with<S, C>(server_config: S, client_config: C) -> (ServerConfig, ClientConfig)
+where
+    S: FnOnce(
+        ServerConfigBuilder<WantsTransportConfigServer>,
+    ) -> ServerConfigBuilder<WantsTransportConfigServer>,
+    C: FnOnce(
+        ClientConfigBuilder<WantsTransportConfigClient>,
+    ) -> ClientConfigBuilder<WantsTransportConfigClient>,
+{
+    let certificate = Certificate::self_signed(["localhost"]);
+
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store
+        .add(&rustls::Certificate(certificate.certificates()[0].clone()))
+        .expect("Self-signed certificate is valid");
+
+    let server_config = server_config(
+        ServerConfig::builder()
+            .with_bind_config(IpBindConfig::LocalV4, 0)
+            .with_certificate(certificate),
+    )
+    .build();
+
+    let client_config = client_config(
+        ClientConfig::builder()
+            .with_bind_config(IpBindConfig::LocalV4)
+            .with_root_certificates(root_store),
+    )
+    .build();
+
+    (server_config, client_config)
+}
+
+/// Like [`connected_pair`], with a custom QUIC transport configuration for the client.
+#[cfg(test)]
+pub(crate) async fn connected_pair_with_transport(
+    client_transport_config: quinn::TransportConfig,
+) -> (Endpoint<Server>, Connection, Connection) {
+    let (server_config, mut client_config) = config_pair_with(|server| server, |client| client);
+
+    client_config
+        .quic_config
+        .transport_config(std::sync::Arc::new(client_transport_config));
+
+    connect(server_config, client_config).await
+}
+
+async fn connect(
+    server_config: ServerConfig,
+    client_config: ClientConfig,
+) -> (Endpoint<Server>, Connection, Connection) {
+    let server =
+        Endpoint::server(server_config).expect("Server endpoint can be bound on localhost");
+
+    let client =
+        Endpoint::client(client_config).expect("Client endpoint can be bound on localhost");
+
+    let url = format!(
+        "https://localhost:{}",
+        server.local_addr().expect("Server is bound").port()
+    );
+
+    let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+        server
+            .accept()
+            .await
+            .await
+            .expect("Session request received")
+            .accept()
+            .await
+    });
+
+    (
+        server,
+        server_connection.expect("Session accepted"),
+        client_connection.expect("Session established"),
+    )
+}
+
+#[cfg(feature = "quinn")]
+mod faulty {
+    use bytes::Bytes;
+    use std::collections::VecDeque;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::time::Instant;
+    use tracing::debug;
+
+    /// Direction of a packet going through a [`FaultySocket`].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum PacketDirection {
+        /// Packet sent by the local endpoint.
+        Outgoing,
+
+        /// Packet received from the network.
+        Incoming,
+    }
+
+    /// Fate of a packet going through a [`FaultySocket`], as decided by its fault policy.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum PacketFate {
+        /// The packet is delivered immediately.
+        Deliver,
+
+        /// The packet is silently discarded, as if lost by the network.
+        Drop,
+
+        /// The packet is delivered after the given delay.
+        ///
+        /// Packets delayed longer than the following ones are reordered.
+        Delay(std::time::Duration),
+    }
+
+    /// A UDP socket applying a fault policy (loss, delay, reordering) to every packet.
+    ///
+    /// It is meant to test how an application behaves on adverse networks, in a reproducible
+    /// way: instead of relying on random faults, the policy decides the [`PacketFate`] of each
+    /// UDP datagram, in both directions. It is plugged with
+    /// [`Endpoint::server_with_socket`](crate::Endpoint::server_with_socket) or
+    /// [`Endpoint::client_with_socket`](crate::Endpoint::client_with_socket).
+    ///
+    /// The policy is invoked once per datagram, in the order datagrams are sent or received.
+    /// Batches of datagrams (segmentation and receive offloads) are split beforehand.
+    ///
+    /// This is intended for tests only: packets are buffered in memory without bound.
+    ///
+    /// # Examples
+    ///
+    /// Drop one outgoing packet out of four:
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use wtransport::test_util::FaultySocket;
+    /// # use wtransport::test_util::PacketDirection;
+    /// # use wtransport::test_util::PacketFate;
+    /// # use wtransport::ClientConfig;
+    /// # use wtransport::Endpoint;
+    /// # fn run() -> std::io::Result<()> {
+    /// let runtime = Arc::new(wtransport::quinn::TokioRuntime);
+    ///
+    /// let mut count = 0;
+    /// let socket = FaultySocket::new(
+    ///     std::net::UdpSocket::bind("127.0.0.1:0")?,
+    ///     runtime.clone(),
+    ///     move |direction, _payload: &[u8]| {
+    ///         count += 1;
+    ///         if direction == PacketDirection::Outgoing && count % 4 == 0 {
+    ///             PacketFate::Drop
+    ///         } else {
+    ///             PacketFate::Deliver
+    ///         }
+    ///     },
+    /// )?;
+    ///
+    /// let client = Endpoint::client_with_socket(ClientConfig::default(), socket, runtime)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub struct FaultySocket {
+        socket: Box<dyn quinn::AsyncUdpSocket>,
+        runtime: Arc<dyn quinn::Runtime>,
+        udp_state: quinn::udp::UdpState,
+        state: Mutex<FaultyState>,
+    }
+
+    type FaultPolicy = Box<dyn FnMut(PacketDirection, &[u8]) -> PacketFate + Send>;
+
+    struct FaultyState {
+        policy: FaultPolicy,
+        outgoing: VecDeque<quinn::udp::Transmit>,
+        incoming: VecDeque<(quinn::udp::RecvMeta, Bytes)>,
+        delayed: Vec<(Instant, DelayedPacket)>,
+        timer: Option<Pin<Box<dyn quinn::AsyncTimer>>>,
+        recv_buffer: Vec<u8>,
+    }
+
+    enum DelayedPacket {
+        Outgoing(quinn::udp::Transmit),
+        Incoming(quinn::udp::RecvMeta, Bytes),
+    }
+
+    impl FaultySocket {
+        /// Wraps `socket`, applying `policy` to each packet.
+        ///
+        /// The `runtime` drives the socket I/O and the timers of delayed packets: it must be the
+        /// one given to the endpoint.
+        pub fn new<P>(
+            socket: std::net::UdpSocket,
+            runtime: Arc<dyn quinn::Runtime>,
+            policy: P,
+        ) -> std::io::Result<Self>
+        where
+            P: FnMut(PacketDirection, &[u8]) -> PacketFate + Send + 'static,
+        {
+            Ok(Self {
+                socket: runtime.wrap_udp_socket(socket)?,
+                runtime,
+                udp_state: quinn::udp::UdpState::new(),
+                state: Mutex::new(FaultyState {
+                    policy: Box::new(policy),
+                    outgoing: VecDeque::new(),
+                    incoming: VecDeque::new(),
+                    delayed: Vec::new(),
+                    timer: None,
+                    recv_buffer: vec![0; u16::MAX as usize],
+                }),
+            })
+        }
+
+        fn lock_state(&self) -> std::sync::MutexGuard<'_, FaultyState> {
+            self.state.lock().expect("Mutex is not poisoned")
+        }
+
+        /// Sends the packets ready to go. Returns `true` once all of them are sent.
+        fn flush_outgoing(&self, state: &mut FaultyState, cx: &mut Context<'_>) -> bool {
+            while !state.outgoing.is_empty() {
+                let (transmits, _) = state.outgoing.as_slices();
+
+                match self.socket.poll_send(&self.udp_state, cx, transmits) {
+                    Poll::Ready(Ok(sent)) => {
+                        state.outgoing.drain(..sent);
+                    }
+                    Poll::Ready(Err(error)) => {
+                        // As for plain UDP, a failed send is a lost packet.
+                        debug!("Faulty socket send failed: {error}");
+                        state.outgoing.pop_front();
+                    }
+                    Poll::Pending => return false,
+                }
+            }
+
+            true
+        }
+
+        /// Releases the delayed packets whose deadline expired, and arms the timer for the
+        /// next one.
+        fn poll_delayed(&self, state: &mut FaultyState, cx: &mut Context<'_>) {
+            loop {
+                let now = Instant::now();
+
+                let mut index = 0;
+                while index < state.delayed.len() {
+                    if state.delayed[index].0 <= now {
+                        match state.delayed.remove(index).1 {
+                            DelayedPacket::Outgoing(transmit) => state.outgoing.push_back(transmit),
+                            DelayedPacket::Incoming(meta, payload) => {
+                                state.incoming.push_back((meta, payload))
+                            }
+                        }
+                    } else {
+                        index += 1;
+                    }
+                }
+
+                let Some(deadline) = state.delayed.iter().map(|(deadline, _)| *deadline).min()
+                else {
+                    state.timer = None;
+                    return;
+                };
+
+                let timer = match &mut state.timer {
+                    Some(timer) => {
+                        timer.as_mut().reset(deadline);
+                        timer
+                    }
+                    None => state.timer.insert(self.runtime.new_timer(deadline)),
+                };
+
+                if timer.as_mut().poll(cx).is_pending() {
+                    return;
+                }
+            }
+        }
+    }
+
+    impl quinn::AsyncUdpSocket for FaultySocket {
+        fn poll_send(
+            &self,
+            _state: &quinn::udp::UdpState,
+            cx: &mut Context<'_>,
+            transmits: &[quinn::udp::Transmit],
+        ) -> Poll<std::io::Result<usize>> {
+            let mut state = self.lock_state();
+            let state = &mut *state;
+
+            if !self.flush_outgoing(state, cx) {
+                return Poll::Pending;
+            }
+
+            for transmit in transmits {
+                let segment_size = transmit.segment_size.unwrap_or(transmit.contents.len());
+
+                for offset in (0..transmit.contents.len()).step_by(segment_size.max(1)) {
+                    let end = (offset + segment_size).min(transmit.contents.len());
+                    let datagram = quinn::udp::Transmit {
+                        contents: transmit.contents.slice(offset..end),
+                        segment_size: None,
+                        ..transmit.clone()
+                    };
+
+                    match (state.policy)(PacketDirection::Outgoing, &datagram.contents) {
+                        PacketFate::Deliver => state.outgoing.push_back(datagram),
+                        PacketFate::Drop => {}
+                        PacketFate::Delay(delay) => state
+                            .delayed
+                            .push((Instant::now() + delay, DelayedPacket::Outgoing(datagram))),
+                    }
+                }
+            }
+
+            self.flush_outgoing(state, cx);
+            self.poll_delayed(state, cx);
+
+            Poll::Ready(Ok(transmits.len()))
+        }
+
+        fn poll_recv(
+            &self,
+            cx: &mut Context<'_>,
+            bufs: &mut [std::io::IoSliceMut<'_>],
+            meta: &mut [quinn::udp::RecvMeta],
+        ) -> Poll<std::io::Result<usize>> {
+            let mut state = self.lock_state();
+            let state = &mut *state;
+
+            loop {
+                self.poll_delayed(state, cx);
+                self.flush_outgoing(state, cx);
+
+                if !state.incoming.is_empty() {
+                    let mut count = 0;
+
+                    while count < bufs.len().min(meta.len()) {
+                        let Some((packet_meta, payload)) = state.incoming.pop_front() else {
+                            break;
+                        };
+
+                        let length = payload.len().min(bufs[count].len());
+                        bufs[count][..length].copy_from_slice(&payload[..length]);
+                        meta[count] = quinn::udp::RecvMeta {
+                            len: length,
+                            stride: length,
+                            ..packet_meta
+                        };
+                        count += 1;
+                    }
+
+                    return Poll::Ready(Ok(count));
+                }
+
+                let mut recv_meta = [quinn::udp::RecvMeta::default(); 1];
+                let received = {
+                    let mut recv_bufs = [std::io::IoSliceMut::new(&mut state.recv_buffer)];
+                    std::task::ready!(self.socket.poll_recv(cx, &mut recv_bufs, &mut recv_meta))?
+                };
+
+                for packet_meta in &recv_meta[..received] {
+                    let data = &state.recv_buffer[..packet_meta.len];
+                    let stride = if packet_meta.stride == 0 {
+                        data.len()
+                    } else {
+                        packet_meta.stride
+                    };
+
+                    for segment in data.chunks(stride.max(1)) {
+                        let payload = Bytes::copy_from_slice(segment);
+
+                        match (state.policy)(PacketDirection::Incoming, &payload) {
+                            PacketFate::Deliver => {
+                                state.incoming.push_back((*packet_meta, payload))
+                            }
+                            PacketFate::Drop => {}
+                            PacketFate::Delay(delay) => state.delayed.push((
+                                Instant::now() + delay,
+                                DelayedPacket::Incoming(*packet_meta, payload),
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+
+        fn local_addr(&self) -> std::io::Result<SocketAddr> {
+            self.socket.local_addr()
+        }
+
+        fn may_fragment(&self) -> bool {
+            self.socket.may_fragment()
+        }
+    }
+
+    impl std::fmt::Debug for FaultySocket {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("FaultySocket")
+                .field("socket", &self.socket)
+                .finish_non_exhaustive()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_util::config_pair_with;
+        use crate::Endpoint;
+
+        #[tokio::test]
+        async fn faulty_socket() {
+            use std::sync::atomic::AtomicUsize;
+            use std::sync::atomic::Ordering;
+            use std::time::Duration;
+
+            let (server_config, client_config) = config_pair_with(|server| server, |client| client);
+            let server = Endpoint::server(server_config).unwrap();
+
+            let runtime = Arc::new(quinn::TokioRuntime);
+            let dropped = Arc::new(AtomicUsize::new(0));
+            let delayed = Arc::new(AtomicUsize::new(0));
+
+            // Loses the very first packet (client Initial), then delays each incoming packet.
+            let mut outgoing = 0;
+            let socket = FaultySocket::new(
+                std::net::UdpSocket::bind("127.0.0.1:0").unwrap(),
+                runtime.clone(),
+                {
+                    let dropped = dropped.clone();
+                    let delayed = delayed.clone();
+                    move |direction, _payload: &[u8]| match direction {
+                        PacketDirection::Outgoing => {
+                            outgoing += 1;
+                            if outgoing == 1 {
+                                dropped.fetch_add(1, Ordering::Relaxed);
+                                PacketFate::Drop
+                            } else {
+                                PacketFate::Deliver
+                            }
+                        }
+                        PacketDirection::Incoming => {
+                            delayed.fetch_add(1, Ordering::Relaxed);
+                            PacketFate::Delay(Duration::from_millis(5))
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+            let client = Endpoint::client_with_socket(client_config, socket, runtime).unwrap();
+
+            let url = format!("https://localhost:{}", server.local_addr().unwrap().port());
+
+            let (client_connection, server_connection) = tokio::join!(client.connect(url), async {
+                server.accept().await.await.unwrap().accept().await
+            });
+
+            let client_connection = client_connection.unwrap();
+            let server_connection = server_connection.unwrap();
+
+            server_connection.send_datagram(b"pong").unwrap();
+            let datagram = client_connection.receive_datagram().await.unwrap();
+            assert_eq!(&*datagram, b"pong");
+
+            assert_eq!(dropped.load(Ordering::Relaxed), 1);
+            assert!(delayed.load(Ordering::Relaxed) > 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connected_pair_streams() {
+        let (_server, server_connection, client_connection) = connected_pair().await;
+
+        let mut send_stream = client_connection.open_uni().await.unwrap().await.unwrap();
+        send_stream.write_all(b"hello").await.unwrap();
+        send_stream.finish().await.unwrap();
+
+        let mut recv_stream = server_connection.accept_uni().await.unwrap();
+        let mut buffer = [0; 5];
+        recv_stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"hello");
+    }
+}
@@ -1,25 +1,46 @@
-use std::error::Error;
 use std::fmt::Debug;
-use std::fmt::Display;
+#[cfg(any(
+    feature = "dangerous-configuration",
+    all(test, feature = "self-signed")
+))]
+use std::net::IpAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use x509_parser::certificate::X509Certificate;
+#[cfg(any(
+    feature = "dangerous-configuration",
+    all(test, feature = "self-signed")
+))]
+use x509_parser::extensions::GeneralName;
 use x509_parser::prelude::FromDer;
 
 /// Error during load operation of certificate.
+#[derive(thiserror::Error, Debug)]
 pub enum CertificateLoadError {
     /// The certificate file does not contain a valid certificate.
+    #[error("no valid certificate to load found")]
     InvalidCertificate,
 
     /// The key file does not contain a valid private key.
+    #[error("no valid private key to load found")]
     InvalidPrivateKey,
 
+    /// The PKCS#12 bundle is malformed or uses an unsupported encryption scheme.
+    #[error("invalid or unsupported PKCS#12 bundle")]
+    InvalidPkcs12,
+
+    /// The password does not match the PKCS#12 bundle.
+    #[error("wrong PKCS#12 password")]
+    WrongPassword,
+
     /// Load operation failed because I/O operation on file.
+    #[error("file ('{}') error", file.display())]
     FileError {
         /// Filename of the operation.
         file: PathBuf,
 
         /// IO error details.
+        #[source]
         error: std::io::Error,
     },
 }
@@ -28,6 +49,8 @@ pub enum CertificateLoadError {
 ///
 /// This error type is used to signal that a certificate in a chain is invalid,
 /// and it provides additional information about the index in the chain.
+#[derive(thiserror::Error, Debug)]
+#[error("invalid certificate (chain index: {0})")]
 pub struct InvalidCertificate(usize);
 
 /// A server TLS certificate.
@@ -134,11 +157,26 @@ impl Certificate {
 
         let cert = rcgen::Certificate::from_params(cert_params).expect("inner params are valid");
 
-        Self::new(
-            vec![cert.serialize_der().expect("valid certificate")],
-            cert.serialize_private_key_der(),
-        )
-        .expect("valid certificate")
+        Self::from_rcgen(&cert).expect("valid certificate")
+    }
+
+    /// Creates a new `Certificate` from an [`rcgen::Certificate`], self-signed.
+    ///
+    /// This avoids a PEM round-trip for certificates generated with `rcgen` (e.g., by test
+    /// infrastructure). The certificate is serialized (DER), and signed with its own key pair.
+    ///
+    /// For certificates signed by a CA (e.g., issued by an ACME flow), serialize the chain
+    /// in DER format and use [`Certificate::new`].
+    ///
+    /// **Note**: this requires the `self-signed` feature, which enables the `rcgen` dependency.
+    #[cfg(feature = "self-signed")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "self-signed")))]
+    pub fn from_rcgen(certificate: &rcgen::Certificate) -> Result<Self, InvalidCertificate> {
+        let der = certificate
+            .serialize_der()
+            .map_err(|_| InvalidCertificate(0))?;
+
+        Self::new(vec![der], certificate.serialize_private_key_der())
     }
 
     /// For each certificate in this chain, computes its corresponding *hash*.
@@ -169,6 +207,8 @@ impl Certificate {
     }
 
     /// Loads a PEM certificates and private key from the filesystem.
+    ///
+    /// For PKCS#12 bundles (`.p12`/`.pfx`), see [`Certificate::load_pkcs12`].
     pub async fn load(
         cert_path: impl AsRef<Path>,
         key_path: impl AsRef<Path>,
@@ -217,61 +257,76 @@ impl Certificate {
         Ok(Self::new(certificates, private_key.0).expect("validated certificate"))
     }
 
-    /// Gets a reference to the certificate data chain associated with this `Certificate`.
+    /// Loads a certificate chain and its private key from a PKCS#12 bundle (`.p12`/`.pfx`)
+    /// on the filesystem.
     ///
-    /// Each certificate is *DER-encoded*.
-    pub fn certificates(&self) -> &[Vec<u8>] {
-        &self.certificates
+    /// See [`Certificate::from_pkcs12`].
+    #[cfg(feature = "pkcs12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pkcs12")))]
+    pub async fn load_pkcs12(
+        path: impl AsRef<Path>,
+        password: &str,
+    ) -> Result<Self, CertificateLoadError> {
+        let bundle = tokio::fs::read(path.as_ref()).await.map_err(|io_error| {
+            CertificateLoadError::FileError {
+                file: path.as_ref().to_path_buf(),
+                error: io_error,
+            }
+        })?;
+
+        Self::from_pkcs12(&bundle, password)
     }
 
-    /// Gets a reference to the private key associated with this `Certificate`.
+    /// Creates a new `Certificate` from a *DER-encoded* PKCS#12 bundle.
     ///
-    /// Each certificate is *DER-encoded*.
-    pub fn private_key(&self) -> &[u8] {
-        &self.private_key
-    }
-}
+    /// The bundle must contain a private key and the certificate matching it (by its
+    /// `localKeyId` attribute), which is placed first in the chain, followed by its issuers.
+    ///
+    /// Parsing is performed by the [`p12-keystore`](https://docs.rs/p12-keystore) crate:
+    /// supported encryption schemes are *PBES2* (e.g., AES-256-CBC, OpenSSL 3 default) and
+    /// the legacy *PBE-SHA1-3DES* and *PBE-SHA1-RC2* schemes.
+    ///
+    /// If the password is wrong, [`CertificateLoadError::WrongPassword`] is returned.
+    #[cfg(feature = "pkcs12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pkcs12")))]
+    pub fn from_pkcs12(bundle: &[u8], password: &str) -> Result<Self, CertificateLoadError> {
+        let keystore =
+            p12_keystore::KeyStore::from_pkcs12(bundle, password).map_err(|error| match error {
+                p12_keystore::error::Error::MacError(_) => CertificateLoadError::WrongPassword,
+                _ => CertificateLoadError::InvalidPkcs12,
+            })?;
 
-impl Error for CertificateLoadError {}
+        let (_, key_chain) = keystore
+            .private_key_chain()
+            .ok_or(CertificateLoadError::InvalidCertificate)?;
 
-impl Debug for CertificateLoadError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidCertificate => write!(f, "no valid certificate to load found"),
-            Self::InvalidPrivateKey => write!(f, "no valid private key to load found"),
-            Self::FileError { file, error } => {
-                write!(f, "file ('{}') error: {:?}", file.display(), error)
-            }
-        }
-    }
-}
+        let certificates = key_chain
+            .chain()
+            .iter()
+            .map(|certificate| certificate.as_der().to_vec())
+            .collect::<Vec<_>>();
 
-impl Display for CertificateLoadError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CertificateLoadError::InvalidCertificate => Debug::fmt(&self, f),
-            CertificateLoadError::InvalidPrivateKey => Debug::fmt(&self, f),
-            CertificateLoadError::FileError { file, error } => {
-                write!(f, "file ('{}') error: {}", file.display(), error)
-            }
+        let private_key = rustls::PrivateKey(key_chain.key().to_vec());
+
+        if rustls::sign::any_supported_type(&private_key).is_err() {
+            return Err(CertificateLoadError::InvalidPrivateKey);
         }
-    }
-}
 
-impl Error for InvalidCertificate {}
+        Self::new(certificates, private_key.0).map_err(|_| CertificateLoadError::InvalidCertificate)
+    }
 
-impl Debug for InvalidCertificate {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "invalid certificate (chain index: {})",
-            self.0
-        ))
+    /// Gets a reference to the certificate data chain associated with this `Certificate`.
+    ///
+    /// Each certificate is *DER-encoded*.
+    pub fn certificates(&self) -> &[Vec<u8>] {
+        &self.certificates
     }
-}
 
-impl Display for InvalidCertificate {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(self, f)
+    /// Gets a reference to the private key associated with this `Certificate`.
+    ///
+    /// Each certificate is *DER-encoded*.
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
     }
 }
 
@@ -352,12 +407,41 @@ impl Debug for Sha256Digest {
 
 #[cfg(feature = "self-signed")]
 #[cfg_attr(docsrs, doc(cfg(feature = "self-signed")))]
-impl Display for Sha256Digest {
+impl std::fmt::Display for Sha256Digest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.fmt_as_dotted_hex(), f)
+        std::fmt::Display::fmt(&self.fmt_as_dotted_hex(), f)
     }
 }
 
+/// Returns the DNS names and IP addresses listed in the subject alternative names
+/// of a *DER-encoded* certificate.
+#[cfg(any(
+    feature = "dangerous-configuration",
+    all(test, feature = "self-signed")
+))]
+pub(crate) fn subject_alt_names(certificate: &[u8]) -> Vec<String> {
+    let Ok((_, certificate)) = X509Certificate::from_der(certificate) else {
+        return Vec::new();
+    };
+
+    let Ok(Some(san)) = certificate.subject_alternative_name() else {
+        return Vec::new();
+    };
+
+    san.value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+            GeneralName::DNSName(name) => Some(name.to_string()),
+            GeneralName::IPAddress(&[a, b, c, d]) => Some(IpAddr::from([a, b, c, d]).to_string()),
+            GeneralName::IPAddress(address) => <[u8; 16]>::try_from(*address)
+                .ok()
+                .map(|address| IpAddr::from(address).to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
 pub use rustls;
 
 #[cfg(test)]
@@ -372,10 +456,79 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn load_error_source() {
+        let Err(error) = Certificate::load("/nonexistent/cert.pem", "/nonexistent/key.pem").await
+        else {
+            panic!("certificate loaded");
+        };
+
+        let source = std::error::Error::source(&error)
+            .and_then(|source| source.downcast_ref::<std::io::Error>())
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+    }
+
     #[cfg(feature = "self-signed")]
     #[test]
     fn valid_self() {
         let cert = Certificate::self_signed(["localhost"]);
         Certificate::new(cert.certificates, cert.private_key).unwrap();
     }
+
+    #[cfg(feature = "self-signed")]
+    #[test]
+    fn san() {
+        let cert = Certificate::self_signed(["localhost", "127.0.0.1", "::1"]);
+        assert_eq!(
+            subject_alt_names(&cert.certificates[0]),
+            ["localhost", "127.0.0.1", "::1"]
+        );
+    }
+
+    #[cfg(feature = "self-signed")]
+    #[test]
+    fn from_rcgen() {
+        let rcgen_certificate =
+            rcgen::generate_simple_self_signed(["localhost".to_string()]).unwrap();
+
+        let certificate = Certificate::from_rcgen(&rcgen_certificate).unwrap();
+
+        assert_eq!(certificate.certificates.len(), 1);
+        assert_eq!(
+            certificate.private_key,
+            rcgen_certificate.serialize_private_key_der()
+        );
+        assert_eq!(
+            subject_alt_names(&certificate.certificates[0]),
+            ["localhost"]
+        );
+    }
+
+    #[cfg(feature = "pkcs12")]
+    #[test]
+    fn pkcs12() {
+        for bundle in [
+            &include_bytes!("../testdata/modern.p12")[..],
+            &include_bytes!("../testdata/legacy.p12")[..],
+        ] {
+            let certificate = Certificate::from_pkcs12(bundle, "wtransport").unwrap();
+
+            assert_eq!(certificate.certificates.len(), 2);
+            assert_eq!(
+                subject_alt_names(&certificate.certificates[0]),
+                ["localhost"]
+            );
+
+            assert!(matches!(
+                Certificate::from_pkcs12(bundle, "wrong"),
+                Err(CertificateLoadError::WrongPassword)
+            ));
+        }
+
+        assert!(matches!(
+            Certificate::from_pkcs12(b"wtransport", "wtransport"),
+            Err(CertificateLoadError::InvalidPkcs12)
+        ));
+    }
 }
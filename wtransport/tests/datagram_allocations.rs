@@ -0,0 +1,106 @@
+//! Heap allocations performed when sending datagrams.
+//!
+//! A counting global allocator records the allocations made by the current thread, so
+//! that the cost of [`Connection::send_datagram`] can be measured in isolation (the
+//! runtime is single-threaded, and the QUIC driver does not run while datagrams are sent).
+
+use bytes::Bytes;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::cell::Cell;
+use wtransport::test_util::connected_pair;
+use wtransport::Connection;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const DATAGRAMS: usize = 64;
+
+/// Upper bound of the allocations performed by sending a single borrowed datagram.
+const MAX_ALLOCATIONS_PER_DATAGRAM: usize = 2;
+
+/// Returns the number of allocations performed by `f` on the current thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+async fn receive_all(connection: &Connection) {
+    for _ in 0..DATAGRAMS {
+        connection.receive_datagram().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn send_datagram_allocations() {
+    let (_server, server_connection, client_connection) = connected_pair().await;
+    let scratch = [0x42; 512];
+
+    // Grows the queues of the QUIC stack, so that they are not reallocated while measuring.
+    for _ in 0..DATAGRAMS {
+        client_connection.send_datagram(scratch).unwrap();
+    }
+    receive_all(&server_connection).await;
+
+    let borrowed = allocations(|| {
+        for _ in 0..DATAGRAMS {
+            client_connection.send_datagram(scratch).unwrap();
+        }
+    });
+    receive_all(&server_connection).await;
+
+    let owned = allocations(|| {
+        for _ in 0..DATAGRAMS {
+            client_connection
+                .send_datagram(Bytes::copy_from_slice(&scratch))
+                .unwrap();
+        }
+    });
+    receive_all(&server_connection).await;
+
+    // The borrowed payload is copied into the buffer queued by the QUIC stack, without
+    // any intermediate allocation: exact counts depend on the QUIC stack internals.
+    assert!(
+        borrowed <= MAX_ALLOCATIONS_PER_DATAGRAM * DATAGRAMS,
+        "{borrowed} allocations for {DATAGRAMS} borrowed datagrams"
+    );
+
+    // An owned copy of the payload is an additional allocation per datagram.
+    assert!(
+        borrowed < owned,
+        "{borrowed} allocations for borrowed datagrams, {owned} for owned ones"
+    );
+}